@@ -40,10 +40,12 @@ export const StaffCanvas: React.FC<StaffCanvasProps> = ({
     onLayoutReady?.(staffLayout);
 
     // Get effective gati and tala: phrase > section > document
+    const annotation = (key: string) => section.annotations?.find(([k]) => k === key)?.[1];
+    const sectionGati = annotation('gati');
     const effectiveGati = phrase.gati || 
-                         (section.annotations?.gati ? parseInt(section.annotations.gati) : documentGati);
+                         (sectionGati ? parseInt(sectionGati) : documentGati);
     const effectiveTala = phrase.tala || 
-                         section.annotations?.tala || 
+                         annotation('tala') || 
                          documentTala;
     
     drawStaff(ctx, staffLayout);
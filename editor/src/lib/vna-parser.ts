@@ -73,8 +73,8 @@ export class VNAParser {
       if (trimmed.startsWith('@')) {
         if (currentSection) {
           const [key, value] = trimmed.substring(1).split(':').map(s => s.trim());
-          currentSection.annotations = currentSection.annotations || {};
-          currentSection.annotations[key] = value;
+          currentSection.annotations = currentSection.annotations || [];
+          currentSection.annotations.push([key, value]);
         }
         return;
       }
@@ -29,9 +29,10 @@ export interface GamakaTemplate {
   description?: string;
 }
 
+// Saved next to the .vna file as `name.gamakas.json`
 export interface CurveData {
   version: string;
-  fileHash: string;
+  fileHash: string;  // SHA-256 of the .vna file's content, in hex
   gamakas: Gamaka[];
   createdAt: string;
   updatedAt: string;
@@ -7,6 +7,8 @@ export interface VNAMetadata {
   composer?: string;
   language?: string;
   key?: string;
+  tuning?: 'equal' | 'just' | 'shruti';
+  tonic_hz?: number;  // Frequency of S from the key, set by the parser
   gati?: number;
 }
 
@@ -17,13 +19,50 @@ export interface VNAPhrase {
   beat_positions?: number[];
   gati?: number;
   tala?: string;
+  raga?: string;
+  sangati?: number;  // Numbered variation of the line before it
+  annotations?: [string, string][];  // Every @key: value directive, in file order
+  sahitya_lines?: VNASahityaLine[];
+  gamakas?: VNAGamaka[];
+}
+
+// Gamaka written inline on a swara: `G~`, `G/`, `G\`, `{R}G`
+export interface VNAGamaka {
+  token: number;  // Index of the swara token
+  unit: number;   // Index of the note among the token's units
+  swara: string;
+  kind: 'kampita' | 'jaru' | 'anuswaram';
+  direction?: 'up' | 'down';  // Jaru only
+  grace?: string;  // Anuswaram only
+}
+
+// Pitch over a phrase, from `VnaParser.phrase_contour`
+export interface VNAContour {
+  tonic_hz: number;  // Frequency of middle S: Hz = tonic_hz * 2^(cents / 1200)
+  points: VNAContourPoint[];
+}
+
+export interface VNAContourPoint {
+  seconds: number;
+  cents: number | null;  // Above middle S; null from the start of a rest
+}
+
+// Further verse (`sahitya[2]:`), script (`sahitya[te]:`) or meaning line
+export interface VNASahityaLine {
+  kind: 'sahitya' | 'meaning';
+  label?: string;
+  tokens: string[];  // Aligned with the swaras, or the free text as one token
+  aligned: boolean;
 }
 
 export interface VNASection {
   name: string;
+  variant?: string;  // Pathantara, from [pallavi@semmangudi]
+  raga?: string;
+  swara_only?: boolean;  // Phrases have no sahitya line
   phrases: VNAPhrase[];
   comments?: string[];
-  annotations?: Record<string, string>;
+  annotations?: [string, string][];  // Every @key: value directive, in file order
 }
 
 export interface VNADocument {
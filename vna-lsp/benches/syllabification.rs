@@ -0,0 +1,105 @@
+//! Sahitya syllabification over a synthetic corpus of varnam-sized files
+//!
+//! Run with `cargo bench --bench syllabification`. Criterion times validating
+//! the corpus with:
+//! - a fresh transliterator per segment and no cache (the original behaviour)
+//! - the shared transliterator without a cache
+//! - the default registry, whose built-in syllabifiers share one cache across
+//!   files; it stays warm between iterations as it does between files
+//!
+//! and reports each, so the three can be compared in one group
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, SamplingMode, Throughput};
+use vna::sahitya_scheme::SahityaScheme;
+use vna::script::Script;
+use vna::syllabifier::{IndicSyllabifier, Syllabifier, SyllabifierRegistry};
+use vna::{parse, validator, VnaDocument};
+
+const FILES: usize = 1000;
+const SECTIONS: usize = 4;
+const PHRASES: usize = 6;
+
+const WORDS: &[&str] = &[
+    "ninnu", "kōri", "yunnānu", "rā", "nikhila", "lōka", "nāyaka", "nannu", "pālimpa",
+    "samayamu", "rā", "nāmīda", "kṟupa", "jūḍa", "sannutāṅga", "śrīnivāsa", "saṅgīta",
+    "vara", "vīṇā", "mṛdu", "pāṇi", "rāma", "kṛṣṇa", "gōpāla", "dēva", "sundara",
+];
+
+/// Swara token for each sahitya word, with enough units to match any split
+const SWARAS: &str = "SRGMPDN";
+
+/// The original implementation constructed a transliterator for every segment
+struct FreshTransliteratorSyllabifier(IndicSyllabifier);
+
+impl Syllabifier for FreshTransliteratorSyllabifier {
+    fn syllabify(&self, segment: &str, scheme: SahityaScheme) -> Vec<String> {
+        let _transliterator = shlesha::Shlesha::new();
+        self.0.syllabify(segment, scheme)
+    }
+}
+
+fn synthetic_file(index: usize) -> String {
+    let mut content = format!(
+        "---\ntitle: \"Synthetic {}\"\nraga: \"mohanam\"\ntala: \"+234+0+0\"\nlanguage: \"telugu\"\n---\n\n",
+        index
+    );
+
+    for section in 0..SECTIONS {
+        content.push_str(&format!("[section{}]\n", section));
+        for phrase in 0..PHRASES {
+            let mut swaras = Vec::new();
+            let mut sahitya = Vec::new();
+            for slot in 0..8 {
+                let word = WORDS[(index * 7 + section * 5 + phrase * 3 + slot) % WORDS.len()];
+                swaras.push(SWARAS.to_string());
+                sahitya.push(word.to_string());
+            }
+            content.push_str(&format!("{} | {} ||\n", swaras[..4].join(" "), swaras[4..].join(" ")));
+            content.push_str(&format!("{} | {} ||\n\n", sahitya[..4].join(" "), sahitya[4..].join(" ")));
+        }
+    }
+
+    content
+}
+
+fn validate_all(corpus: &[VnaDocument], registry_for_file: &impl Fn() -> SyllabifierRegistry) -> usize {
+    let mut issues = 0;
+    for document in corpus {
+        let registry = registry_for_file();
+        issues += validator::validate_with(document, &registry).unwrap().len();
+    }
+    issues
+}
+
+fn syllabification(c: &mut Criterion) {
+    let corpus: Vec<VnaDocument> = (0..FILES)
+        .map(|i| parse(&synthetic_file(i)).expect("synthetic file parses"))
+        .collect();
+    assert!(
+        validate_all(&corpus, &SyllabifierRegistry::default) > 0,
+        "synthetic corpus should produce unit mismatches"
+    );
+
+    let mut group = c.benchmark_group("validate");
+    // A pass over the corpus takes seconds, so take criterion's fewest samples
+    // of one pass each rather than shrinking the corpus
+    group.sample_size(10);
+    group.sampling_mode(SamplingMode::Flat);
+    group.throughput(Throughput::Elements((FILES * SECTIONS * PHRASES * 8) as u64));
+
+    let fresh = || SyllabifierRegistry::empty(FreshTransliteratorSyllabifier(IndicSyllabifier::new(Script::Telugu)));
+    let uncached = || SyllabifierRegistry::empty(IndicSyllabifier::new(Script::Telugu));
+    group.bench_function(BenchmarkId::new("fresh transliterator", FILES), |b| {
+        b.iter(|| validate_all(&corpus, &fresh))
+    });
+    group.bench_function(BenchmarkId::new("shared transliterator", FILES), |b| {
+        b.iter(|| validate_all(&corpus, &uncached))
+    });
+    group.bench_function(BenchmarkId::new("shared transliterator and cache", FILES), |b| {
+        b.iter(|| validate_all(&corpus, &SyllabifierRegistry::default))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, syllabification);
+criterion_main!(benches);
@@ -0,0 +1,303 @@
+use crate::lyrics::{WORD_BREAK, WORD_JOIN};
+use crate::sahitya_parser::parse_sahitya_token_with_registry;
+use crate::sahitya_scheme::SahityaScheme;
+use crate::swara_parser::{is_note, parse_swara_units, split_token_gati};
+use crate::syllabifier::SyllabifierRegistry;
+use anyhow::{anyhow, Result};
+
+/// A sahitya line generated from plain lyrics, one token per swara token
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alignment {
+    pub sahitya: Vec<String>,
+    /// Placements chosen heuristically that should be checked by hand
+    pub notes: Vec<AlignmentNote>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlignmentNote {
+    /// Index of the swara token
+    pub token: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Slot {
+    /// A note held by a following sustain
+    LongNote,
+    /// A note in a token that starts with a note, i.e. on the beat
+    StrongNote,
+    Note,
+    Sustain,
+    Rest,
+}
+
+struct Syllable {
+    text: String,
+    starts_word: bool,
+}
+
+/// Align plain lyrics (words separated by spaces) to swara tokens
+///
+/// Syllables go on note onsets, preferring held notes and then notes that
+/// start a token; with fewer syllables than notes, the remaining notes become
+/// melismas on the preceding syllable. Sustains only take syllables when
+/// there are more syllables than notes. Every other unit is filled with `-`,
+/// and a `_` word break is written before each word after the first.
+pub fn align(
+    swaras: &[String],
+    lyrics: &str,
+    registry: &SyllabifierRegistry,
+    language: Option<&str>,
+    scheme: SahityaScheme,
+) -> Result<Alignment> {
+    let syllables = syllabify_lyrics(lyrics, registry, language, scheme);
+    if syllables.is_empty() {
+        return Err(anyhow!("No lyrics to align"));
+    }
+
+    // (token, unit, slot) for every swara unit in the line
+    let mut slots = Vec::new();
+    let mut token_units = Vec::new();
+    for (token_index, token) in swaras.iter().enumerate() {
+        let units = parse_swara_units(split_token_gati(token).0);
+        let starts_with_note = units.first().is_some_and(|u| is_note(u));
+        for (unit_index, unit) in units.iter().enumerate() {
+            let slot = match unit.as_str() {
+                "," => Slot::Sustain,
+                "-" => Slot::Rest,
+                _ if unit_index == 0 && starts_with_note => Slot::StrongNote,
+                _ => Slot::Note,
+            };
+            slots.push((token_index, unit_index, slot));
+        }
+        token_units.push(units.len());
+    }
+    for i in 0..slots.len().saturating_sub(1) {
+        if is_note_slot(slots[i].2) && slots[i + 1].2 == Slot::Sustain {
+            slots[i].2 = Slot::LongNote;
+        }
+    }
+
+    let note_count = slots.iter().filter(|(_, _, s)| is_note_slot(*s)).count();
+    let sustain_count = slots.iter().filter(|(_, _, s)| *s == Slot::Sustain).count();
+    if syllables.len() > note_count + sustain_count {
+        return Err(anyhow!(
+            "{} syllables but only {} notes and {} sustains to place them on",
+            syllables.len(),
+            note_count,
+            sustain_count
+        ));
+    }
+
+    // Rank slots by preference; the line's first note always takes the first syllable
+    let first_note = slots.iter().position(|(_, _, s)| is_note_slot(*s));
+    let mut ranked: Vec<usize> = first_note.into_iter().collect();
+    for preferred in [Slot::LongNote, Slot::StrongNote, Slot::Note, Slot::Sustain] {
+        ranked.extend(
+            (0..slots.len()).filter(|&i| slots[i].2 == preferred && Some(i) != first_note),
+        );
+    }
+    let mut chosen: Vec<usize> = ranked.into_iter().take(syllables.len()).collect();
+    chosen.sort_unstable();
+
+    let mut units: Vec<Vec<String>> = token_units.iter().map(|&n| vec!["-".to_string(); n]).collect();
+    let mut word_breaks: Vec<Vec<bool>> = token_units.iter().map(|&n| vec![false; n]).collect();
+    for (syllable, &slot_index) in syllables.iter().zip(&chosen) {
+        let (token, unit, _) = slots[slot_index];
+        units[token][unit] = syllable.text.clone();
+        word_breaks[token][unit] = syllable.starts_word;
+    }
+
+    let mut notes = Vec::new();
+    let mut last_syllable: Option<&str> = None;
+    let mut slot_iter = slots.iter().enumerate().peekable();
+    for (token_index, swara) in swaras.iter().enumerate() {
+        let mut melisma = 0;
+        let mut on_sustain = Vec::new();
+        while let Some((i, &(token, unit, slot))) = slot_iter.peek().copied() {
+            if token != token_index {
+                break;
+            }
+            slot_iter.next();
+            if chosen.binary_search(&i).is_ok() {
+                last_syllable = Some(units[token][unit].as_str());
+                if slot == Slot::Sustain {
+                    on_sustain.push(units[token][unit].clone());
+                }
+            } else if is_note_slot(slot) {
+                melisma += 1;
+            }
+        }
+
+        if melisma > 0 {
+            notes.push(AlignmentNote {
+                token: token_index,
+                message: format!(
+                    "'{}': {} note(s) sung as a melisma on '{}'",
+                    swara,
+                    melisma,
+                    last_syllable.unwrap_or("")
+                ),
+            });
+        }
+        for syllable in on_sustain {
+            notes.push(AlignmentNote {
+                token: token_index,
+                message: format!("'{}': '{}' placed on a sustain", swara, syllable),
+            });
+        }
+    }
+
+    let sahitya = units
+        .iter()
+        .zip(&word_breaks)
+        .map(|(units, breaks)| encode_token(units, breaks, registry, language, scheme))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Alignment { sahitya, notes })
+}
+
+fn is_note_slot(slot: Slot) -> bool {
+    matches!(slot, Slot::LongNote | Slot::StrongNote | Slot::Note)
+}
+
+fn syllabify_lyrics(
+    lyrics: &str,
+    registry: &SyllabifierRegistry,
+    language: Option<&str>,
+    scheme: SahityaScheme,
+) -> Vec<Syllable> {
+    let mut syllables = Vec::new();
+
+    for word in lyrics.split_whitespace() {
+        let units = parse_sahitya_token_with_registry(word, registry, language, scheme);
+        let mut starts_word = true;
+        for unit in units.into_iter().filter(|u| u != "-") {
+            syllables.push(Syllable { text: unit, starts_word });
+            starts_word = false;
+        }
+    }
+
+    syllables
+}
+
+/// Write a token's units so that they parse back to exactly those units
+/// Each word is written plainly when automatic syllabification agrees, and
+/// with backticks around its first syllable otherwise (`` `nin`-nu- ``)
+/// `word_breaks` marks the syllables starting a word; a token whose first
+/// syllable doesn't is joined to the one before (`=ri---`)
+pub(crate) fn encode_token(
+    units: &[String],
+    word_breaks: &[bool],
+    registry: &SyllabifierRegistry,
+    language: Option<&str>,
+    scheme: SahityaScheme,
+) -> Result<String> {
+    let mut token = String::new();
+    let first_syllable = units.iter().position(|u| u != "-");
+    if first_syllable.is_some_and(|first| !word_breaks[first]) {
+        token.push(WORD_JOIN);
+    }
+
+    // Words after the first in the token follow a word break
+    let mut start = 0;
+    while start < units.len() {
+        let end = (start + 1..units.len())
+            .find(|&i| word_breaks[i] && first_syllable.is_some_and(|first| i > first))
+            .unwrap_or(units.len());
+        if start > 0 {
+            token.push(WORD_BREAK);
+        }
+        token.push_str(&encode_piece(&units[start..end], registry, language, scheme));
+        start = end;
+    }
+
+    let parsed = parse_sahitya_token_with_registry(&token, registry, language, scheme);
+    if parsed != units {
+        return Err(anyhow!("Cannot write syllables {:?} as a single sahitya token", units));
+    }
+    Ok(token)
+}
+
+fn encode_piece(
+    units: &[String],
+    registry: &SyllabifierRegistry,
+    language: Option<&str>,
+    scheme: SahityaScheme,
+) -> String {
+    let plain = units.concat();
+    if parse_sahitya_token_with_registry(&plain, registry, language, scheme) == units {
+        return plain;
+    }
+
+    let mut piece = String::new();
+    let mut wrapped = false;
+    let mut previous_is_syllable = false;
+    for unit in units {
+        if unit == "-" {
+            piece.push('-');
+            previous_is_syllable = false;
+            continue;
+        }
+        if !wrapped {
+            piece.push('`');
+            piece.push_str(unit);
+            piece.push('`');
+            wrapped = true;
+        } else {
+            if previous_is_syllable && !piece.ends_with('`') {
+                piece.push('`');
+            }
+            piece.push_str(unit);
+        }
+        previous_is_syllable = true;
+    }
+    piece
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(line: &str) -> Vec<String> {
+        crate::parser::parse_notation_line(line).0
+    }
+
+    fn align_telugu(swaras: &str, lyrics: &str) -> Alignment {
+        align(&tokens(swaras), lyrics, SyllabifierRegistry::shared(), Some("telugu"), SahityaScheme::Iso).unwrap()
+    }
+
+    #[test]
+    fn test_align_on_onsets() {
+        let alignment = align_telugu("G,G, R,,, | SSRR GGRR ||", "nin`nu kōri");
+        assert_eq!(alignment.sahitya, vec!["`nin`-nu-", "kō---", "=ri---", "----"]);
+        // "ri" takes the first note of SSRR; the rest of the line is a melisma on it
+        assert_eq!(alignment.notes.len(), 2);
+        assert_eq!(alignment.notes[0].token, 2);
+        assert!(alignment.notes[1].message.contains("melisma on 'ri'"));
+    }
+
+    #[test]
+    fn test_align_round_trips_through_lyrics() {
+        let alignment = align_telugu("SRG SRG | SRG SRG ||", "sa ri ga ma pa da ni sa");
+        let line = alignment.sahitya.join(" ");
+        let doc = crate::parser::parse(&format!(
+            "---\ntitle: \"T\"\nraga: \"r\"\ntala: \"+234+0+0\"\n---\n\n[p]\nSRG SRG SRG SRG ||\n{} ||\n",
+            line
+        ))
+        .unwrap();
+        assert!(crate::validator::validate(&doc).unwrap().is_empty());
+        assert_eq!(crate::lyrics::extract(&doc)[0].lines, vec!["sa ri ga ma pa da ni sa"]);
+    }
+
+    #[test]
+    fn test_align_on_sustains() {
+        let alignment = align_telugu("S,R, ||", "a ri ka");
+        assert_eq!(alignment.sahitya, vec!["a_ri_ka-"]);
+        assert_eq!(alignment.notes.len(), 1);
+        assert!(alignment.notes[0].message.contains("'ri' placed on a sustain"));
+
+        let error = align(&tokens("S, ||"), "a ri ka", SyllabifierRegistry::shared(), None, SahityaScheme::Iso);
+        assert!(error.is_err());
+    }
+}
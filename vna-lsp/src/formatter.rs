@@ -1,3 +1,5 @@
+use crate::parser::TYPED_DIRECTIVES;
+use crate::repeat;
 use crate::types::*;
 use anyhow::Result;
 
@@ -6,6 +8,13 @@ pub fn format(document: &VnaDocument) -> Result<String> {
     formatter.format(document)
 }
 
+/// Format a single phrase with aligned swara and sahitya columns
+pub fn format_phrase(phrase: &Phrase) -> Result<String> {
+    let mut formatter = VnaFormatter::new();
+    formatter.format_phrase(phrase, false)?;
+    Ok(formatter.output)
+}
+
 struct VnaFormatter {
     output: String,
 }
@@ -64,6 +73,10 @@ impl VnaFormatter {
         if let Some(gati) = metadata.gati {
             self.output.push_str(&format!("gati: {}\n", gati));
         }
+
+        if let Some(eduppu) = metadata.eduppu {
+            self.output.push_str(&format!("eduppu: \"{}\"\n", eduppu));
+        }
         
         if let Some(comp_type) = &metadata.composition_type {
             self.output.push_str(&format!("type: \"{}\"\n", comp_type));
@@ -73,6 +86,10 @@ impl VnaFormatter {
             self.output.push_str(&format!("key: \"{}\"\n", key));
         }
         
+        if let Some(tuning) = &metadata.tuning {
+            self.output.push_str(&format!("tuning: \"{}\"\n", tuning));
+        }
+        
         if let Some(default_octave) = &metadata.default_octave {
             self.output.push_str(&format!("default_octave: \"{}\"\n", default_octave));
         }
@@ -91,12 +108,10 @@ impl VnaFormatter {
 
     fn format_section(&mut self, section: &Section) -> Result<()> {
         // Section header
-        self.output.push_str(&format!("[{}]\n", section.name));
+        self.output.push_str(&format!("[{}]\n", section.header()));
         
-        // Output section-level gati if present
-        if let Some(gati) = section.gati {
-            self.output.push_str(&format!("@gati: {}\n", gati));
-        }
+        // Output section-level directives if present
+        self.format_directives(ordered_directives(section_directive_values(section), &section.directives));
         
         // Output any section-level comments
         for comment in &section.comments {
@@ -113,46 +128,79 @@ impl VnaFormatter {
             if i > 0 && phrase.preceding_comments.is_empty() {
                 self.output.push('\n'); // Blank line between phrases
             }
-            self.format_phrase(phrase)?;
+            self.format_phrase(phrase, section.swara_only)?;
         }
 
         Ok(())
     }
 
-    fn format_phrase(&mut self, phrase: &Phrase) -> Result<()> {
-        // Output line-level gati if present
-        if let Some(gati) = phrase.gati {
-            self.output.push_str(&format!("@gati: {}\n", gati));
-        }
+    fn format_phrase(&mut self, phrase: &Phrase, swara_only: bool) -> Result<()> {
+        // Output line-level directives if present
+        self.format_directives(ordered_directives(line_directive_values(phrase), &phrase.directives));
         
         // Don't pad - preserve original token structure
         let swaras = &phrase.swaras;
         let sahitya = &phrase.sahitya;
+        let aligned: Vec<&SahityaLine> = phrase.sahitya_lines.iter().filter(|l| l.is_aligned()).collect();
         
         // Calculate column widths for alignment
         let mut col_widths = Vec::new();
         for i in 0..swaras.len() {
             let swara_width = swaras.get(i).map(|s| s.chars().count()).unwrap_or(0);
             let sahitya_width = sahitya.get(i).map(|s| s.chars().count()).unwrap_or(0);
+            let extra_width = aligned
+                .iter()
+                .filter_map(|l| l.syllables.get(i))
+                .map(|s| s.chars().count())
+                .max()
+                .unwrap_or(0);
             
-            let max_width = swara_width.max(sahitya_width);
+            let max_width = swara_width.max(sahitya_width).max(extra_width);
             col_widths.push(max_width);
         }
 
+        // Labelled lines start after their tag, so indent the others to keep columns aligned
+        let indent = aligned.iter().map(|l| l.tag().chars().count() + 2).max().unwrap_or(0);
+
         // Format swara line
+        self.output.push_str(&" ".repeat(indent));
         self.format_notation_line(swaras, &col_widths, &phrase.beat_positions)?;
         
         // Format sahitya line
-        self.format_notation_line(sahitya, &col_widths, &phrase.beat_positions)?;
+        if !swara_only {
+            self.output.push_str(&" ".repeat(indent));
+            self.format_notation_line(sahitya, &col_widths, &phrase.beat_positions)?;
+        }
         
         // Add phrase analysis if present
         if let Some(analysis) = &phrase.phrase_analysis {
             self.output.push_str(&format!("phrases = {}\n", analysis));
         }
 
+        // Further verses, scripts and meanings in their written order
+        for line in &phrase.sahitya_lines {
+            match &line.text {
+                Some(text) => self.output.push_str(&format!("{}: {}\n", line.tag(), text)),
+                None => {
+                    self.output.push_str(&format!("{:<indent$}", format!("{}:", line.tag())));
+                    self.format_notation_line(&line.syllables, &col_widths, &phrase.beat_positions)?;
+                }
+            }
+        }
+
         Ok(())
     }
 
+    fn format_directives(&mut self, directives: Vec<(String, String)>) {
+        for (key, value) in directives {
+            if value.is_empty() {
+                self.output.push_str(&format!("@{}\n", key));
+            } else {
+                self.output.push_str(&format!("@{}: {}\n", key, value));
+            }
+        }
+    }
+
     fn format_notation_line(&mut self, elements: &[String], col_widths: &[usize], beat_positions: &[usize]) -> Result<()> {
         let mut formatted_elements = Vec::new();
         let mut current_pos = 0;
@@ -205,6 +253,9 @@ pub fn format_preserve_beats(document: &VnaDocument) -> Result<String> {
     if let Some(key) = &document.metadata.key {
         output.push_str(&format!("key: \"{}\"\n", key));
     }
+    if let Some(tuning) = &document.metadata.tuning {
+        output.push_str(&format!("tuning: \"{}\"\n", tuning));
+    }
     
     output.push_str("---\n\n");
 
@@ -214,7 +265,10 @@ pub fn format_preserve_beats(document: &VnaDocument) -> Result<String> {
             output.push('\n');
         }
         
-        output.push_str(&format!("[{}]\n", section.name));
+        output.push_str(&format!("[{}]\n", section.header()));
+        if section.swara_only {
+            output.push_str("@swara-only\n");
+        }
         
         for phrase in &section.phrases {
             // Simple join with spaces - preserves original structure
@@ -222,7 +276,15 @@ pub fn format_preserve_beats(document: &VnaDocument) -> Result<String> {
             let sahitya_line = phrase.sahitya.join(" ") + " ||"; 
             
             output.push_str(&format!("{}\n", swara_line));
-            output.push_str(&format!("{}\n", sahitya_line));
+            if !section.swara_only {
+                output.push_str(&format!("{}\n", sahitya_line));
+            }
+            for line in &phrase.sahitya_lines {
+                match &line.text {
+                    Some(text) => output.push_str(&format!("{}: {}\n", line.tag(), text)),
+                    None => output.push_str(&format!("{}: {} ||\n", line.tag(), line.syllables.join(" "))),
+                }
+            }
             output.push('\n');
         }
     }
@@ -230,6 +292,96 @@ pub fn format_preserve_beats(document: &VnaDocument) -> Result<String> {
     Ok(output)
 }
 
+/// Mark sections whose sahitya lines are all dashes `@swara-only`, dropping
+/// those lines, and return the names of the sections changed
+pub fn migrate_swara_only(document: &mut VnaDocument) -> Vec<String> {
+    let mut migrated = Vec::new();
+    for section in document.sections.iter_mut().filter(|s| has_dash_only_sahitya(s)) {
+        section.swara_only = true;
+        for phrase in &mut section.phrases {
+            phrase.sahitya.clear();
+        }
+        migrated.push(section.name.clone());
+    }
+    migrated
+}
+
+/// A section with sahitya lines made only of dashes, there just to pair with
+/// the swaras; further words lines mean the section is sung after all
+pub fn has_dash_only_sahitya(section: &Section) -> bool {
+    let dashes = |token: &String| {
+        let tokens = match repeat::parse_group(token) {
+            Ok(group) => group.tokens,
+            Err(_) => vec![token.clone()],
+        };
+        tokens.iter().all(|t| t.chars().all(|c| c == '-'))
+    };
+    !section.swara_only
+        && !section.phrases.is_empty()
+        && section
+            .phrases
+            .iter()
+            .all(|p| p.sahitya_lines.is_empty() && p.sahitya.iter().all(dashes))
+}
+
+/// The typed directives that are set, as written in a file
+pub fn directive_values(
+    gati: Option<u8>,
+    tala: &Option<String>,
+    raga: &Option<String>,
+    tempo: Option<Tempo>,
+    kalam: Option<u8>,
+) -> Vec<(&'static str, String)> {
+    let mut values = Vec::new();
+    if let Some(gati) = gati {
+        values.push(("gati", gati.to_string()));
+    }
+    if let Some(tala) = tala {
+        values.push(("tala", format!("\"{}\"", tala)));
+    }
+    if let Some(raga) = raga {
+        values.push(("raga", format!("\"{}\"", raga)));
+    }
+    if let Some(tempo) = tempo {
+        values.push(("tempo", tempo.to_string()));
+    }
+    if let Some(kalam) = kalam {
+        values.push(("kalam", kalam.to_string()));
+    }
+    values
+}
+
+/// The typed directives of a section, its eduppu and `@swara-only` last
+pub fn section_directive_values(section: &Section) -> Vec<(&'static str, String)> {
+    let mut values = directive_values(section.gati, &section.tala, &section.raga, section.tempo, section.kalam);
+    values.extend(section.eduppu.map(|e| ("eduppu", e.to_string())));
+    if section.swara_only {
+        values.push(("swara-only", String::new()));
+    }
+    values
+}
+
+/// The typed directives of a line, its sangati number first
+pub fn line_directive_values(phrase: &Phrase) -> Vec<(&'static str, String)> {
+    let values = directive_values(phrase.gati, &phrase.tala, &phrase.raga, phrase.tempo, phrase.kalam);
+    phrase.sangati.map(|n| ("sangati", n.to_string())).into_iter().chain(values).collect()
+}
+
+/// Every directive in the order written, typed ones with the values they
+/// have now and dropped once unset; typed ones set since come first
+pub fn ordered_directives(known: Vec<(&'static str, String)>, written: &[(String, String)]) -> Vec<(String, String)> {
+    let mut known = known;
+    let mut ordered = Vec::new();
+    for (key, value) in written {
+        if !TYPED_DIRECTIVES.contains(&key.as_str()) {
+            ordered.push((key.clone(), value.clone()));
+        } else if let Some(i) = known.iter().position(|(k, _)| k == key) {
+            ordered.push((key.clone(), known.remove(i).1));
+        }
+    }
+    known.into_iter().map(|(key, value)| (key.to_string(), value)).chain(ordered).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -260,6 +412,103 @@ ni nn | ukō- ||
         assert_eq!(doc.sections.len(), doc2.sections.len());
     }
 
+    #[test]
+    fn test_format_keeps_directive_order() {
+        let original = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+@tempo: 72
+@source: Book of varnams, p. 12
+@fingering: 1-2-1-3
+@bowing: down
+SRGR GRSR | PDSR SRGR ||
+---- ---- | ---- ---- ||
+
+@kalam: 2
+@zz-last: first
+@aa-first: second
+SRGR GRSR | PDSR SRGR ||
+---- ---- | ---- ---- ||
+"#;
+
+        // Directives come back in the order they were written
+        let doc = parse(original).unwrap();
+        assert_eq!(format(&doc).unwrap(), original);
+
+        // Typed ones included, wherever they are
+        let unknown_first = original.replace(
+            "@tempo: 72\n@source: Book of varnams, p. 12\n",
+            "@source: Book of varnams, p. 12\n@eduppu: 1/2\n@tempo: 72\n",
+        );
+        let doc = parse(&unknown_first).unwrap();
+        assert_eq!(format(&doc).unwrap(), unknown_first);
+
+        // A typed directive set since parsing comes first, and one unset goes
+        let mut doc = doc;
+        doc.sections[0].gati = Some(3);
+        doc.sections[0].tempo = None;
+        let formatted = format(&doc).unwrap();
+        assert!(formatted.contains("[pallavi]\n@gati: 3\n@source: Book of varnams, p. 12\n@eduppu: 1/2\n@fingering"), "{}", formatted);
+    }
+
+    #[test]
+    fn test_format_keeps_gamakas() {
+        let original = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+{R}G~,,, P/,D\, |  S,,, ||
+ni--- kō--- | sa--- ||
+"#;
+
+        let doc = parse(original).unwrap();
+        let formatted = format(&doc).unwrap();
+        assert!(formatted.contains("{R}G~,,, P/,D\\, | S,,,"), "{}", formatted);
+        assert_eq!(parse(&formatted).unwrap().sections[0].phrases[0].swaras, doc.sections[0].phrases[0].swaras);
+    }
+
+    #[test]
+    fn test_migrate_swara_only() {
+        let original = r#"---
+title: "Test Varnam"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+G,G, R,,, ||
+nin- kō-- ||
+
+[muktayisvaram]
+G,RG (RSR,)x2 ||
+---- (----)x2 ||
+
+SRGR GRSR ||
+---- ---- ||
+"#;
+
+        let mut doc = parse(original).unwrap();
+        assert_eq!(migrate_swara_only(&mut doc), vec!["muktayisvaram"]);
+        assert!(!doc.sections[0].swara_only);
+
+        let formatted = format(&doc).unwrap();
+        assert!(formatted.ends_with("[muktayisvaram]\n@swara-only\nG,RG (RSR,)x2 ||\n\nSRGR GRSR ||\n"), "{}", formatted);
+        let migrated = parse(&formatted).unwrap();
+        assert!(migrated.sections[1].swara_only);
+        assert!(migrated.sections[1].phrases[1].sahitya.is_empty());
+        assert_eq!(format(&migrated).unwrap(), formatted);
+
+        // Already migrated
+        assert!(migrate_swara_only(&mut doc).is_empty());
+    }
+
     #[test]
     fn test_format_preserve_beats() {
         let content = r#"---
@@ -0,0 +1,247 @@
+use crate::repeat;
+use crate::swara_parser::{parse_swara_units, split_token_gati};
+use crate::types::*;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Gamaka curves drawn in the editor for a `.vna` file, kept next to it as
+/// `name.gamakas.json` (`CurveData` in `editor/src/types/gamaka.ts`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurveData {
+    pub version: String,
+    /// SHA-256 of the `.vna` file the curves were drawn on, in hex
+    pub file_hash: String,
+    pub gamakas: Vec<GamakaCurve>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// One drawn curve, placed by section and phrase index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GamakaCurve {
+    pub id: String,
+    pub points: Vec<CurvePoint>,
+    #[serde(rename = "type")]
+    pub kind: CurveKind,
+    /// Swaras the curve runs between, as written; empty when not set
+    pub swara_start: String,
+    pub swara_end: String,
+    pub phrase_index: usize,
+    /// Section header: `pallavi`, `pallavi@semmangudi`
+    pub section_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub markers: Option<CurveMarkers>,
+}
+
+/// A point in the editor's phrase canvas, `y` growing downwards
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CurvePoint {
+    pub x: f64,
+    pub y: f64,
+    pub pressure: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CurveKind {
+    Kampita,
+    Andolita,
+    Jaru,
+    Tribhinna,
+    Custom,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurveMarkers {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+}
+
+impl CurveKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CurveKind::Kampita => "kampita",
+            CurveKind::Andolita => "andolita",
+            CurveKind::Jaru => "jaru",
+            CurveKind::Tribhinna => "tribhinna",
+            CurveKind::Custom => "custom",
+        }
+    }
+}
+
+impl CurveData {
+    /// Curves drawn on a phrase of a section
+    pub fn for_phrase<'a>(&'a self, section: &Section, phrase_index: usize) -> impl Iterator<Item = &'a GamakaCurve> {
+        let header = section.header();
+        self.gamakas
+            .iter()
+            .filter(move |g| g.section_name == header && g.phrase_index == phrase_index)
+    }
+}
+
+/// Where the curves for a `.vna` file are kept: `song.vna` → `song.gamakas.json`
+pub fn sidecar_path(file: &Path) -> PathBuf {
+    file.with_extension("gamakas.json")
+}
+
+/// The `fileHash` for a file's content
+pub fn file_hash(content: &str) -> String {
+    Sha256::digest(content.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn parse(json: &str) -> Result<CurveData> {
+    serde_json::from_str(json).map_err(|e| anyhow!("Invalid gamaka curves: {}", e))
+}
+
+/// The curves kept next to a `.vna` file, if it has any
+pub fn load(file: &Path) -> Result<Option<CurveData>> {
+    let path = sidecar_path(file);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json = std::fs::read_to_string(&path).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    parse(&json).map(Some).map_err(|e| anyhow!("{}: {}", path.display(), e))
+}
+
+/// Check that the curves still fit the notation: every section, phrase and
+/// swara they name must exist, and the file must be the one they were drawn on
+pub fn check(curves: &CurveData, document: &VnaDocument, content: &str) -> Vec<ValidationIssue> {
+    check_hashed(curves, document, &file_hash(content))
+}
+
+/// `check` with the file given by its hash, for callers that keep it
+pub fn check_hashed(curves: &CurveData, document: &VnaDocument, hash: &str) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let mut warn = |line: usize, message: String, code: &str| {
+        issues.push(ValidationIssue {
+            severity: Severity::Warning,
+            message,
+            line,
+            column: None,
+            code: Some(code.to_string()),
+            range: None,
+        });
+    };
+
+    if curves.file_hash != hash {
+        warn(
+            1,
+            "Gamaka curves were drawn on another version of this file; check them against the notation".to_string(),
+            "stale_gamaka_curves",
+        );
+    }
+
+    for curve in &curves.gamakas {
+        let Some(section) = document.sections.iter().find(|s| s.header() == curve.section_name) else {
+            warn(
+                1,
+                format!("Gamaka curve '{}' is on [{}], which the file doesn't have", curve.id, curve.section_name),
+                "orphaned_gamaka_curve",
+            );
+            continue;
+        };
+        let Some(phrase) = section.phrases.get(curve.phrase_index) else {
+            warn(
+                section.line_number,
+                format!(
+                    "Gamaka curve '{}' is on line {} of [{}], which has {} lines",
+                    curve.id,
+                    curve.phrase_index + 1,
+                    curve.section_name,
+                    section.phrases.len()
+                ),
+                "orphaned_gamaka_curve",
+            );
+            continue;
+        };
+
+        // Swaras are looked for in the written-out form of any repeat groups
+        let swaras = repeat::expand_phrase(phrase).map(|p| p.swaras).unwrap_or_default();
+        let units: Vec<String> = swaras.iter().flat_map(|t| parse_swara_units(split_token_gati(t).0)).collect();
+        for swara in [&curve.swara_start, &curve.swara_end] {
+            if !swara.is_empty() && !units.contains(swara) {
+                warn(
+                    phrase.line_number,
+                    format!("Gamaka curve '{}' runs from or to '{}', which the line doesn't have", curve.id, swara),
+                    "orphaned_gamaka_curve",
+                );
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    const CONTENT: &str = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+G,G, R,,, ||
+ni-- kō-- ||
+"#;
+
+    fn curve(section: &str, phrase_index: usize, swara_start: &str) -> String {
+        format!(
+            r#"{{"id": "gamaka_1", "points": [{{"x": 40, "y": 200, "pressure": 0.5}}], "type": "kampita",
+                "swaraStart": "{}", "swaraEnd": "", "phraseIndex": {}, "sectionName": "{}"}}"#,
+            swara_start, phrase_index, section
+        )
+    }
+
+    fn curves(hash: &str, gamakas: &[String]) -> CurveData {
+        parse(&format!(
+            r#"{{"version": "1.0", "fileHash": "{}", "gamakas": [{}],
+                "createdAt": "2024-01-01T00:00:00Z", "updatedAt": "2024-01-01T00:00:00Z"}}"#,
+            hash,
+            gamakas.join(", ")
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_sidecar_path_and_hash() {
+        assert_eq!(sidecar_path(Path::new("data/song.vna")), PathBuf::from("data/song.gamakas.json"));
+        assert_eq!(file_hash("abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+
+    #[test]
+    fn test_check_curves() {
+        let doc = parser::parse(CONTENT).unwrap();
+        let hash = file_hash(CONTENT);
+
+        let fitting = curves(&hash, &[curve("pallavi", 0, "G")]);
+        assert_eq!(fitting.gamakas[0].kind, CurveKind::Kampita);
+        assert!(check(&fitting, &doc, CONTENT).is_empty());
+        assert_eq!(fitting.for_phrase(&doc.sections[0], 0).count(), 1);
+
+        let edited = CONTENT.replace("R,,,", "RSR,");
+        let codes = |issues: Vec<ValidationIssue>| -> Vec<(usize, String)> {
+            issues.into_iter().map(|i| (i.line, i.code.unwrap())).collect()
+        };
+        assert_eq!(codes(check(&fitting, &doc, &edited)), vec![(1, "stale_gamaka_curves".to_string())]);
+
+        let orphaned = curves(&hash, &[curve("charanam", 0, ""), curve("pallavi", 3, ""), curve("pallavi", 0, "N")]);
+        assert_eq!(
+            codes(check(&orphaned, &doc, CONTENT)),
+            vec![
+                (1, "orphaned_gamaka_curve".to_string()),
+                (7, "orphaned_gamaka_curve".to_string()),
+                (8, "orphaned_gamaka_curve".to_string()),
+            ]
+        );
+    }
+}
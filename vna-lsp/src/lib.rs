@@ -4,6 +4,23 @@ pub mod parser;
 pub mod validator;
 pub mod formatter;
 pub mod sahitya_parser;
+pub mod swara_parser;
+pub mod sahitya_scheme;
+pub mod script;
+pub mod syllabifier;
+pub mod prosody;
+pub mod lyrics;
+pub mod align;
+pub mod ratio;
+pub mod timeline;
+pub mod transform;
+pub mod repeat;
+pub mod rhythm;
+pub mod raga;
+pub mod variants;
+pub mod gamaka_sidecar;
+pub mod pitch_contour;
+pub mod tuning;
 
 // Re-export core functionality
 pub use parser::parse;
@@ -116,6 +116,14 @@ fn create_metadata_completions() -> Vec<CompletionItem> {
             insert_text_format: Some(InsertTextFormat::SNIPPET),
             ..Default::default()
         },
+        CompletionItem {
+            label: "tuning".to_string(),
+            kind: Some(CompletionItemKind::PROPERTY),
+            detail: Some("How swarasthanas are tuned against the key".to_string()),
+            insert_text: Some("tuning: \"${1|equal,just,shruti|}\"".to_string()),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            ..Default::default()
+        },
         CompletionItem {
             label: "arohanam".to_string(),
             kind: Some(CompletionItemKind::PROPERTY),
@@ -133,7 +141,8 @@ fn create_metadata_completions() -> Vec<CompletionItem> {
         CompletionItem {
             label: "default_octave".to_string(),
             kind: Some(CompletionItemKind::PROPERTY),
-            insert_text: Some("default_octave: \"${1:middle}\"".to_string()),
+            detail: Some("Octave of unmarked swaras".to_string()),
+            insert_text: Some("default_octave: \"${1|madhya,mandra,tara,anumandra,atitara|}\"".to_string()),
             insert_text_format: Some(InsertTextFormat::SNIPPET),
             ..Default::default()
         },
@@ -145,6 +154,14 @@ fn create_metadata_completions() -> Vec<CompletionItem> {
             insert_text_format: Some(InsertTextFormat::SNIPPET),
             ..Default::default()
         },
+        CompletionItem {
+            label: "eduppu".to_string(),
+            kind: Some(CompletionItemKind::PROPERTY),
+            detail: Some("Start offset from samam in aksharas".to_string()),
+            insert_text: Some("eduppu: \"${1:1/2}\"".to_string()),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            ..Default::default()
+        },
     ]
 }
 
@@ -208,9 +225,33 @@ fn create_notation_completions() -> Vec<CompletionItem> {
         ..Default::default()
     });
 
-    // Gati annotation
+    // Further words lines under the sahitya
+    let words_lines = [
+        ("sahitya[2]:", "sahitya[${1:2}]: $2", "Another verse sung to the same swaras"),
+        ("sahitya[te]:", "sahitya[${1:te}]: $2", "The words in another script"),
+        ("meaning:", "meaning: $1", "Translation of the words"),
+    ];
+
+    for (label, snippet, description) in words_lines {
+        completions.push(CompletionItem {
+            label: label.to_string(),
+            kind: Some(CompletionItemKind::SNIPPET),
+            detail: Some(description.to_string()),
+            insert_text: Some(snippet.to_string()),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            ..Default::default()
+        });
+    }
+
+    // Gati, raga, tempo, eduppu, swara-only and sangati annotations
     let annotations = [
         ("@gati:", "Gati override for section/line"),
+        ("@raga:", "Raga change for section/line (ragamalika)"),
+        ("@tempo:", "Tempo change for section/line, e.g. 80 or 80 over 2"),
+        ("@kalam:", "Speed for section/line: 1, 2 or 3"),
+        ("@eduppu:", "Section start offset from samam"),
+        ("@swara-only", "Section of swara lines without sahitya"),
+        ("@sangati:", "Numbered variation of the line before it"),
     ];
 
     for (annotation, description) in annotations {
@@ -218,7 +259,10 @@ fn create_notation_completions() -> Vec<CompletionItem> {
             label: annotation.to_string(),
             kind: Some(CompletionItemKind::PROPERTY),
             detail: Some(description.to_string()),
-            insert_text: Some(format!("{} $1", annotation)),
+            insert_text: Some(match annotation.ends_with(':') {
+                true => format!("{} $1", annotation),
+                false => annotation.to_string(),
+            }),
             insert_text_format: Some(InsertTextFormat::SNIPPET),
             ..Default::default()
         });
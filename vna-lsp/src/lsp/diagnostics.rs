@@ -1,20 +1,63 @@
+use crate::gamaka_sidecar::CurveData;
+use crate::syllabifier::SyllabifierRegistry;
 use crate::types::{VnaDocument, ValidationIssue, Severity};
 use tower_lsp::lsp_types::*;
 
 pub struct DiagnosticsProvider;
 
+impl Default for DiagnosticsProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DiagnosticsProvider {
     pub fn new() -> Self {
         Self
     }
 
     pub fn provide_diagnostics(&self, document: &VnaDocument) -> Vec<Diagnostic> {
-        match crate::validator::validate(document) {
+        self.provide_diagnostics_with(document, SyllabifierRegistry::shared())
+    }
+
+    pub fn provide_diagnostics_with(&self, document: &VnaDocument, registry: &SyllabifierRegistry) -> Vec<Diagnostic> {
+        match crate::validator::validate_with(document, registry) {
             Ok(issues) => issues.into_iter().map(|issue| self.convert_issue(issue)).collect(),
             Err(_) => vec![],
         }
     }
 
+    /// Opt-in prasa diagnostics, enabled with the `prosody` initialization option
+    pub fn provide_prosody_diagnostics(&self, document: &VnaDocument, registry: &SyllabifierRegistry) -> Vec<Diagnostic> {
+        let analysis = crate::prosody::analyze(document, registry);
+        crate::prosody::issues(&analysis)
+            .into_iter()
+            .map(|issue| self.convert_issue(issue))
+            .collect()
+    }
+
+    /// Checks of the gamaka curves kept next to the file, given the hash of
+    /// the file as last saved
+    pub fn provide_curve_diagnostics(&self, curves: &CurveData, document: &VnaDocument, file_hash: &str) -> Vec<Diagnostic> {
+        crate::gamaka_sidecar::check_hashed(curves, document, file_hash)
+            .into_iter()
+            .map(|issue| self.convert_issue(issue))
+            .collect()
+    }
+
+    /// A sidecar that couldn't be read, reported on the first line rather
+    /// than leaving the file looking as if it had no curves
+    pub fn provide_sidecar_error(&self, error: &str) -> Diagnostic {
+        self.convert_issue(ValidationIssue {
+            severity: Severity::Error,
+            message: error.to_string(),
+            line: 1,
+            column: None,
+            code: Some("invalid_gamaka_sidecar".to_string()),
+            range: None,
+        })
+    }
+
     fn convert_issue(&self, issue: ValidationIssue) -> Diagnostic {
         let severity = match issue.severity {
             Severity::Error => DiagnosticSeverity::ERROR,
@@ -50,7 +93,7 @@ impl DiagnosticsProvider {
         Diagnostic {
             range,
             severity: Some(severity),
-            code: issue.code.map(|c| NumberOrString::String(c)),
+            code: issue.code.map(NumberOrString::String),
             source: Some("vna".to_string()),
             message: issue.message,
             related_information: None,
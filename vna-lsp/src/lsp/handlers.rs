@@ -101,39 +101,38 @@ pub fn create_document_symbols(document: &VnaDocument) -> Vec<DocumentSymbol> {
 }
 
 pub fn create_code_actions(_document: &VnaDocument, _range: &Range) -> CodeActionResponse {
-    let mut actions = Vec::new();
-
-    // Add format action
-    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
-        title: "Format VNA Document".to_string(),
-        kind: Some(CodeActionKind::SOURCE_FIX_ALL),
-        diagnostics: None,
-        edit: None, // Will be handled by formatting provider
-        command: Some(Command {
-            title: "Format".to_string(),
-            command: "vna.format".to_string(),
-            arguments: None,
+    let actions = vec![
+        // Add format action
+        CodeActionOrCommand::CodeAction(CodeAction {
+            title: "Format VNA Document".to_string(),
+            kind: Some(CodeActionKind::SOURCE_FIX_ALL),
+            diagnostics: None,
+            edit: None, // Will be handled by formatting provider
+            command: Some(Command {
+                title: "Format".to_string(),
+                command: "vna.format".to_string(),
+                arguments: None,
+            }),
+            is_preferred: Some(true),
+            disabled: None,
+            data: None,
         }),
-        is_preferred: Some(true),
-        disabled: None,
-        data: None,
-    }));
-
-    // Add auto-fix for common issues
-    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
-        title: "Add missing beat markers".to_string(),
-        kind: Some(CodeActionKind::QUICKFIX),
-        diagnostics: None,
-        edit: None, // Would need line-specific logic
-        command: Some(Command {
-            title: "Fix Beat Markers".to_string(),
-            command: "vna.fixBeatMarkers".to_string(),
-            arguments: None,
+        // Add auto-fix for common issues
+        CodeActionOrCommand::CodeAction(CodeAction {
+            title: "Add missing beat markers".to_string(),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: None,
+            edit: None, // Would need line-specific logic
+            command: Some(Command {
+                title: "Fix Beat Markers".to_string(),
+                command: "vna.fixBeatMarkers".to_string(),
+                arguments: None,
+            }),
+            is_preferred: Some(false),
+            disabled: None,
+            data: None,
         }),
-        is_preferred: Some(false),
-        disabled: None,
-        data: None,
-    }));
+    ];
 
     CodeActionResponse::from(actions)
 }
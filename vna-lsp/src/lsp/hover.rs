@@ -1,4 +1,7 @@
-use crate::types::VnaDocument;
+use crate::gamaka_sidecar::CurveData;
+use crate::timeline::{self, Note};
+use crate::tuning::Tuning;
+use crate::types::{SahityaLine, SahityaLineKind, VnaDocument};
 use tower_lsp::lsp_types::*;
 
 pub fn provide_hover(document: &VnaDocument, position: Position) -> Option<Hover> {
@@ -15,10 +18,25 @@ pub fn provide_hover(document: &VnaDocument, position: Position) -> Option<Hover
         
         // Check if we're in phrases of this section
         for phrase in &section.phrases {
+            if let Some(line) = phrase.sahitya_lines.iter().find(|l| l.line_number - 1 == line_num) {
+                return Some(create_sahitya_line_hover(line));
+            }
+
+            // Any other line after the sahitya (or a swara-only line) is the phrase analysis
             let phrase_start = phrase.line_number - 1;
-            let phrase_lines = if phrase.phrase_analysis.is_some() { 3 } else { 2 };
+            let sahitya_lines = if section.swara_only { 0 } else { 1 };
+            let phrase_lines = 1 + sahitya_lines + phrase.sahitya_lines.len() + phrase.phrase_analysis.is_some() as usize;
             if line_num >= phrase_start && line_num < phrase_start + phrase_lines {
-                return Some(create_phrase_hover(line_num - phrase_start, phrase.phrase_analysis.is_some()));
+                let line_type = match line_num - phrase_start {
+                    0 => 0,
+                    offset if offset <= sahitya_lines => 1,
+                    _ => 2,
+                };
+                let mut hover = create_phrase_hover(line_type, phrase.phrase_analysis.is_some());
+                if let (0, HoverContents::Markup(markup)) = (line_type, &mut hover.contents) {
+                    markup.value.push_str(&pitches(document, phrase.line_number));
+                }
+                return Some(hover);
             }
         }
     }
@@ -27,6 +45,62 @@ pub fn provide_hover(document: &VnaDocument, position: Position) -> Option<Hover
     None
 }
 
+/// The gamaka curves drawn on a swara line in the editor
+pub fn provide_curve_hover(document: &VnaDocument, curves: &CurveData, position: Position) -> Option<Hover> {
+    let line_num = position.line as usize;
+    let (section, index) = document.sections.iter().find_map(|section| {
+        let index = section.phrases.iter().position(|p| p.line_number - 1 == line_num)?;
+        Some((section, index))
+    })?;
+
+    let drawn: Vec<String> = curves
+        .for_phrase(section, index)
+        .map(|curve| match (curve.swara_start.as_str(), curve.swara_end.as_str()) {
+            ("", _) | (_, "") => format!("- {}", curve.kind.name()),
+            (start, end) => format!("- {} from {} to {}", curve.kind.name(), start, end),
+        })
+        .collect();
+    if drawn.is_empty() {
+        return None;
+    }
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: format!("**Drawn Gamakas**: Curves from the editor on this line\n{}", drawn.join("\n")),
+        }),
+        range: None,
+    })
+}
+
+/// The frequencies of the notes on a swara line, in the document's key and
+/// tuning, each named as written
+fn pitches(document: &VnaDocument, line_number: usize) -> String {
+    let tuning = Tuning::new(&document.metadata);
+    let octave = timeline::default_octave(&document.metadata);
+    let mut notes: Vec<Note> = Vec::new();
+    for note in timeline::build(document).events.into_iter().filter(|e| e.line_number == line_number).filter_map(|e| e.note) {
+        if !notes.contains(&note) {
+            notes.push(note);
+        }
+    }
+    notes.sort_by_key(|n| n.semitones());
+
+    let lines: Vec<String> = notes
+        .iter()
+        .map(|note| match tuning.shruti(note) {
+            Some(shruti) => format!("- {}: {:.1} Hz ({})", note.shifted(-octave), tuning.frequency(note), shruti),
+            None => format!("- {}: {:.1} Hz", note.shifted(-octave), tuning.frequency(note)),
+        })
+        .collect();
+    format!(
+        "\n\n**Pitches** (S = {:.1} Hz, {})\n{}",
+        tuning.tonic_hz,
+        tuning.temperament.name(),
+        lines.join("\n")
+    )
+}
+
 fn create_section_hover(section_name: &str) -> Hover {
     let content = match section_name {
         "pallavi" => "**Pallavi**: The main theme or refrain of the composition. Usually the most important melodic phrase that returns throughout the piece.",
@@ -50,9 +124,10 @@ fn create_section_hover(section_name: &str) -> Hover {
 fn create_phrase_hover(line_type: usize, has_phrase_analysis: bool) -> Hover {
     let content = match line_type {
         0 => "**Swara Line**: Musical notes using S R G M P D N. \
-               Octave markers: `.` (lower), `'` (upper), `''` (two up). \
+               Octave markers: `.` (lower), `'` (upper), `''` (two up), counted from `default_octave`. \
                Variants: R1/R2/R3, G1/G2/G3, M1/M2, D1/D2/D3, N1/N2/N3.\n\
-               Gati notation: `SRG:3` (tisra), `SRGR:4` (catusra), `SRGMP:5` (khanda)",
+               Gati notation: `SRG:3` (tisra), `SRGR:4` (catusra), `SRGMP:5` (khanda).\n\
+               Gamakas: `G~` (kampita), `G/` `G\\` (jaru up or down to the next note), `{R}G` (anuswaram)",
         1 => "**Sahitya Line**: Lyrics or syllables that align with the swaras above. \
                Use `-` for continuation or empty positions. \
                Each token must match the character count of the swara above.",
@@ -73,12 +148,41 @@ fn create_phrase_hover(line_type: usize, has_phrase_analysis: bool) -> Hover {
     }
 }
 
+fn create_sahitya_line_hover(line: &SahityaLine) -> Hover {
+    let content = match (line.kind, line.verse()) {
+        (SahityaLineKind::Sahitya, Some(verse)) => format!(
+            "**Sahitya, verse {}**: Further words sung to the same swaras. \
+             Each token must match the swara above unit for unit.",
+            verse
+        ),
+        (SahityaLineKind::Sahitya, None) => format!(
+            "**Sahitya ({})**: The words in another script or transliteration. \
+             Each token must match the swara above unit for unit.",
+            line.label.as_deref().unwrap_or_default()
+        ),
+        (SahityaLineKind::Meaning, _) => "**Meaning**: A translation of the words. \
+             Written against the beat markers or as free text, and not unit-checked."
+            .to_string(),
+    };
+
+    Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: content,
+        }),
+        range: None,
+    }
+}
+
 pub fn create_symbol_hover(symbol: &str) -> Option<Hover> {
     let content = match symbol {
         "||" => "**Phrase End**: Marks the end of a complete musical phrase or line",
         "|" => "**Beat Boundary**: Indicates tala beat divisions within the phrase",
         "-" => "**Rest/Silence**: Indicates a pause or empty position in sahitya",
         "," => "**Sustain/Pause**: When standalone, sustains the previous note",
+        "~" => "**Kampita**: Oscillation on the note before it. Not a unit",
+        "/" => "**Jaru (up)**: Slide up from the note before it into the next note. Not a unit",
+        "\\" => "**Jaru (down)**: Slide down from the note before it into the next note. Not a unit",
         "_" => "**Held Note**: In phrase analysis, indicates sustained notes",
         "*" => "**Quick Note**: In phrase analysis, indicates crisp notes",
         "S" => "**Shadja (Sa)**: The tonic note, foundation of the scale",
@@ -88,6 +192,12 @@ pub fn create_symbol_hover(symbol: &str) -> Option<Hover> {
         "P" => "**Panchama (Pa)**: The fifth note, perfect fifth",
         "D" | "D1" | "D2" | "D3" => "**Dhaivata (Dha)**: The sixth note with variants",
         "N" | "N1" | "N2" | "N3" => "**Nishada (Ni)**: The seventh note with variants",
+        "@eduppu:" => "**Eduppu**: Where the section starts relative to samam, in aksharas. `1/2` starts half an akshara after samam (anagata), `-1/2` half an akshara before it (atita)",
+        "@raga:" => "**Raga Change**: Sets the raga for a section (before its first line) or for one line, as in a ragamalika. Swaras are checked against the raga's scale when it is known",
+        "@tempo:" => "**Tempo Change**: Aksharas per minute for a section or line. `80 over 2` moves gradually from the tempo before it to 80 BPM across two avartanams",
+        "@kalam:" => "**Kalam**: Plays a section or line in first (1), second (2) or third (3) speed, at twice or four times the density of its gati",
+        "@swara-only" => "**Swara-Only Section**: Phrases in the section are single swara lines, with no sahitya line under them",
+        "@sangati:" => "**Sangati**: Marks the line as a numbered variation of the line it follows. Sangatis are numbered from 2, the line itself being the first",
         "@gati:" => "**Gati Override**: Sets the rhythmic subdivision for a section or line. Values: 3 (tisra), 4 (catusra), 5 (khanda), 7 (misra), 9 (sankirna)",
         _ => return None,
     };
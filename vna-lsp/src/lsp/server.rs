@@ -1,16 +1,37 @@
+use crate::gamaka_sidecar::{self, CurveData};
 use crate::lsp::diagnostics::DiagnosticsProvider;
+use crate::syllabifier::{ExceptionDictionary, SyllabifierRegistry, EXCEPTIONS_FILE_NAME};
 use crate::types::VnaDocument;
 use anyhow::Result;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use tokio::sync::RwLock;
 use tower_lsp::jsonrpc::Result as LspResult;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
 
+/// Gamaka curves drawn in the editor for a file, with the hash of the file as
+/// last saved; the editor draws on saved files, so that's what they're checked against
+struct Sidecar {
+    /// Why the sidecar couldn't be read, if it couldn't
+    curves: std::result::Result<CurveData, String>,
+    saved_hash: String,
+}
+
 pub struct VnaLanguageServer {
     client: Client,
     documents: RwLock<HashMap<Url, VnaDocument>>,
+    /// Sidecars of open files, read on open and again on save or when the
+    /// sidecar changes; None for files without one
+    curves: RwLock<HashMap<Url, Option<Sidecar>>>,
+    /// Syllabifiers by directory, with the exception dictionary found for it;
+    /// None where there is none. Cleared when a dictionary changes
+    registries: RwLock<HashMap<PathBuf, Option<Arc<SyllabifierRegistry>>>>,
     diagnostics_provider: DiagnosticsProvider,
+    prosody_diagnostics: AtomicBool,
+    watch_files: AtomicBool,
 }
 
 impl VnaLanguageServer {
@@ -18,7 +39,11 @@ impl VnaLanguageServer {
         Self {
             client,
             documents: RwLock::new(HashMap::new()),
+            curves: RwLock::new(HashMap::new()),
+            registries: RwLock::new(HashMap::new()),
             diagnostics_provider: DiagnosticsProvider::new(),
+            prosody_diagnostics: AtomicBool::new(false),
+            watch_files: AtomicBool::new(false),
         }
     }
 
@@ -26,28 +51,100 @@ impl VnaLanguageServer {
         let stdin = tokio::io::stdin();
         let stdout = tokio::io::stdout();
 
-        let (service, socket) = LspService::new(|client| VnaLanguageServer::new(client));
+        let (service, socket) = LspService::new(VnaLanguageServer::new);
         Server::new(stdin, stdout, socket).serve(service).await;
 
         Ok(())
     }
 
+    /// Syllabifiers for a file on disk, with the project's exception dictionary
+    async fn registry(&self, uri: &Url) -> Option<Arc<SyllabifierRegistry>> {
+        let path = uri.to_file_path().ok()?;
+        let directory = path.parent()?.to_path_buf();
+        if let Some(registry) = self.registries.read().await.get(&directory) {
+            return registry.clone();
+        }
+
+        let registry = ExceptionDictionary::find_for(&path)
+            .and_then(|dictionary| ExceptionDictionary::load(&dictionary).ok())
+            .map(|exceptions| Arc::new(SyllabifierRegistry::default().with_exceptions(exceptions)));
+        self.registries.write().await.insert(directory, registry.clone());
+        registry
+    }
+
+    /// Read the sidecar of a file, given the file's content as saved
+    async fn load_curves(&self, uri: &Url, saved: &str) {
+        let curves = uri.to_file_path().ok().and_then(|path| gamaka_sidecar::load(&path).transpose());
+        let sidecar = curves.map(|curves| Sidecar {
+            curves: curves.map_err(|err| err.to_string()),
+            saved_hash: gamaka_sidecar::file_hash(saved),
+        });
+        self.curves.write().await.insert(uri.clone(), sidecar);
+    }
+
     async fn update_diagnostics(&self, uri: &Url, document: &VnaDocument) {
-        let diagnostics = self.diagnostics_provider.provide_diagnostics(document);
+        let registry = self.registry(uri).await;
+        let registry = registry.as_deref().unwrap_or_else(|| SyllabifierRegistry::shared());
+        let mut diagnostics = self.diagnostics_provider.provide_diagnostics_with(document, registry);
+        if self.prosody_diagnostics.load(Ordering::Relaxed) {
+            diagnostics.extend(self.diagnostics_provider.provide_prosody_diagnostics(document, registry));
+        }
+
+        if let Some(Some(sidecar)) = self.curves.read().await.get(uri) {
+            match &sidecar.curves {
+                Ok(curves) => diagnostics.extend(self.diagnostics_provider.provide_curve_diagnostics(
+                    curves,
+                    document,
+                    &sidecar.saved_hash,
+                )),
+                Err(err) => diagnostics.push(self.diagnostics_provider.provide_sidecar_error(err)),
+            }
+        }
         self.client
             .publish_diagnostics(uri.clone(), diagnostics, None)
             .await;
     }
+
+    /// Re-check every open file, after something they share has changed
+    async fn update_all_diagnostics(&self) {
+        let documents: Vec<(Url, VnaDocument)> =
+            self.documents.read().await.iter().map(|(uri, document)| (uri.clone(), document.clone())).collect();
+        for (uri, document) in documents {
+            self.update_diagnostics(&uri, &document).await;
+        }
+    }
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for VnaLanguageServer {
-    async fn initialize(&self, _: InitializeParams) -> LspResult<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> LspResult<InitializeResult> {
+        // { "prosody": true } turns on prasa diagnostics
+        let prosody = params
+            .initialization_options
+            .as_ref()
+            .and_then(|options| options.get("prosody"))
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+        self.prosody_diagnostics.store(prosody, Ordering::Relaxed);
+
+        let watch_files = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.did_change_watched_files.as_ref())
+            .and_then(|watched| watched.dynamic_registration)
+            .unwrap_or(false);
+        self.watch_files.store(watch_files, Ordering::Relaxed);
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
-                text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::FULL,
-                )),
+                text_document_sync: Some(TextDocumentSyncCapability::Options(TextDocumentSyncOptions {
+                    open_close: Some(true),
+                    change: Some(TextDocumentSyncKind::FULL),
+                    // Saves re-read the gamaka curves
+                    save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                    ..Default::default()
+                })),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 completion_provider: Some(CompletionOptions {
                     resolve_provider: Some(false),
@@ -66,6 +163,27 @@ impl LanguageServer for VnaLanguageServer {
     }
 
     async fn initialized(&self, _: InitializedParams) {
+        // Watch exception dictionaries and gamaka sidecars, which are cached
+        if self.watch_files.load(Ordering::Relaxed) {
+            let watcher = |pattern: &str| FileSystemWatcher {
+                glob_pattern: GlobPattern::String(pattern.to_string()),
+                kind: None,
+            };
+            let options = DidChangeWatchedFilesRegistrationOptions {
+                watchers: vec![watcher(&format!("**/{}", EXCEPTIONS_FILE_NAME)), watcher("**/*.gamakas.json")],
+            };
+            let registration = Registration {
+                id: "vna-watched-files".to_string(),
+                method: "workspace/didChangeWatchedFiles".to_string(),
+                register_options: serde_json::to_value(options).ok(),
+            };
+            if let Err(err) = self.client.register_capability(vec![registration]).await {
+                self.client
+                    .log_message(MessageType::WARNING, format!("Can't watch syllable dictionaries and gamaka curves: {}", err))
+                    .await;
+            }
+        }
+
         self.client
             .log_message(MessageType::INFO, "VNA Language Server initialized")
             .await;
@@ -82,6 +200,7 @@ impl LanguageServer for VnaLanguageServer {
         match crate::parser::parse(&content) {
             Ok(document) => {
                 self.documents.write().await.insert(uri.clone(), document.clone());
+                self.load_curves(&uri, &content).await;
                 self.update_diagnostics(&uri, &document).await;
             }
             Err(err) => {
@@ -137,10 +256,47 @@ impl LanguageServer for VnaLanguageServer {
         }
     }
 
-    async fn did_save(&self, _: DidSaveTextDocumentParams) {}
+    async fn did_save(&self, params: DidSaveTextDocumentParams) {
+        let uri = params.text_document.uri;
+        let saved = params
+            .text
+            .or_else(|| uri.to_file_path().ok().and_then(|path| std::fs::read_to_string(path).ok()));
+        if let Some(saved) = saved {
+            self.load_curves(&uri, &saved).await;
+        }
+
+        let document = self.documents.read().await.get(&uri).cloned();
+        if let Some(document) = document {
+            self.update_diagnostics(&uri, &document).await;
+        }
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        for change in params.changes {
+            let Ok(path) = change.uri.to_file_path() else {
+                continue;
+            };
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+            if name == EXCEPTIONS_FILE_NAME {
+                // A dictionary serves every directory below it
+                self.registries.write().await.clear();
+            } else if let Some(stem) = name.strip_suffix(".gamakas.json") {
+                let Ok(uri) = Url::from_file_path(path.with_file_name(format!("{}.vna", stem))) else {
+                    continue;
+                };
+                if !self.curves.read().await.contains_key(&uri) {
+                    continue;
+                }
+                let saved = uri.to_file_path().ok().and_then(|path| std::fs::read_to_string(path).ok());
+                self.load_curves(&uri, &saved.unwrap_or_default()).await;
+            }
+        }
+        self.update_all_diagnostics().await;
+    }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         self.documents.write().await.remove(&params.text_document.uri);
+        self.curves.write().await.remove(&params.text_document.uri);
     }
 
     async fn hover(&self, params: HoverParams) -> LspResult<Option<Hover>> {
@@ -149,6 +305,12 @@ impl LanguageServer for VnaLanguageServer {
 
         let documents = self.documents.read().await;
         if let Some(document) = documents.get(&uri) {
+            let curves = self.curves.read().await;
+            let sidecar = curves.get(&uri).and_then(Option::as_ref);
+            let curves = sidecar.and_then(|s| s.curves.as_ref().ok());
+            if let Some(hover) = curves.and_then(|c| crate::lsp::hover::provide_curve_hover(document, c, position)) {
+                return Ok(Some(hover));
+            }
             return Ok(crate::lsp::hover::provide_hover(document, position));
         }
 
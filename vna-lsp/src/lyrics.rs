@@ -0,0 +1,205 @@
+use crate::repeat;
+use crate::sahitya_scheme::{detect_document_scheme, detect_token_scheme, from_iso, to_iso, SahityaScheme};
+use crate::types::*;
+use anyhow::Result;
+
+/// Ends a word inside a sahitya token: `` `nin`-nu-_kō `` is "ninnu kō"
+pub const WORD_BREAK: char = '_';
+
+/// Starts a token that carries on the previous token's last word, since words
+/// routinely span several tokens: `kō--- | =ri---` is "kōri"
+/// Tokens are otherwise words of their own
+pub const WORD_JOIN: char = '=';
+
+/// Plain lyrics of one section, one entry per sahitya line
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionLyrics {
+    pub name: String,
+    pub lines: Vec<String>,
+}
+
+/// Extract the lyrics of a document as sung, with repeat groups written out
+/// Dashes, backticks and word breaks are removed and sections without sahitya are skipped
+/// Further verses (`sahitya[2]:`) follow the section's first verse, in order
+pub fn extract(document: &VnaDocument) -> Vec<SectionLyrics> {
+    repeat::expand(document)
+        .sections
+        .iter()
+        .map(|section| {
+            let mut verses: Vec<u32> = section
+                .phrases
+                .iter()
+                .flat_map(|p| p.sahitya_lines.iter().filter_map(|l| l.verse()))
+                .collect();
+            verses.sort_unstable();
+            verses.dedup();
+
+            let first = section.phrases.iter().map(|phrase| line_words(&phrase.sahitya).join(" "));
+            let further = verses.into_iter().flat_map(|verse| {
+                section.phrases.iter().flat_map(move |phrase| {
+                    phrase
+                        .sahitya_lines
+                        .iter()
+                        .filter(move |l| l.verse() == Some(verse))
+                        .map(|l| line_words(&l.syllables).join(" "))
+                })
+            });
+            SectionLyrics {
+                name: section.header(),
+                lines: first.chain(further).filter(|line| !line.is_empty()).collect(),
+            }
+        })
+        .filter(|section| !section.lines.is_empty())
+        .collect()
+}
+
+/// Extract the lyrics transliterated to a scheme or native script
+/// Each word is read in its own detected scheme, falling back to the document's dominant one
+pub fn extract_in(document: &VnaDocument, target: SahityaScheme) -> Result<Vec<SectionLyrics>> {
+    let document_scheme = detect_document_scheme(document);
+    let mut sections = extract(document);
+
+    for section in &mut sections {
+        for line in &mut section.lines {
+            let words = line
+                .split(' ')
+                .map(|word| {
+                    let from = detect_token_scheme(word).unwrap_or(document_scheme);
+                    from_iso(&to_iso(word, from)?, target)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            *line = words.join(" ");
+        }
+    }
+
+    Ok(sections)
+}
+
+/// Render lyrics as text, with a `[section]` heading before each section's lines
+pub fn to_text(sections: &[SectionLyrics]) -> String {
+    sections
+        .iter()
+        .map(|section| format!("[{}]\n{}\n", section.name, section.lines.join("\n")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Words of a line of sahitya tokens
+/// A token not starting with a word join, or a word break, ends the word at
+/// the next letter; so a join carries a word across dash-only tokens
+fn line_words(tokens: &[String]) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for token in tokens {
+        let (mut pending_break, token) = match token.strip_prefix(WORD_JOIN) {
+            Some(rest) => (false, rest),
+            None => (true, token.as_str()),
+        };
+        for ch in token.chars() {
+            match ch {
+                WORD_BREAK => pending_break = true,
+                '-' | '`' => {}
+                _ => {
+                    if std::mem::take(&mut pending_break) && !current.is_empty() {
+                        words.push(std::mem::take(&mut current));
+                    }
+                    current.push(ch);
+                }
+            }
+        }
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    const CONTENT: &str = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+language: "telugu"
+---
+
+[pallavi]
+G,G,      R,,,  | SSRR   GGRR | SRGR     SRSD.  | SRGP     GRSR  ||
+`nin`-nu- kō--- | =ri--- ---- | `yun`--- =nā--- | =`nu`--- rā--- ||
+
+[muktayisvaram]
+G,RG RSR, ||
+---- ---- ||
+"#;
+
+    #[test]
+    fn test_extract() {
+        let doc = parse(CONTENT).unwrap();
+        let lyrics = extract(&doc);
+
+        assert_eq!(lyrics.len(), 1);
+        assert_eq!(lyrics[0].name, "pallavi");
+        assert_eq!(lyrics[0].lines, vec!["ninnu kōri yunnānu rā"]);
+        assert_eq!(to_text(&lyrics), "[pallavi]\nninnu kōri yunnānu rā\n");
+    }
+
+    #[test]
+    fn test_line_words() {
+        let words = |tokens: &[&str]| line_words(&tokens.iter().map(|t| t.to_string()).collect::<Vec<_>>());
+        assert_eq!(words(&["`nin`-nu-_kō---", "=ri---"]), vec!["ninnu", "kōri"]);
+        // A join carries the word across tokens without syllables
+        assert_eq!(words(&["kō---", "----", "=ri---"]), vec!["kōri"]);
+        assert_eq!(words(&["kō---", "ri---"]), vec!["kō", "ri"]);
+    }
+
+    #[test]
+    fn test_extract_data_file() {
+        let lyrics = extract(&parse(include_str!("../../data/ninnukori_mohanam.vna")).unwrap());
+        let lines: Vec<&str> = lyrics.iter().flat_map(|s| s.lines.iter().map(String::as_str)).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "ninnu kōri yunnānurā",
+                "nikhila lōka nāyaka",
+                "nannu pālimpa samayamu rā",
+                "nā mīda kṟupa jūḍa rā",
+                "sannutānga śrīṉivāsā",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_further_verses() {
+        let content = r#"---
+title: "Verses"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[charanam]
+G,RG RSR, ||
+ma--- =hi--- ||
+sahitya[2]: sā--- =mi--- ||
+meaning: great one
+
+S,RG PDS' ||
+ta--- =nu--- ||
+sahitya[2]: ra--- =ma--- ||
+"#;
+        let lyrics = extract(&parse(content).unwrap());
+        assert_eq!(lyrics[0].lines, vec!["mahi", "tanu", "sāmi", "rama"]);
+    }
+
+    #[test]
+    fn test_extract_in_script() {
+        let doc = parse(CONTENT).unwrap();
+        let lyrics = extract_in(&doc, SahityaScheme::Native(crate::script::Script::Telugu)).unwrap();
+        assert_eq!(lyrics[0].lines, vec!["నిన్ను కోరి యున్నాను రా"]);
+    }
+}
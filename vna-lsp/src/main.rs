@@ -3,6 +3,7 @@ use colored::*;
 use std::path::PathBuf;
 
 use vna::*;
+use vna::syllabifier::SyllabifierRegistry;
 
 #[derive(Parser)]
 #[command(name = "vna")]
@@ -25,11 +26,17 @@ enum Commands {
         /// Watch for changes
         #[arg(short, long)]
         watch: bool,
+        /// Also report prasa breaks in sahitya
+        #[arg(long)]
+        prosody: bool,
     },
     /// Validate .vna file structure and musical correctness
     Validate {
         /// File to validate
         file: PathBuf,
+        /// Also report prasa breaks in sahitya
+        #[arg(long)]
+        prosody: bool,
     },
     /// Format .vna files with consistent spacing and alignment
     Format {
@@ -38,6 +45,9 @@ enum Commands {
         /// Check if files are formatted (exit 1 if not)
         #[arg(short, long)]
         check: bool,
+        /// Drop sahitya lines made only of dashes, marking their sections @swara-only
+        #[arg(long)]
+        swara_only: bool,
     },
     /// Generate PDF with frequency grids from .vna file
     Pdf {
@@ -46,9 +56,9 @@ enum Commands {
         /// Output PDF file
         #[arg(short, long)]
         output: Option<PathBuf>,
-        /// Height of frequency grids in pixels
-        #[arg(long, default_value = "60")]
-        grid_height: u32,
+        /// Deprecated and ignored: the staff lines carry the frequencies
+        #[arg(long, hide = true)]
+        grid_height: Option<u32>,
         /// Page size (a4, letter)
         #[arg(long, default_value = "a4")]
         page_size: String,
@@ -57,17 +67,104 @@ enum Commands {
     Info {
         /// VNA file to analyze
         file: PathBuf,
+        /// Print metadata and timing as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Convert sahitya in .vna files to a single transliteration scheme
+    Convert {
+        /// Files to convert (supports globs)
+        files: Vec<PathBuf>,
+        /// Target scheme (iso, iast, harvard-kyoto) or script (telugu, tamil, devanagari, kannada, malayalam)
+        #[arg(long)]
+        sahitya_scheme: String,
+    },
+    /// Rewrite a .vna file musically (e.g. in a faster kalam)
+    Transform {
+        /// VNA file to transform
+        file: PathBuf,
+        /// Re-express at 2x or 4x density (second or third speed)
+        #[arg(long)]
+        kalam: Option<u8>,
+        /// Rewrite in another gati, keeping every note's duration
+        #[arg(long, conflicts_with = "kalam")]
+        gati: Option<u8>,
+        /// Rewrite octave markers to count from another default octave (anumandra, mandra, madhya, tara, atitara)
+        #[arg(long, conflicts_with_all = ["kalam", "gati", "section"])]
+        rebase_octave: Option<String>,
+        /// Only transform this section
+        #[arg(long)]
+        section: Option<String>,
+        /// Only transform the phrase whose swara line is on this line (with --gati)
+        #[arg(long, requires = "gati")]
+        line: Option<usize>,
+        /// Write the result to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print the plain lyrics of a .vna file, section by section
+    Lyrics {
+        /// VNA file to read
+        file: PathBuf,
+        /// Transliterate to a scheme (iso, iast, harvard-kyoto) or script (telugu, tamil, devanagari, kannada, malayalam)
+        #[arg(long)]
+        script: Option<String>,
+        /// Write the lyrics to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Export one version of a .vna file, choosing each section's pathantara by school
+    Render {
+        /// VNA file to render
+        file: PathBuf,
+        /// School whose pathantaras replace the sections they are versions of (e.g. semmangudi)
+        #[arg(long)]
+        variant: Option<String>,
+        /// Output format: vna, pdf or lyrics
+        #[arg(long, default_value = "vna")]
+        to: String,
+        /// Write to a file instead of stdout (a pdf defaults to the file name with .pdf)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Align plain lyrics to a swara line, producing a padded sahitya line
+    Align {
+        /// Swara line, e.g. "G,G, R,,, | SSRR GGRR ||"
+        swaras: String,
+        /// Lyrics for the line, words separated by spaces
+        lyrics: String,
+        /// Sahitya language used for syllabification (e.g. telugu)
+        #[arg(long)]
+        language: Option<String>,
+    },
+    /// Analyze the music of a .vna file
+    Analyze {
+        #[command(subcommand)]
+        analysis: Analysis,
     },
     /// Start LSP server for editor integration
     Lsp,
 }
 
+#[derive(Subcommand)]
+enum Analysis {
+    /// Check that korvais and moras (patterns played three times) end on samam or the eduppu
+    Rhythm {
+        /// VNA file to analyze
+        file: PathBuf,
+        /// Expected landing point in aksharas from samam, e.g. 2, 1/2 or -1/4
+        /// (defaults to each section's eduppu)
+        #[arg(long, allow_hyphen_values = true)]
+        eduppu: Option<Eduppu>,
+    },
+}
+
 fn main() -> anyhow::Result<()> {
     env_logger::init();
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Lint { files, fix, watch } => {
+        Commands::Lint { files, fix, watch, prosody } => {
             if watch {
                 todo!("Watch mode not implemented yet");
             }
@@ -83,7 +180,7 @@ fn main() -> anyhow::Result<()> {
 
             let mut has_errors = false;
             for file in files {
-                match lint_file(&file, fix) {
+                match lint_file(&file, fix, prosody) {
                     Ok(had_issues) => {
                         if had_issues {
                             has_errors = true;
@@ -103,8 +200,8 @@ fn main() -> anyhow::Result<()> {
             }
         }
         
-        Commands::Validate { file } => {
-            match validate_file(&file) {
+        Commands::Validate { file, prosody } => {
+            match validate_file(&file, prosody) {
                 Ok(_) => println!("{}", "✅ File is valid!".green()),
                 Err(e) => {
                     println!("{} {}", "❌ Error:".red(), e);
@@ -113,7 +210,7 @@ fn main() -> anyhow::Result<()> {
             }
         }
 
-        Commands::Format { files, check } => {
+        Commands::Format { files, check, swara_only } => {
             let files = if files.is_empty() {
                 glob::glob("*.vna")?.collect::<Result<Vec<_>, _>>()?
             } else {
@@ -122,7 +219,7 @@ fn main() -> anyhow::Result<()> {
 
             let mut needs_formatting = false;
             for file in files {
-                match format_file(&file, check) {
+                match format_file(&file, check, swara_only) {
                     Ok(was_formatted) => {
                         if was_formatted {
                             needs_formatting = true;
@@ -143,10 +240,13 @@ fn main() -> anyhow::Result<()> {
             let output = output.unwrap_or_else(|| {
                 file.with_extension("pdf")
             });
+            if grid_height.is_some() {
+                println!("{} --grid-height is deprecated and has no effect", "⚠️".yellow());
+            }
 
             println!("{} {}...", "🎵 Generating PDF from".blue(), file.display());
             
-            match generate_pdf(&file, &output, grid_height, &page_size) {
+            match generate_pdf(&file, &output, grid_height.unwrap_or(pdf::DEFAULT_GRID_HEIGHT), &page_size) {
                 Ok(_) => {
                     println!("{} {}", "✅ PDF generated:".green(), output.display());
                 }
@@ -157,8 +257,9 @@ fn main() -> anyhow::Result<()> {
             }
         }
 
-        Commands::Info { file } => {
-            match show_info(&file) {
+        Commands::Info { file, json } => {
+            let result = if json { show_info_json(&file) } else { show_info(&file) };
+            match result {
                 Ok(_) => {}
                 Err(e) => {
                     println!("{} {}", "❌ Error:".red(), e);
@@ -167,6 +268,92 @@ fn main() -> anyhow::Result<()> {
             }
         }
 
+        Commands::Convert { files, sahitya_scheme } => {
+            let Some(target) = sahitya_scheme::SahityaScheme::from_name(&sahitya_scheme) else {
+                println!("{} Unknown sahitya scheme: {}", "❌ Error:".red(), sahitya_scheme);
+                std::process::exit(1);
+            };
+
+            let files = if files.is_empty() {
+                glob::glob("*.vna")?.collect::<Result<Vec<_>, _>>()?
+            } else {
+                files
+            };
+
+            let mut has_errors = false;
+            for file in files {
+                if let Err(e) = convert_file(&file, target) {
+                    println!("{} {}: {}", "❌".red(), file.display(), e);
+                    has_errors = true;
+                }
+            }
+
+            if has_errors {
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Transform { file, kalam, gati, rebase_octave, section, line, output } => {
+            let transformation = match (kalam, gati, rebase_octave) {
+                (Some(factor), _, _) => Transformation::Kalam(factor),
+                (None, Some(gati), _) => Transformation::Gati(gati, line),
+                (None, None, Some(name)) => match timeline::parse_octave(&name) {
+                    Some(octave) => Transformation::RebaseOctave(octave),
+                    None => {
+                        println!("{} Unknown octave: {}", "❌ Error:".red(), name);
+                        std::process::exit(1);
+                    }
+                },
+                (None, None, None) => {
+                    println!("{} Nothing to do: choose --kalam, --gati or --rebase-octave", "❌ Error:".red());
+                    std::process::exit(1);
+                }
+            };
+            if let Err(e) = transform_file(&file, transformation, section.as_deref(), output.as_ref()) {
+                println!("{} {}: {}", "❌".red(), file.display(), e);
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Analyze { analysis: Analysis::Rhythm { file, eduppu } } => {
+            if let Err(e) = show_rhythm(&file, eduppu) {
+                println!("{} {}", "❌ Error:".red(), e);
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Lyrics { file, script, output } => {
+            let target = match script.as_deref() {
+                Some(name) => match sahitya_scheme::SahityaScheme::from_name(name) {
+                    Some(scheme) => Some(scheme),
+                    None => {
+                        println!("{} Unknown script: {}", "❌ Error:".red(), name);
+                        std::process::exit(1);
+                    }
+                },
+                None => None,
+            };
+
+            if let Err(e) = show_lyrics(&file, target, output.as_ref()) {
+                println!("{} {}: {}", "❌".red(), file.display(), e);
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Render { file, variant, to, output } => {
+            if let Err(e) = render_file(&file, variant.as_deref(), &to, output.as_ref()) {
+                println!("{} {}: {}", "❌".red(), file.display(), e);
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Align { swaras, lyrics, language } => {
+            if let Err(e) = align_line(&swaras, &lyrics, language.as_deref()) {
+                println!("{} {}", "❌ Error:".red(), e);
+                std::process::exit(1);
+            }
+        }
+
         Commands::Lsp => {
             println!("{}", "🚀 Starting VNA Language Server...".blue().bold());
             tokio::runtime::Runtime::new()?.block_on(lsp::VnaLanguageServer::run())?;
@@ -176,10 +363,14 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn lint_file(file: &PathBuf, fix: bool) -> anyhow::Result<bool> {
+fn lint_file(file: &PathBuf, fix: bool, prosody: bool) -> anyhow::Result<bool> {
     let content = std::fs::read_to_string(file)?;
     let document = parser::parse(&content)?;
-    let issues = validator::validate(&document)?;
+    let registry = SyllabifierRegistry::for_file(file)?;
+    let mut issues = validator::validate_with(&document, &registry)?;
+    if prosody {
+        issues.extend(prosody::issues(&prosody::analyze(&document, &registry)));
+    }
 
     println!("{} {}", "📄".cyan(), file.display());
 
@@ -213,10 +404,18 @@ fn lint_file(file: &PathBuf, fix: bool) -> anyhow::Result<bool> {
     Ok(has_errors)
 }
 
-fn validate_file(file: &PathBuf) -> anyhow::Result<()> {
+fn validate_file(file: &PathBuf, prosody: bool) -> anyhow::Result<()> {
     let content = std::fs::read_to_string(file)?;
     let document = parser::parse(&content)?;
-    let issues = validator::validate(&document)?;
+    let registry = SyllabifierRegistry::for_file(file)?;
+    let mut issues = validator::validate_with(&document, &registry)?;
+    if prosody {
+        issues.extend(prosody::issues(&prosody::analyze(&document, &registry)));
+    }
+    // Curves drawn in the editor must still fit the notation
+    if let Some(curves) = gamaka_sidecar::load(file)? {
+        issues.extend(gamaka_sidecar::check(&curves, &document, &content));
+    }
 
     println!("{} {}...", "Validating".cyan(), file.display());
 
@@ -242,9 +441,14 @@ fn validate_file(file: &PathBuf) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn format_file(file: &PathBuf, check_only: bool) -> anyhow::Result<bool> {
+fn format_file(file: &PathBuf, check_only: bool, swara_only: bool) -> anyhow::Result<bool> {
     let content = std::fs::read_to_string(file)?;
-    let document = parser::parse(&content)?;
+    let mut document = parser::parse(&content)?;
+    if swara_only {
+        for section in formatter::migrate_swara_only(&mut document) {
+            println!("{} [{}] is now @swara-only", "🎵".cyan(), section);
+        }
+    }
     let formatted = formatter::format(&document)?;
 
     if content != formatted {
@@ -261,14 +465,269 @@ fn format_file(file: &PathBuf, check_only: bool) -> anyhow::Result<bool> {
     }
 }
 
+fn convert_file(file: &PathBuf, target: sahitya_scheme::SahityaScheme) -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(file)?;
+    let mut document = parser::parse(&content)?;
+    let changed = sahitya_scheme::convert_document(&mut document, target)?;
+
+    if changed == 0 {
+        println!("{} {} is already {}", "✅".bright_black(), file.display(), target.name());
+        return Ok(());
+    }
+
+    let formatted = formatter::format(&document)?;
+    std::fs::write(file, formatted)?;
+    println!(
+        "{} Converted {} sahitya tokens in {} to {}",
+        "✅".green(), changed, file.display(), target.name()
+    );
+    Ok(())
+}
+
+fn show_info_json(file: &PathBuf) -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(file)?;
+    let document = parser::parse(&content)?;
+    let registry = SyllabifierRegistry::for_file(file)?;
+    let report = timeline::build_with(&variants::select(&document, None)?, &registry).report();
+    let tuning = tuning::Tuning::new(&document.metadata);
+    let sangatis: Vec<serde_json::Value> = document
+        .sections
+        .iter()
+        .flat_map(|section| {
+            variants::sangati_groups(section).into_iter().map(move |group| {
+                serde_json::json!({
+                    "section": section.header(),
+                    "line": section.phrases[group.phrases[0]].line_number,
+                    "count": group.phrases.len(),
+                })
+            })
+        })
+        .collect();
+
+    let info = serde_json::json!({
+        "file": file.display().to_string(),
+        "metadata": document.metadata,
+        "sahitya_scheme": sahitya_scheme::detect_document_scheme(&document).name(),
+        "tonic_hz": tuning.tonic_hz,
+        "tuning": tuning.temperament.name(),
+        "default_octave": timeline::default_octave(&document.metadata),
+        "range": note_range(&document).map(|(lowest, highest)| [lowest.to_string(), highest.to_string()]),
+        "pathantaras": variants::variant_names(&document),
+        "sangatis": sangatis,
+        "timing": report,
+    });
+    println!("{}", serde_json::to_string_pretty(&info)?);
+    Ok(())
+}
+
+/// "at 80 BPM", "reaching 80 BPM over 2 avartanams"
+fn describe_tempo(tempo: Tempo) -> String {
+    match tempo.over {
+        Some(1) => format!("reaching {} BPM over 1 avartanam", tempo.bpm),
+        Some(over) => format!("reaching {} BPM over {} avartanams", tempo.bpm, over),
+        None => format!("at {} BPM", tempo.bpm),
+    }
+}
+
+/// "1/2 akshara after samam", "on samam"
+fn describe_eduppu(eduppu: Eduppu) -> String {
+    if eduppu.aksharas.is_zero() {
+        return "on samam".to_string();
+    }
+    let unit = if eduppu.aksharas > ratio::Ratio::from(1) { "aksharas" } else { "akshara" };
+    let side = if eduppu.before_samam { "before" } else { "after" };
+    format!("{} {} {} samam", eduppu.aksharas, unit, side)
+}
+
+/// Format seconds as m:ss
+fn format_duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+enum Transformation {
+    Kalam(u8),
+    /// Target gati, and optionally the line of the one phrase to convert
+    Gati(u8, Option<usize>),
+    /// Default octave to count octave markers from
+    RebaseOctave(i8),
+}
+
+fn transform_file(
+    file: &PathBuf,
+    transformation: Transformation,
+    section: Option<&str>,
+    output: Option<&PathBuf>,
+) -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(file)?;
+    let document = parser::parse(&content)?;
+    let registry = SyllabifierRegistry::for_file(file)?;
+
+    let document = match transformation {
+        Transformation::Kalam(factor) => transform::change_kalam(&document, factor, section, &registry)?,
+        Transformation::Gati(gati, line) => transform::change_gati(&document, gati, section, line, &registry)?,
+        Transformation::RebaseOctave(octave) => transform::rebase_octave(&document, octave)?,
+    };
+
+    let formatted = formatter::format(&document)?;
+    match output {
+        Some(output) => {
+            std::fs::write(output, formatted)?;
+            println!("{} Wrote {}", "✅".green(), output.display());
+        }
+        None => print!("{}", formatted),
+    }
+    Ok(())
+}
+
+fn show_lyrics(
+    file: &PathBuf,
+    target: Option<sahitya_scheme::SahityaScheme>,
+    output: Option<&PathBuf>,
+) -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(file)?;
+    let document = parser::parse(&content)?;
+    let sections = match target {
+        Some(target) => lyrics::extract_in(&document, target)?,
+        None => lyrics::extract(&document),
+    };
+    let text = lyrics::to_text(&sections);
+
+    match output {
+        Some(output) => {
+            std::fs::write(output, text)?;
+            println!("{} Lyrics written to {}", "✅".green(), output.display());
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+fn render_file(file: &PathBuf, variant: Option<&str>, to: &str, output: Option<&PathBuf>) -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(file)?;
+    let document = variants::select(&parser::parse(&content)?, variant)?;
+
+    let text = match to {
+        "vna" => formatter::format(&document)?,
+        "lyrics" => lyrics::to_text(&lyrics::extract(&document)),
+        "pdf" => {
+            let output = output.cloned().unwrap_or_else(|| file.with_extension("pdf"));
+            // Curves are drawn on the sections as written, so not on another school's
+            let curves = if variant.is_none() { gamaka_sidecar::load(file)? } else { None };
+            let pdf_bytes = match curves {
+                Some(curves) => pdf::generate_with_curves(&document, &curves, pdf::DEFAULT_GRID_HEIGHT, "a4")?,
+                None => pdf::generate(&document, pdf::DEFAULT_GRID_HEIGHT, "a4")?,
+            };
+            std::fs::write(&output, pdf_bytes)?;
+            println!("{} {}", "✅ PDF generated:".green(), output.display());
+            return Ok(());
+        }
+        other => return Err(anyhow::anyhow!("Unknown format '{}' (expected vna, pdf or lyrics)", other)),
+    };
+
+    match output {
+        Some(output) => {
+            std::fs::write(output, text)?;
+            println!("{} Wrote {}", "✅".green(), output.display());
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+fn show_rhythm(file: &PathBuf, eduppu: Option<Eduppu>) -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(file)?;
+    let document = parser::parse(&content)?;
+    let analysis = rhythm::analyze(&document, eduppu);
+
+    println!("{} {}", "🥁 Rhythm:".blue().bold(), file.display());
+    for section in &analysis.sections {
+        if section.structures.is_empty() {
+            println!("  {}: no korvais or moras", section.name);
+            continue;
+        }
+        let target = if section.target.aksharas.is_zero() {
+            "samam".to_string()
+        } else {
+            format!("the eduppu, {}", describe_eduppu(section.target))
+        };
+        println!("  {}:", section.name);
+        for korvai in &section.structures {
+            let marked = if korvai.marked { ", marked" } else { "" };
+            println!("    Line {}{}: {}", korvai.line_number, marked, korvai.breakdown());
+
+            match korvai.landing {
+                None => println!("      {} no tala pattern to land on", "⚠️".yellow()),
+                Some(_) if korvai.lands => println!("      {} ends on {}", "✅".green(), target),
+                Some(landing) if landing.is_zero() => {
+                    println!("      {} ends on samam, not on {}", "❌".red(), target)
+                }
+                Some(landing) => println!(
+                    "      {} ends {} aksharas after samam, not on {}",
+                    "❌".red(),
+                    landing,
+                    target
+                ),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn align_line(swara_line: &str, lyrics: &str, language: Option<&str>) -> anyhow::Result<()> {
+    let (swaras, beat_positions) = parser::parse_notation_line(swara_line.trim());
+    let scheme = lyrics
+        .split_whitespace()
+        .find_map(sahitya_scheme::detect_token_scheme)
+        .unwrap_or(sahitya_scheme::SahityaScheme::Iso);
+    let alignment = align::align(&swaras, lyrics, SyllabifierRegistry::shared(), language, scheme)?;
+
+    let phrase = Phrase {
+        swaras: swaras.clone(),
+        sahitya: alignment.sahitya,
+        sahitya_lines: Vec::new(),
+        phrase_analysis: None,
+        line_number: 0,
+        preceding_comments: Vec::new(),
+        gati: None,
+        tala: None,
+        raga: None,
+        tempo: None,
+        kalam: None,
+        sangati: None,
+        directives: Default::default(),
+        beat_positions,
+    };
+    print!("{}", formatter::format_phrase(&phrase)?);
+
+    if !alignment.notes.is_empty() {
+        println!("\n{}", "Check these placements:".yellow());
+        for note in &alignment.notes {
+            println!("  ⚠️ Token {}: {}", note.token + 1, note.message);
+        }
+    }
+    Ok(())
+}
+
 fn generate_pdf(input: &PathBuf, output: &PathBuf, grid_height: u32, page_size: &str) -> anyhow::Result<()> {
     let content = std::fs::read_to_string(input)?;
     let document = parser::parse(&content)?;
-    let pdf_bytes = pdf::generate(&document, grid_height, page_size)?;
+    let pdf_bytes = match gamaka_sidecar::load(input)? {
+        Some(curves) => pdf::generate_with_curves(&document, &curves, grid_height, page_size)?,
+        None => pdf::generate(&document, grid_height, page_size)?,
+    };
     std::fs::write(output, pdf_bytes)?;
     Ok(())
 }
 
+/// Lowest and highest notes of the piece, with octaves from madhya S
+fn note_range(document: &types::VnaDocument) -> Option<(timeline::Note, timeline::Note)> {
+    let notes: Vec<timeline::Note> = timeline::build(document).events.into_iter().filter_map(|e| e.note).collect();
+    let lowest = notes.iter().min_by_key(|n| n.semitones())?.clone();
+    let highest = notes.iter().max_by_key(|n| n.semitones())?.clone();
+    Some((lowest, highest))
+}
+
 fn show_info(file: &PathBuf) -> anyhow::Result<()> {
     let content = std::fs::read_to_string(file)?;
     let document = parser::parse(&content)?;
@@ -285,16 +744,151 @@ fn show_info(file: &PathBuf) -> anyhow::Result<()> {
     if let Some(language) = &document.metadata.language {
         println!("Language: {}", language);
     }
-    if let Some(key) = &document.metadata.key {
-        println!("Key: {}", key);
+    println!("Sahitya scheme: {}", sahitya_scheme::detect_document_scheme(&document).name());
+    let tuning = tuning::Tuning::new(&document.metadata);
+    println!(
+        "Key: {} ({:.1} Hz, {})",
+        document.metadata.key.as_deref().unwrap_or(tuning::DEFAULT_KEY),
+        tuning.tonic_hz,
+        tuning.temperament.name()
+    );
+    let octave = timeline::default_octave(&document.metadata);
+    if octave != 0 {
+        println!("Default octave: {} (unmarked swaras)", timeline::octave_name(octave).unwrap_or_default());
+    }
+    if let Some((lowest, highest)) = note_range(&document) {
+        println!("Range: {} to {} (from madhya S)", lowest, highest);
     }
     if let Some(composition_type) = &document.metadata.composition_type {
         println!("Type: {}", composition_type);
     }
+    if let Some(eduppu) = document.metadata.eduppu {
+        println!("Eduppu: {}", describe_eduppu(eduppu));
+    }
 
     println!("\n{}", "📊 Structure:".cyan());
     for section in &document.sections {
-        println!("  {}: {} phrases", section.name, section.phrases.len());
+        let mut notes = Vec::new();
+        if section.swara_only {
+            notes.push("swara only".to_string());
+        }
+        if let Some(raga) = &section.raga {
+            notes.push(format!("raga {}", raga));
+        }
+        let mut line_ragas: Vec<&str> = Vec::new();
+        for raga in section.phrases.iter().filter_map(|p| p.raga.as_deref()) {
+            if !line_ragas.contains(&raga) {
+                line_ragas.push(raga);
+            }
+        }
+        if !line_ragas.is_empty() {
+            notes.push(format!("lines in {}", line_ragas.join(", ")));
+        }
+        let mut tags: Vec<String> = Vec::new();
+        for tag in section.phrases.iter().flat_map(|p| p.sahitya_lines.iter().map(|l| l.tag())) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        if !tags.is_empty() {
+            notes.push(format!("with {}", tags.join(", ")));
+        }
+        let sangatis: Vec<String> = variants::sangati_groups(section)
+            .iter()
+            .map(|g| g.phrases.len().to_string())
+            .collect();
+        match sangatis.len() {
+            0 => {}
+            1 => notes.push(format!("1 line with {} sangatis", sangatis[0])),
+            lines => notes.push(format!("{} lines with sangatis ({})", lines, sangatis.join(", "))),
+        }
+        let notes = if notes.is_empty() { String::new() } else { format!(" ({})", notes.join("; ")) };
+        println!("  {}: {} phrases{}", section.header(), section.phrases.len(), notes);
+    }
+
+    let schools = variants::variant_names(&document);
+    if !schools.is_empty() {
+        let pathantaras: Vec<String> = schools
+            .iter()
+            .map(|school| {
+                let sections: Vec<&str> = document
+                    .sections
+                    .iter()
+                    .filter(|s| s.variant.as_deref() == Some(school))
+                    .map(|s| s.name.as_str())
+                    .collect();
+                format!("{} ({})", school, sections.join(", "))
+            })
+            .collect();
+        println!("Pathantaras: {}", pathantaras.join("; "));
+    }
+
+    let registry = SyllabifierRegistry::for_file(file)?;
+    // Time the sections as written, without other schools' versions
+    let performed = variants::select(&document, None)?;
+    let report = timeline::build_with(&performed, &registry).report();
+    println!("\n{}", format!("⏱️  Timing at {} BPM:", report.tempo).cyan());
+    for section in &report.sections {
+        let incomplete = match section.incomplete_aksharas {
+            Some(aksharas) => format!(" + incomplete avartanam of {} aksharas", aksharas),
+            None => String::new(),
+        };
+        let eduppu = match section.eduppu {
+            Some(eduppu) => format!(", starting {}", describe_eduppu(eduppu)),
+            None => String::new(),
+        };
+        let mut speed = String::new();
+        if section.tempo != Tempo::from(report.tempo) {
+            speed.push_str(&format!(", {}", describe_tempo(section.tempo)));
+        }
+        if section.kalam > 1 {
+            speed.push_str(&format!(", {} speed", if section.kalam == 2 { "second" } else { "third" }));
+        }
+        println!(
+            "  {}: {} avartanams{}, {} aksharas, {}{}{}",
+            section.name,
+            section.avartanams,
+            incomplete,
+            section.aksharas,
+            format_duration(section.seconds),
+            eduppu,
+            speed
+        );
+    }
+    println!(
+        "  Total: {} aksharas; first speed {}, second speed {}, both speeds {}",
+        report.total_aksharas,
+        format_duration(report.first_speed_seconds),
+        format_duration(report.second_speed_seconds),
+        format_duration(report.both_speeds_seconds)
+    );
+
+    let analysis = prosody::analyze(&document, &registry);
+    println!("\n{}", "🔤 Prosody:".cyan());
+    for section in analysis.sections.iter().filter(|s| !s.lines.is_empty()) {
+        match &section.prasa {
+            Some(prasa) => println!("  {}: prasa '{}'", section.name, prasa),
+            None => println!("  {}: no common prasa", section.name),
+        }
+        for line in &section.lines {
+            let mut notes = Vec::new();
+            if let Some(consonant) = &line.prasa_consonant {
+                let mark = if line.breaks_prasa { " (breaks prasa)" } else { "" };
+                notes.push(format!("second akshara '{}'{}", consonant, mark));
+            }
+            if let Some(yati) = &line.yati {
+                notes.push(format!("yati '{}'", yati));
+            }
+            if let Some((consonant, count)) = line.anuprasa.first() {
+                notes.push(format!("anuprasa '{}' x{}", consonant, count));
+            }
+            println!(
+                "    Line {}: {} - {}",
+                line.line_number + 1,
+                line.syllables.iter().take(2).cloned().collect::<Vec<_>>().join("·"),
+                notes.join(", ")
+            );
+        }
     }
 
     Ok(())
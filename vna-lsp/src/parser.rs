@@ -7,8 +7,215 @@ pub fn parse(content: &str) -> Result<VnaDocument> {
     parser.parse()
 }
 
+/// Split a swara or sahitya line into tokens and the token positions of its `|` beat markers
+/// A repeat group such as `(SRG, PDS)x3` is kept as one token, with single spaces inside
+pub fn parse_notation_line(line: &str) -> (Vec<String>, Vec<usize>) {
+    // Remove || at end
+    let clean_line = line.strip_suffix("||").unwrap_or(line).trim();
+
+    let mut elements = Vec::new();
+    let mut beat_positions = Vec::new();
+    let mut current = String::new();
+    let mut in_group = false;
+
+    for ch in clean_line.chars() {
+        match ch {
+            '(' if current.is_empty() => {
+                in_group = true;
+                current.push(ch);
+            }
+            ')' if in_group => {
+                in_group = false;
+                current.truncate(current.trim_end().len());
+                current.push(ch);
+            }
+            _ if ch.is_whitespace() => {
+                if !in_group {
+                    if !current.is_empty() {
+                        elements.push(std::mem::take(&mut current));
+                    }
+                } else if !current.ends_with(' ') && !current.ends_with('(') {
+                    current.push(' ');
+                }
+            }
+            '|' if !in_group => {
+                if !current.is_empty() {
+                    elements.push(std::mem::take(&mut current));
+                }
+                // Record the beat position (a marker before any token has none)
+                if !elements.is_empty() {
+                    beat_positions.push(elements.len());
+                }
+            }
+            _ => current.push(ch),
+        }
+    }
+    if !current.is_empty() {
+        elements.push(current);
+    }
+
+    (elements, beat_positions)
+}
+
+/// Directives that only apply to a whole section
+const SECTION_DIRECTIVES: &[&str] = &["eduppu", "swara-only"];
+
+/// Directives that only apply to a single line, even before a section's first line
+const LINE_DIRECTIVES: &[&str] = &["sangati"];
+
+/// Directives read into their own fields of a section or phrase
+pub const TYPED_DIRECTIVES: &[&str] = &["gati", "tala", "raga", "eduppu", "tempo", "kalam", "sangati", "swara-only"];
+
+/// Split a `@key: value` directive line into its key and value
+/// Keys are letters, digits, `-` and `_`; a flag such as `@swara-only` has an empty value
+pub fn parse_directive(line: &str) -> Option<(&str, &str)> {
+    let line = line.strip_prefix('@')?;
+    let (key, value) = line.split_once(':').unwrap_or((line, ""));
+    let valid = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then(|| (key, value.trim()))
+}
+
+/// Split a labelled words line such as `sahitya[2]: ...` or `meaning: ...`
+/// into its kind, bracketed label and content
+pub fn parse_sahitya_label(line: &str) -> Option<(SahityaLineKind, Option<&str>, &str)> {
+    let (tag, content) = line.split_once(':')?;
+    let (kind, label) = match tag.trim_end().split_once('[') {
+        Some((kind, label)) => (kind, Some(label.strip_suffix(']')?.trim())),
+        None => (tag.trim_end(), None),
+    };
+    let kind = match kind {
+        "sahitya" if label.is_some() => SahityaLineKind::Sahitya,
+        "meaning" => SahityaLineKind::Meaning,
+        _ => return None,
+    };
+    if label.is_some_and(|l| l.is_empty() || l.contains(char::is_whitespace)) {
+        return None;
+    }
+    Some((kind, label, content.trim()))
+}
+
+/// Directives of a section or line: known keys checked and typed, others kept as written
+#[derive(Default)]
+struct Directives {
+    gati: Option<u8>,
+    tala: Option<String>,
+    raga: Option<String>,
+    eduppu: Option<Eduppu>,
+    tempo: Option<Tempo>,
+    kalam: Option<u8>,
+    sangati: Option<u32>,
+    swara_only: bool,
+    /// Every directive in order, typed ones only marking their place
+    written: Vec<(String, String)>,
+}
+
+impl Directives {
+    fn has(&self, key: &str) -> bool {
+        match key {
+            "gati" => self.gati.is_some(),
+            "tala" => self.tala.is_some(),
+            "raga" => self.raga.is_some(),
+            "eduppu" => self.eduppu.is_some(),
+            "tempo" => self.tempo.is_some(),
+            "kalam" => self.kalam.is_some(),
+            "sangati" => self.sangati.is_some(),
+            "swara-only" => self.swara_only,
+            _ => self.written.iter().any(|(k, _)| k == key),
+        }
+    }
+
+    /// Record a directive from the given (0-based) line
+    fn set(&mut self, key: &str, value: &str, line: usize) -> Result<()> {
+        match key {
+            "gati" => {
+                let gati = value
+                    .parse::<u8>()
+                    .map_err(|_| anyhow!("Invalid gati value at line {}: {}", line + 1, value))?;
+                self.gati = Some(gati);
+            }
+            "tala" => self.tala = Some(value.trim_matches('"').to_string()),
+            "raga" => {
+                let raga = value.trim_matches('"').trim();
+                if raga.is_empty() {
+                    return Err(anyhow!("Missing raga name in @raga at line {}", line + 1));
+                }
+                self.raga = Some(raga.to_string());
+            }
+            "eduppu" => {
+                let eduppu = value
+                    .parse::<Eduppu>()
+                    .map_err(|e| anyhow!("{} at line {}", e, line + 1))?;
+                self.eduppu = Some(eduppu);
+            }
+            "tempo" => {
+                let tempo = value
+                    .parse::<Tempo>()
+                    .map_err(|e| anyhow!("{} at line {}", e, line + 1))?;
+                self.tempo = Some(tempo);
+            }
+            "kalam" => {
+                let kalam = value
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|k| (1..=3).contains(k))
+                    .ok_or_else(|| anyhow!("Invalid kalam value at line {}: {} (expected 1, 2 or 3)", line + 1, value))?;
+                self.kalam = Some(kalam);
+            }
+            "sangati" => {
+                let sangati = value
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| anyhow!("Invalid sangati number at line {}: {}", line + 1, value))?;
+                self.sangati = Some(sangati);
+            }
+            "swara-only" => {
+                if !value.is_empty() {
+                    return Err(anyhow!("@swara-only at line {} takes no value", line + 1));
+                }
+                self.swara_only = true;
+            }
+            _ => {
+                self.written.push((key.to_string(), value.to_string()));
+                return Ok(());
+            }
+        }
+        self.written.push((key.to_string(), String::new()));
+        Ok(())
+    }
+}
+
+/// Sangati 2 must follow the line it varies, and each later sangati the one before it
+fn check_sangatis(phrases: &[Phrase]) -> Result<()> {
+    let mut previous: Option<&Phrase> = None;
+    for phrase in phrases {
+        let expected = match phrase.sangati {
+            Some(n) if n > 1 => n - 1,
+            _ => {
+                previous = Some(phrase);
+                continue;
+            }
+        };
+        let follows = match previous {
+            Some(previous) if expected == 1 => previous.sangati.is_none_or(|p| p == 1),
+            Some(previous) => previous.sangati == Some(expected),
+            None => false,
+        };
+        if !follows {
+            let after = if expected == 1 { "the line it varies".to_string() } else { format!("sangati {}", expected) };
+            return Err(anyhow!(
+                "Sangati {} at line {} must follow {}",
+                expected + 1,
+                phrase.line_number,
+                after
+            ));
+        }
+        previous = Some(phrase);
+    }
+    Ok(())
+}
+
 struct VnaParser {
-    content: String,
     lines: Vec<String>,
     current_line: usize,
 }
@@ -17,7 +224,6 @@ impl VnaParser {
     fn new(content: &str) -> Self {
         let lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
         Self {
-            content: content.to_string(),
             lines,
             current_line: 0,
         }
@@ -87,9 +293,9 @@ impl VnaParser {
                 continue;
             }
 
-            if line.starts_with('#') {
+            if let Some(text) = line.strip_prefix('#') {
                 comments.push(Comment {
-                    text: line[1..].trim().to_string(),
+                    text: text.trim().to_string(),
                     line_number: self.current_line + 1,
                     comment_type: CommentType::Line,
                 });
@@ -121,14 +327,23 @@ impl VnaParser {
             return Err(anyhow!("Invalid section header at line {}", section_line + 1));
         }
 
-        let name = line[1..line.len()-1].to_string();
+        // A pathantara names its school after the section: [pallavi@semmangudi]
+        let header = &line[1..line.len()-1];
+        let (name, variant) = match header.split_once('@') {
+            Some((name, variant)) => {
+                if name.trim().is_empty() || variant.trim().is_empty() {
+                    return Err(anyhow!("Invalid section header at line {}: expected [section@variant]", section_line + 1));
+                }
+                (name.trim().to_string(), Some(variant.trim().to_string()))
+            }
+            None => (header.to_string(), None),
+        };
         self.advance_line();
 
         let mut phrases = Vec::new();
         let mut pending_comments = Vec::new();
         let mut section_comments = Vec::new();
-        let mut section_gati = None;
-        let mut section_tala = None;
+        let mut directives = Directives::default();
 
         while self.current_line < self.lines.len() {
             let line = self.current_line_trimmed();
@@ -140,9 +355,9 @@ impl VnaParser {
             }
 
             // Comment - collect
-            if line.starts_with('#') {
+            if let Some(text) = line.strip_prefix('#') {
                 let comment = Comment {
-                    text: line[1..].trim().to_string(),
+                    text: text.trim().to_string(),
                     line_number: self.current_line + 1,
                     comment_type: CommentType::Line,
                 };
@@ -154,36 +369,30 @@ impl VnaParser {
             // New section - break
             if line.starts_with('[') && line.ends_with(']') {
                 // Any pending comments belong to the section
-                section_comments.extend(pending_comments.drain(..));
+                section_comments.append(&mut pending_comments);
                 break;
             }
 
-            // Gati annotation
-            if line.starts_with("@gati:") {
-                // Section-level gati override
-                let gati_str = line[6..].trim();
-                if let Ok(gati) = gati_str.parse::<u8>() {
-                    section_gati = Some(gati);
-                } else {
-                    return Err(anyhow!("Invalid gati value at line {}: {}", self.current_line + 1, gati_str));
+            // Directives: section-level before the first phrase (once each), line-level after it
+            if let Some((key, value)) = parse_directive(&line) {
+                if phrases.is_empty() && !directives.has(key) && !LINE_DIRECTIVES.contains(&key) {
+                    directives.set(key, value, self.current_line)?;
+                    self.advance_line();
+                    continue;
+                }
+                if SECTION_DIRECTIVES.contains(&key) {
+                    return Err(anyhow!(
+                        "@{} at line {} must come once, before the first line of the section",
+                        key,
+                        self.current_line + 1
+                    ));
                 }
-                self.advance_line();
-                continue;
-            }
-
-            // Tala annotation
-            if line.starts_with("@tala:") {
-                // Section-level tala override
-                let tala_str = line[6..].trim().trim_matches('"');
-                section_tala = Some(tala_str.to_string());
-                self.advance_line();
-                continue;
             }
 
-            // Notation line - parse phrase
-            if line.contains('|') {
-                let mut phrase = self.parse_phrase()?;
-                phrase.preceding_comments = pending_comments.drain(..).collect();
+            // Notation line, possibly after line-level directives - parse phrase
+            if line.contains('|') || parse_directive(&line).is_some() {
+                let mut phrase = self.parse_phrase(directives.swara_only)?;
+                phrase.preceding_comments = std::mem::take(&mut pending_comments);
                 phrases.push(phrase);
                 continue;
             }
@@ -198,45 +407,50 @@ impl VnaParser {
 
         // Any remaining comments belong to the section
         section_comments.extend(pending_comments);
+        check_sangatis(&phrases)?;
 
         Ok(Section {
             name,
+            variant,
             phrases,
             line_number: section_line + 1,
             comments: section_comments,
-            gati: section_gati,
-            tala: section_tala,
+            gati: directives.gati,
+            tala: directives.tala,
+            eduppu: directives.eduppu,
+            raga: directives.raga,
+            tempo: directives.tempo,
+            kalam: directives.kalam,
+            swara_only: directives.swara_only,
+            directives: directives.written,
         })
     }
 
-    fn parse_phrase(&mut self) -> Result<Phrase> {
+    /// A phrase: swara line and sahitya line, or only the swara line in a
+    /// `@swara-only` section
+    fn parse_phrase(&mut self, swara_only: bool) -> Result<Phrase> {
         let phrase_start_line = self.current_line;
-        let mut line_gati = None;
-        let mut line_tala = None;
+        let mut directives = Directives::default();
 
-        // Check for line-level annotations before the swara line
+        // Line-level directives before the swara line
         while self.current_line < self.lines.len() {
             let line = self.current_line_trimmed();
-            
-            if line.starts_with("@gati:") {
-                let gati_str = line[6..].trim();
-                if let Ok(gati) = gati_str.parse::<u8>() {
-                    line_gati = Some(gati);
-                } else {
-                    return Err(anyhow!("Invalid gati value at line {}: {}", self.current_line + 1, gati_str));
-                }
-                self.advance_line();
-            } else if line.starts_with("@tala:") {
-                let tala_str = line[6..].trim().trim_matches('"');
-                line_tala = Some(tala_str.to_string());
-                self.advance_line();
-            } else {
-                break; // Not an annotation, must be the swara line
+            let Some((key, value)) = parse_directive(&line) else {
+                break; // Not a directive, must be the swara line
+            };
+            if SECTION_DIRECTIVES.contains(&key) {
+                return Err(anyhow!(
+                    "@{} at line {} must come once, before the first line of the section",
+                    key,
+                    self.current_line + 1
+                ));
             }
+            directives.set(key, value, self.current_line)?;
+            self.advance_line();
         }
 
         // Expect at least 2 lines: swara, sahitya
-        if self.current_line + 1 >= self.lines.len() {
+        if !swara_only && self.current_line + 1 >= self.lines.len() {
             return Err(anyhow!(
                 "Incomplete phrase at line {} - need at least 2 lines (swara, sahitya)",
                 phrase_start_line + 1
@@ -244,6 +458,7 @@ impl VnaParser {
         }
 
         // Parse swara line
+        let swara_line_number = self.current_line + 1;
         let swara_line = self.current_line_trimmed();
         if !swara_line.contains('|') {
             return Err(anyhow!(
@@ -251,86 +466,105 @@ impl VnaParser {
                 self.current_line + 1
             ));
         }
-        let (swaras, swara_beats) = self.parse_notation_line_with_beats(&swara_line)?;
+        let (swaras, swara_beats) = parse_notation_line(&swara_line);
         self.advance_line();
 
         // Parse sahitya line
-        let sahitya_line = self.current_line_trimmed();
-        if !sahitya_line.contains('|') {
-            return Err(anyhow!(
-                "Invalid sahitya line at {}: missing beat markers",
-                self.current_line + 1
-            ));
+        let mut sahitya = Vec::new();
+        if !swara_only {
+            let sahitya_line = self.current_line_trimmed();
+            if !sahitya_line.contains('|') {
+                return Err(anyhow!(
+                    "Invalid sahitya line at {}: missing beat markers",
+                    self.current_line + 1
+                ));
+            }
+            let (tokens, sahitya_beats) = parse_notation_line(&sahitya_line);
+            self.advance_line();
+
+            // Verify beat alignment
+            if swara_beats != sahitya_beats {
+                return Err(anyhow!(
+                    "Beat markers misaligned between swara and sahitya lines at line {}",
+                    phrase_start_line + 1
+                ));
+            }
+            sahitya = tokens;
         }
-        let (sahitya, sahitya_beats) = self.parse_notation_line_with_beats(&sahitya_line)?;
-        self.advance_line();
 
-        // Check for optional phrase analysis line
+        // Optional labelled words lines and phrase analysis line, in any order
+        // (a swara-only phrase has no words, so only its analysis)
+        let mut sahitya_lines = Vec::new();
         let mut phrase_analysis = None;
-        if self.current_line < self.lines.len() {
+        while self.current_line < self.lines.len() {
             let next_line = self.current_line_trimmed();
-            if next_line.starts_with("phrases = ") {
-                phrase_analysis = Some(next_line[10..].to_string());
-                self.advance_line();
+            if let Some(analysis) = next_line.strip_prefix("phrases = ") {
+                if phrase_analysis.is_some() {
+                    break;
+                }
+                phrase_analysis = Some(analysis.to_string());
+            } else if let Some((kind, label, content)) = parse_sahitya_label(&next_line).filter(|_| !swara_only) {
+                sahitya_lines.push(self.parse_sahitya_line(kind, label, content, &swara_beats)?);
+            } else {
+                break;
             }
-        }
-
-        // Verify beat alignment
-        if swara_beats != sahitya_beats {
-            return Err(anyhow!(
-                "Beat markers misaligned between swara and sahitya lines at line {}",
-                phrase_start_line + 1
-            ));
+            self.advance_line();
         }
 
         Ok(Phrase {
             swaras,
             sahitya,
+            sahitya_lines,
             phrase_analysis,
-            line_number: phrase_start_line + 1,
+            line_number: swara_line_number,
             preceding_comments: Vec::new(), // Will be filled by parse_section
-            gati: line_gati,
-            tala: line_tala,
+            gati: directives.gati,
+            tala: directives.tala,
+            raga: directives.raga,
+            tempo: directives.tempo,
+            kalam: directives.kalam,
+            sangati: directives.sangati,
+            directives: directives.written,
             beat_positions: swara_beats,
         })
     }
 
-    fn parse_notation_line(&self, line: &str) -> Result<Vec<String>> {
-        let (elements, _) = self.parse_notation_line_with_beats(line)?;
-        Ok(elements)
-    }
-
-    fn parse_notation_line_with_beats(&self, line: &str) -> Result<(Vec<String>, Vec<usize>)> {
-        // Remove || at end
-        let clean_line = if line.ends_with("||") {
-            &line[..line.len() - 2]
-        } else {
-            line
-        }.trim();
-        
-        let mut elements = Vec::new();
-        let mut beat_positions = Vec::new();
-        let mut current_pos = 0;
-        
-        // Split by | to get beats
-        let beats: Vec<&str> = clean_line.split('|').collect();
-        
-        for (i, beat) in beats.iter().enumerate() {
-            let beat_elements: Vec<&str> = beat.trim().split_whitespace().collect();
-            for element in beat_elements {
-                if !element.is_empty() {
-                    elements.push(element.to_string());
-                    current_pos += 1;
-                }
-            }
-            
-            // Record beat position after this beat (except for last beat)
-            if i < beats.len() - 1 && current_pos > 0 {
-                beat_positions.push(current_pos);
-            }
+    /// A labelled words line at the current line; sung lines must follow the
+    /// swaras' beat markers, a meaning may instead be free text
+    fn parse_sahitya_line(
+        &self,
+        kind: SahityaLineKind,
+        label: Option<&str>,
+        content: &str,
+        swara_beats: &[usize],
+    ) -> Result<SahityaLine> {
+        let line_number = self.current_line + 1;
+        let label = label.map(|l| l.to_string());
+        if kind == SahityaLineKind::Meaning && !content.contains('|') {
+            return Ok(SahityaLine {
+                kind,
+                label,
+                syllables: Vec::new(),
+                text: Some(content.to_string()),
+                line_number,
+            });
+        }
+        if !content.contains('|') {
+            return Err(anyhow!(
+                "Invalid sahitya line at {}: missing beat markers",
+                line_number
+            ));
         }
 
-        Ok((elements, beat_positions))
+        let (syllables, beats) = parse_notation_line(content);
+        if beats != swara_beats {
+            return Err(anyhow!(
+                "Beat markers misaligned between swara and {} lines at line {}",
+                kind.name(),
+                line_number
+            ));
+        }
+        Ok(SahityaLine { kind, label, syllables, text: None, line_number })
     }
 
     fn current_line_trimmed(&self) -> String {
@@ -357,6 +591,7 @@ impl VnaParser {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ratio::Ratio;
 
     #[test]
     fn test_parse_complete_file() {
@@ -387,6 +622,77 @@ pa da sa da | pa ga ra sa ||
         assert_eq!(doc.sections[0].phrases.len(), 1);
     }
 
+    #[test]
+    fn test_parse_gati_levels() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[muktayisvaram]
+@gati: 3
+SRG MPD | GRS DPM ||
+--- --- | --- --- ||
+
+@gati: 5
+@tala: "+23"
+SRGMP | DPMGR ||
+----- | ----- ||
+
+SRG MPD | GRS DPM ||
+--- --- | --- --- ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let section = &doc.sections[0];
+        assert_eq!(section.gati, Some(3));
+        assert_eq!(section.tala, None);
+        assert_eq!(section.phrases.len(), 3);
+        assert_eq!(section.phrases[0].gati, None);
+        assert_eq!(section.phrases[1].gati, Some(5));
+        assert_eq!(section.phrases[1].tala.as_deref(), Some("+23"));
+        // The phrase's line number is its swara line, after the annotations
+        assert_eq!(section.phrases[1].line_number, 14);
+        assert_eq!(section.phrases[2].gati, None);
+    }
+
+    #[test]
+    fn test_parse_raga_levels() {
+        let content = r#"---
+title: "Test"
+raga: "ragamalika"
+tala: "+234+0+0"
+---
+
+[pallavi]
+@raga: "kalyani"
+SRGM PDNS' | S'NDP MGRS ||
+---- ---- | ---- ---- ||
+
+@raga: mohanam
+SRGP DS'DP | GRS, SRGR ||
+---- ---- | ---- ---- ||
+
+[anupallavi]
+SRGM PDNS' | S'NDP MGRS ||
+---- ---- | ---- ---- ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let section = &doc.sections[0];
+        assert_eq!(section.raga.as_deref(), Some("kalyani"));
+        assert_eq!(section.phrases[0].raga, None);
+        assert_eq!(section.phrases[1].raga.as_deref(), Some("mohanam"));
+        assert_eq!(doc.sections[1].raga, None);
+
+        let formatted = crate::formatter::format(&doc).unwrap();
+        assert_eq!(parse(&formatted).unwrap(), doc);
+
+        let empty = content.replace("@raga: mohanam", "@raga:");
+        assert!(parse(&empty).unwrap_err().to_string().contains("line 12"));
+    }
+
     #[test]
     fn test_parse_invalid_yaml() {
         let content = r#"---
@@ -455,4 +761,213 @@ phrases = (_ *)* *   * *
         let doc = result.unwrap();
         assert_eq!(doc.sections[0].phrases[0].phrase_analysis, Some("(_ *)* *   * *".to_string()));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_directives() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[muktayisvaram]
+@tala: "+234+0+0"
+@source: Book of varnams, p. 12
+@tempo: 72
+SRGR GRSR | PDSR SRGR ||
+---- ---- | ---- ---- ||
+
+@kalam: 2
+@fingering: 1-2-1-3
+SRGR GRSR | PDSR SRGR ||
+---- ---- | ---- ---- ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let section = &doc.sections[0];
+        assert_eq!(section.tala.as_deref(), Some("+234+0+0"));
+        let keys: Vec<&str> = section.directives.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, ["tala", "source", "tempo"]);
+        assert_eq!(section.directives[1].1, "Book of varnams, p. 12");
+        assert_eq!(section.tempo, Some(Tempo::from(72)));
+        assert!(section.phrases[0].directives.is_empty());
+        assert_eq!(section.phrases[1].kalam, Some(2));
+        assert_eq!(section.phrases[1].directives[1], ("fingering".to_string(), "1-2-1-3".to_string()));
+
+        // Every directive survives formatting in its place, including @tala
+        let formatted = crate::formatter::format(&doc).unwrap();
+        assert!(formatted.contains("@tala: \"+234+0+0\"\n@source: Book of varnams, p. 12\n@tempo: 72\n"));
+        assert_eq!(parse(&formatted).unwrap(), doc);
+
+        // Known keys are checked
+        let error = parse(&content.replace("@tempo: 72", "@tempo: fast")).unwrap_err();
+        assert!(error.to_string().contains("'fast'") && error.to_string().ends_with("at line 10"), "{}", error);
+        assert_eq!(parse(&content.replace("@tempo: 72", "@tempo: 72 over 2")).unwrap().sections[0].tempo.unwrap().over, Some(2));
+        assert!(parse(&content.replace("@tempo: 72", "@tempo: 72 over")).is_err());
+        assert!(parse(&content.replace("@kalam: 2", "@kalam: 5")).is_err());
+        assert_eq!(parse_directive("@gati 3"), None);
+        assert_eq!(parse_directive("@swara_notes:  fast "), Some(("swara_notes", "fast")));
+    }
+
+    #[test]
+    fn test_parse_sahitya_lines() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[charanam]
+             G,RG | RSR, ||
+             ma-- | hi-- ||
+phrases = (__)
+sahitya[2]:  sā-- | mi-- ||
+sahitya[te]: మ-- | హి-- ||
+meaning:     great | one ||
+meaning[ta]: the great one
+
+S,RG | PDS' ||
+ta-- | nu-- ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let phrase = &doc.sections[0].phrases[0];
+        assert_eq!(phrase.phrase_analysis.as_deref(), Some("(__)"));
+        let tags: Vec<String> = phrase.sahitya_lines.iter().map(|l| l.tag()).collect();
+        assert_eq!(tags, vec!["sahitya[2]", "sahitya[te]", "meaning", "meaning[ta]"]);
+        assert_eq!(phrase.sahitya_lines[0].syllables, vec!["sā--", "mi--"]);
+        assert_eq!(phrase.sahitya_lines[0].verse(), Some(2));
+        assert_eq!(phrase.sahitya_lines[1].verse(), None);
+        assert_eq!(phrase.sahitya_lines[1].line_number, 12);
+        assert_eq!(phrase.sahitya_lines[3].text.as_deref(), Some("the great one"));
+        assert!(doc.sections[0].phrases[1].sahitya_lines.is_empty());
+
+        // Labelled lines are indented past, so every column lines up
+        let formatted = crate::formatter::format(&doc).unwrap();
+        assert!(formatted.contains("             G,RG  | RSR, ||\n"), "{}", formatted);
+        assert!(formatted.contains("\nsahitya[2]:  sā--  | mi-- ||\nsahitya[te]: మ--   | హి-- ||\n"), "{}", formatted);
+        assert_eq!(parse(&formatted).unwrap(), doc);
+
+        // Sung lines must follow the swaras' beat markers
+        let error = parse(&content.replace("sā-- | mi--", "sā-- mi--")).unwrap_err();
+        assert_eq!(error.to_string(), "Beat markers misaligned between swara and sahitya lines at line 11");
+        assert!(parse(&content.replace("sā-- | mi-- ||", "sā-- mi--")).is_err());
+        assert_eq!(parse_sahitya_label("sahitya: x ||"), None);
+        assert_eq!(parse_sahitya_label("SRG:3 | PDS ||"), None);
+    }
+
+    #[test]
+    fn test_parse_swara_only() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[cittasvaram]
+@swara-only
+# 1.
+G,,, ,,,R | ,S,R ,,D., ||
+phrases = (_ *)
+G,GR GRSR | G,G.P D.D.SR ||
+
+[charanam]
+G,G, GPGR | RGPG P,,, ||
+sa-- ---- | ---- ---- ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let section = &doc.sections[0];
+        assert!(section.swara_only);
+        assert!(!doc.sections[1].swara_only);
+        assert_eq!(section.phrases.len(), 2);
+        assert!(section.phrases[0].sahitya.is_empty());
+        assert_eq!(section.phrases[0].phrase_analysis.as_deref(), Some("(_ *)"));
+        assert_eq!(section.phrases[1].line_number, 12);
+        assert_eq!(section.phrases[1].beat_positions, vec![2]);
+
+        let error = parse(&content.replace("G,GR GRSR", "@swara-only\nG,GR GRSR")).unwrap_err();
+        assert_eq!(error.to_string(), "@swara-only at line 12 must come once, before the first line of the section");
+        let error = parse(&content.replace("@swara-only", "@swara-only: yes")).unwrap_err();
+        assert_eq!(error.to_string(), "@swara-only at line 8 takes no value");
+        assert_eq!(parse_directive("@swara-only"), Some(("swara-only", "")));
+    }
+
+    #[test]
+    fn test_parse_variants() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+@sangati: 1
+@raga: "mohanam"
+G,G, R,,, ||
+nin- kō-- ||
+
+@sangati: 2
+G,GR R,,, ||
+nin- kō-- ||
+
+[pallavi @ semmangudi]
+G,G, RSR, ||
+nin- kō-- ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let pallavi = &doc.sections[0];
+        // A line-only directive makes the directives with it line-level too
+        assert_eq!(pallavi.raga, None);
+        assert_eq!(pallavi.phrases[0].raga.as_deref(), Some("mohanam"));
+        assert_eq!(pallavi.phrases[0].sangati, Some(1));
+        assert_eq!(pallavi.phrases[1].sangati, Some(2));
+        assert_eq!(doc.sections[1].name, "pallavi");
+        assert_eq!(doc.sections[1].variant.as_deref(), Some("semmangudi"));
+
+        let formatted = crate::formatter::format(&doc).unwrap();
+        assert!(formatted.contains("[pallavi@semmangudi]\n"));
+        assert!(formatted.contains("@sangati: 1\n@raga: \"mohanam\"\n"));
+        assert_eq!(parse(&formatted).unwrap().sections, doc.sections);
+
+        let error = parse(&content.replace("@sangati: 2", "@sangati: 3")).unwrap_err();
+        assert_eq!(error.to_string(), "Sangati 3 at line 14 must follow sangati 2");
+        let error = parse(&content.replace("@sangati: 1\n", "@sangati: 2\n")).unwrap_err();
+        assert_eq!(error.to_string(), "Sangati 2 at line 10 must follow the line it varies");
+        assert!(parse(&content.replace("@sangati: 2", "@sangati: 0")).is_err());
+        assert!(parse(&content.replace("[pallavi @ semmangudi]", "[pallavi@]")).is_err());
+    }
+
+    #[test]
+    fn test_parse_eduppu() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+eduppu: 0.75
+---
+
+[pallavi]
+@eduppu: -1/2
+SRGR | GRSR ||
+---- | ---- ||
+"#;
+
+        let doc = parse(content).unwrap();
+        assert_eq!(doc.metadata.eduppu, Some(Eduppu { aksharas: Ratio::new(3, 4), before_samam: false }));
+        let section_eduppu = doc.sections[0].eduppu.unwrap();
+        assert!(section_eduppu.before_samam);
+        assert_eq!(section_eduppu.offset_in(8), Ratio::new(15, 2));
+
+        // The formatter writes both back
+        let formatted = crate::formatter::format(&doc).unwrap();
+        assert!(formatted.contains("eduppu: \"3/4\"") && formatted.contains("@eduppu: -1/2"));
+        assert_eq!(parse(&formatted).unwrap(), doc);
+
+        // Section-level only, and must be a number of aksharas
+        let late = content.replace("---- | ---- ||\n", "---- | ---- ||\n@eduppu: 1\nSRGR | GRSR ||\n---- | ---- ||\n");
+        assert!(parse(&late).unwrap_err().to_string().contains("before the first line"));
+        assert!(parse(&content.replace("-1/2", "half")).is_err());
+    }
+}
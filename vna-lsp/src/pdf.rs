@@ -1,23 +1,47 @@
+use crate::ratio::Ratio;
+use crate::repeat;
+use crate::swara_parser::{parse_swara_units, split_token_gati};
+use crate::timeline::{default_octave, octave_name, tala_aksharas, Note, DEFAULT_GATI};
+use crate::raga::RagaScales;
+use crate::tuning::Tuning;
+use crate::gamaka_sidecar::CurveData;
+use crate::pitch_contour::{canvas_degree, swara_degree, CANVAS_LEFT, CANVAS_RIGHT};
 use crate::types::*;
 use anyhow::{anyhow, Result};
 use std::fs;
 use std::process::Command;
 
-pub fn generate(document: &VnaDocument, grid_height: u32, page_size: &str) -> Result<Vec<u8>> {
-    let mut generator = LatexPdfGenerator::new(grid_height, page_size)?;
+/// Grid height to pass to `generate`, which no longer uses it
+pub const DEFAULT_GRID_HEIGHT: u32 = 60;
+
+/// `_grid_height` is no longer used; the staff lines carry the frequencies
+pub fn generate(document: &VnaDocument, _grid_height: u32, page_size: &str) -> Result<Vec<u8>> {
+    let mut generator = LatexPdfGenerator::new(page_size)?;
+    generator.generate(document)
+}
+
+/// Like `generate`, drawing the editor's gamaka curves on the staff lines
+pub fn generate_with_curves(document: &VnaDocument, curves: &CurveData, _grid_height: u32, page_size: &str) -> Result<Vec<u8>> {
+    let mut generator = LatexPdfGenerator::new(page_size)?;
+    generator.curves = Some(curves.clone());
     generator.generate(document)
 }
 
+/// Height of the staff lines in the PDF, in cm
+const STAFF_HEIGHT: f64 = 4.0;
+/// Swaras of the staff lines from the bottom, 0.4cm apart
+const STAFF_LINES: [&str; 11] = ["P.", "D.", "N.", "S", "R", "G", "M", "P", "D", "N", "S'"];
+
 struct LatexPdfGenerator {
-    grid_height: u32,
     page_size: String,
+    curves: Option<CurveData>,
 }
 
 impl LatexPdfGenerator {
-    fn new(grid_height: u32, page_size: &str) -> Result<Self> {
+    fn new(page_size: &str) -> Result<Self> {
         Ok(Self {
-            grid_height,
             page_size: page_size.to_string(),
+            curves: None,
         })
     }
 
@@ -83,7 +107,7 @@ impl LatexPdfGenerator {
 \newcommand{{\merge}}[1]{{\texttt{{\footnotesize #1}}}}
 
 % Staff lines command
-\newcommand{{\stafflines}}{{%
+\newcommand{{\stafflines}}[1][]{{%
 \vspace{{0.3em}}
 \noindent\begin{{tikzpicture}}[baseline=0pt]
 % Staff lines - from mandra P to tara P (octave + fourth)
@@ -95,10 +119,16 @@ impl LatexPdfGenerator {
 % Make S and P lines solid for reference
 \draw[darkgray, solid, thick] (0,1.2) -- (\textwidth,1.2); % S
 \draw[darkgray, solid, thick] (0,2.8) -- (\textwidth,2.8); % P'
+% Frequencies of the lines in the section's raga
+\gridlabels
+% Drawn gamaka curves, if any
+#1
 \end{{tikzpicture}}
 \vspace{{0.5em}}
 }}
 
+\newcommand{{\gridlabels}}{{}}
+
 \setlength{{\parindent}}{{0pt}}
 \setlength{{\parskip}}{{0pt}}
 
@@ -110,7 +140,7 @@ impl LatexPdfGenerator {
         latex.push_str(&format!(r#"
 {{\Large \textbf{{{}}}}}
 
-\textit{{Raga: {} | Tala: {} | Composer: {} | Tempo: {} BPM}}
+\textit{{Raga: {} | Tala: {} | Composer: {} | Tempo: {} BPM{}}}
 
 \vspace{{1em}}
 
@@ -119,18 +149,33 @@ impl LatexPdfGenerator {
             self.escape_latex(&document.metadata.raga),
             self.escape_latex(&document.metadata.tala),
             self.escape_latex(document.metadata.composer.as_deref().unwrap_or("Unknown")),
-            document.metadata.tempo.unwrap_or(60)
+            document.metadata.tempo.unwrap_or(60),
+            // Unmarked swaras are in another octave than madhya
+            octave_name(default_octave(&document.metadata))
+                .filter(|name| *name != "madhya")
+                .map(|name| format!(" | Octave: {}", name))
+                .unwrap_or_default()
         ));
 
         // Sections
         for section in &document.sections {
+            let raga = match &section.raga {
+                Some(raga) => format!(" \\quad \\textit{{Raga: {}}}", self.escape_latex(raga)),
+                None => String::new(),
+            };
             latex.push_str(&format!(r#"
-\textbf{{[{}]}}
+\textbf{{[{}]}}{}
 
-"#, self.escape_latex(&section.name)));
+"#, self.escape_latex(&section.header()), raga));
+            latex.push_str(&format!("\\renewcommand{{\\gridlabels}}{{{}}}\n\n", grid_labels(document, section)));
 
-            for phrase in &section.phrases {
-                self.add_phrase_latex(&mut latex, phrase)?;
+            for (i, phrase) in section.phrases.iter().enumerate() {
+                if let Some(raga) = &phrase.raga {
+                    latex.push_str(&format!("\\textit{{Raga: {}}}\n\n", self.escape_latex(raga)));
+                }
+                let blanks = if i == 0 { leading_blanks(document, section, phrase) } else { 0 };
+                self.add_phrase_latex(&mut latex, phrase, blanks)?;
+                self.add_staff_latex(&mut latex, section, i);
             }
             
             latex.push_str("\\vspace{0.5em}\n\n");
@@ -141,26 +186,65 @@ impl LatexPdfGenerator {
         Ok(latex)
     }
 
-    fn add_phrase_latex(&self, latex: &mut String, phrase: &Phrase) -> Result<()> {
-        // Create aligned table with proper spacing
-        let table_content = self.create_aligned_table(&phrase.swaras, &phrase.sahitya);
+    fn add_phrase_latex(&self, latex: &mut String, phrase: &Phrase, leading_blanks: usize) -> Result<()> {
+        // Blank cells before the eduppu, then the aligned tokens
+        let blanks = vec![String::new(); leading_blanks];
+        let swaras = [blanks.clone(), phrase.swaras.clone()].concat();
+        // A swara-only phrase has no sahitya row
+        let mut words = Vec::new();
+        if !phrase.sahitya.is_empty() {
+            words.push([blanks.clone(), phrase.sahitya.clone()].concat());
+        }
+        for line in phrase.sahitya_lines.iter().filter(|l| l.is_aligned()) {
+            words.push([blanks.clone(), line.syllables.clone()].concat());
+        }
+        let table_content = self.create_aligned_table(&swaras, &words);
         
         latex.push_str(&table_content);
-        
-        // Staff lines for hand notation
-        latex.push_str(r#"\stafflines
 
-"#);
+        // Free-text meanings under the table
+        for text in phrase.sahitya_lines.iter().filter_map(|l| l.text.as_ref()) {
+            latex.push_str(&format!("\\textit{{{}}}\n\n", self.escape_latex(text)));
+        }
         
         Ok(())
     }
 
-    fn create_aligned_table(&self, swaras: &[String], sahitya: &[String]) -> String {
+    /// Staff lines for hand notation, with the phrase's drawn curves
+    fn add_staff_latex(&self, latex: &mut String, section: &Section, phrase_index: usize) {
+        // Each drawn point goes to the line of the swara it is at on the canvas
+        let bottom = Note::parse(STAFF_LINES[0]).map_or(0, |note| swara_degree(&note)) as f64;
+        let spacing = STAFF_HEIGHT / (STAFF_LINES.len() - 1) as f64;
+        let curves: Vec<String> = self
+            .curves
+            .iter()
+            .flat_map(|c| c.for_phrase(section, phrase_index))
+            .filter(|curve| curve.points.len() > 1)
+            .map(|curve| {
+                let coordinates: Vec<String> = curve.points.iter().map(|p| {
+                    let x = (p.x - CANVAS_LEFT) / (CANVAS_RIGHT - CANVAS_LEFT);
+                    let y = (canvas_degree(p.y) - bottom) * spacing;
+                    format!("({:.3}\\textwidth,{:.2}cm)", x, y)
+                }).collect();
+                format!("\\draw[blue, thick] plot[smooth] coordinates {{{}}};", coordinates.join(" "))
+            })
+            .collect();
+
+        if curves.is_empty() {
+            latex.push_str("\\stafflines\n\n");
+        } else {
+            // Braced so the curves' own brackets don't end the optional argument
+            latex.push_str(&format!("\\stafflines[{{{}}}]\n\n", curves.join("\n")));
+        }
+    }
+
+    /// The swara row, then one row per words line
+    fn create_aligned_table(&self, swaras: &[String], words: &[Vec<String>]) -> String {
         let mut latex = String::new();
         
         // Calculate number of beats (groups of 4)
-        let max_len = swaras.len().max(sahitya.len());
-        let num_beats = (max_len + 3) / 4;
+        let max_len = words.iter().map(|w| w.len()).fold(swaras.len(), usize::max);
+        let num_beats = max_len.div_ceil(4);
         
         // Create table with fixed column widths across full page
         latex.push_str(r#"\noindent\begin{tabularx}{\textwidth}{@{}"#);
@@ -168,39 +252,30 @@ impl LatexPdfGenerator {
         // Create column specification - each beat gets equal space with separators
         for i in 0..num_beats {
             if i > 0 {
-                latex.push_str("c"); // Center column for beat marker
+                latex.push('c'); // Center column for beat marker
             }
-            latex.push_str("X"); // Expandable column for content
+            latex.push('X'); // Expandable column for content
         }
         latex.push_str("r@{}}\n"); // Right align for final ||
         
         // Build swara row
-        let mut beat_idx = 0;
-        for beat_num in 0..num_beats {
-            if beat_num > 0 {
-                latex.push_str(" & \\texttt{|} & ");
-            }
-            
-            // Collect elements for this beat
-            let mut beat_elements = Vec::new();
-            for _ in 0..4 {
-                if beat_idx < swaras.len() {
-                    beat_elements.push(format!("\\texttt{{{}}}", self.escape_latex(&swaras[beat_idx])));
-                    beat_idx += 1;
-                }
-            }
-            
-            // Create evenly spaced content within the cell
-            if beat_elements.is_empty() {
-                latex.push_str("\\phantom{M}");
-            } else {
-                latex.push_str(&format!("\\makebox[\\linewidth][s]{{{}}}", beat_elements.join("\\hfill")));
-            }
+        let swara_cells: Vec<String> = swaras.iter().map(|t| self.swara_latex(t)).collect();
+        self.add_table_row(&mut latex, &swara_cells, num_beats, "texttt");
+        
+        // Build sahitya rows
+        for row in words {
+            let cells: Vec<String> = row.iter().map(|t| self.escape_latex(t)).collect();
+            self.add_table_row(&mut latex, &cells, num_beats, "textit");
         }
-        latex.push_str(" & \\texttt{||} \\\\\n");
         
-        // Build sahitya row
-        beat_idx = 0;
+        latex.push_str("\\end{tabularx}\n\n");
+        
+        latex
+    }
+
+    /// `cells` are already LaTeX
+    fn add_table_row(&self, latex: &mut String, cells: &[String], num_beats: usize, style: &str) {
+        let mut beat_idx = 0;
         for beat_num in 0..num_beats {
             if beat_num > 0 {
                 latex.push_str(" & \\texttt{|} & ");
@@ -209,8 +284,8 @@ impl LatexPdfGenerator {
             // Collect elements for this beat
             let mut beat_elements = Vec::new();
             for _ in 0..4 {
-                if beat_idx < sahitya.len() {
-                    beat_elements.push(format!("\\textit{{{}}}", self.escape_latex(&sahitya[beat_idx])));
+                if beat_idx < cells.len() {
+                    beat_elements.push(format!("\\{}{{{}}}", style, cells[beat_idx]));
                     beat_idx += 1;
                 }
             }
@@ -223,32 +298,28 @@ impl LatexPdfGenerator {
             }
         }
         latex.push_str(" & \\texttt{||} \\\\\n");
-        
-        latex.push_str("\\end{tabularx}\n\n");
-        
-        latex
     }
 
-    fn format_vna_line(&self, elements: &[String]) -> String {
-        // Reconstruct the line with beat markers
-        let mut result = String::new();
-        
-        for (i, element) in elements.iter().enumerate() {
-            if i > 0 && i % 4 == 0 {
-                result.push_str(" | ");
-            } else if i > 0 {
-                result.push(' ');
+    /// A swara token with its gamakas drawn: the anuswaram raised before the
+    /// note, a kampita raised after it and a jaru as an arrow
+    fn swara_latex(&self, token: &str) -> String {
+        let mut latex = String::new();
+        let mut chars = token.chars();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '{' => {
+                    let grace: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                    latex.push_str(&format!("\\textsuperscript{{{}}}", self.escape_latex(&grace)));
+                }
+                '~' => latex.push_str("\\textsuperscript{$\\sim$}"),
+                '/' => latex.push_str("$\\nearrow$"),
+                '\\' => latex.push_str("$\\searrow$"),
+                _ => latex.push_str(&self.escape_latex(&ch.to_string())),
             }
-            result.push_str(element);
         }
-        
-        // Add ending marker
-        result.push_str(" ||");
-        
-        result
+        latex
     }
 
-
     fn escape_latex(&self, text: &str) -> String {
         text.replace("&", "\\&")
             .replace("%", "\\%")
@@ -263,6 +334,67 @@ impl LatexPdfGenerator {
     }
 }
 
+/// Labels for the staff lines: each swara of the section's raga, as written,
+/// with its frequency in the document's key, tuning and default octave
+fn grid_labels(document: &VnaDocument, section: &Section) -> String {
+    let tuning = Tuning::new(&document.metadata);
+    let octave = default_octave(&document.metadata);
+    let scale = RagaScales::new(&document.metadata).get(section.raga.as_deref().unwrap_or(&document.metadata.raga));
+    let labels: Vec<String> = STAFF_LINES
+        .iter()
+        .enumerate()
+        .filter_map(|(i, unit)| {
+            let note = Note::parse(unit)?;
+            let note = scale.as_ref().map_or(note.clone(), |s| s.resolve(note));
+            if scale.as_ref().is_some_and(|s| !s.contains(&note)) {
+                return None;
+            }
+            let y = STAFF_HEIGHT * i as f64 / (STAFF_LINES.len() - 1) as f64;
+            Some(format!(
+                "\\node[anchor=east, font=\\tiny, gray] at (0,{:.1}) {{{} {:.1} Hz}};",
+                y,
+                note,
+                tuning.frequency(&note.shifted(octave))
+            ))
+        })
+        .collect();
+    labels.join(" ")
+}
+
+/// Empty cells standing for the part of the avartanam before a section's
+/// eduppu, at the density (tokens per akshara) of the section's first line
+fn leading_blanks(document: &VnaDocument, section: &Section, phrase: &Phrase) -> usize {
+    let Some(eduppu) = section.eduppu.or(document.metadata.eduppu) else {
+        return 0;
+    };
+    let Some(cycle) = tala_aksharas(section.tala.as_deref().unwrap_or(&document.metadata.tala)) else {
+        return 0;
+    };
+    let offset = eduppu.offset_in(cycle);
+    if offset.is_zero() {
+        return 0;
+    }
+
+    let gati = phrase
+        .gati
+        .or(section.gati)
+        .or(document.metadata.gati)
+        .filter(|g| *g > 0)
+        .unwrap_or(DEFAULT_GATI);
+    let phrase = repeat::expand_phrase(phrase).unwrap_or_else(|_| phrase.clone());
+    let aksharas = phrase.swaras.iter().fold(Ratio::ZERO, |sum, token| {
+        let (text, token_gati) = split_token_gati(token);
+        let gati = token_gati.and_then(|g| g.parse::<u8>().ok()).filter(|g| *g > 0).unwrap_or(gati);
+        sum + Ratio::new(parse_swara_units(text).len() as u64, gati as u64)
+    });
+    if aksharas.is_zero() {
+        return 0;
+    }
+
+    let blanks = offset * Ratio::from(phrase.swaras.len() as u64) / aksharas;
+    (blanks.to_f64().round() as usize).max(1)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -283,11 +415,104 @@ nin - nu - | ko - - - ||
 "#;
 
         let doc = parse(content).unwrap();
-        let generator = LatexPdfGenerator::new(40, "a4").unwrap();
+        let generator = LatexPdfGenerator::new("a4").unwrap();
         let latex = generator.create_latex(&doc).unwrap();
         
         assert!(latex.contains("\\documentclass"));
         assert!(latex.contains("Test Composition"));
         assert!(latex.contains("mohanam"));
     }
+
+    #[test]
+    fn test_frequency_grid_labels() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+key: "5 kattai"
+tuning: "just"
+---
+
+[pallavi]
+G,G, R,,, ||
+ni-- kō-- ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let generator = LatexPdfGenerator::new("a4").unwrap();
+        let latex = generator.create_latex(&doc).unwrap();
+
+        // The lines of the raga's swaras are labelled, the rest left blank
+        assert!(latex.contains("\\node[anchor=east, font=\\tiny, gray] at (0,1.2) {S 196.0 Hz};"), "{}", latex);
+        assert!(latex.contains("at (0,2.0) {G3 245.0 Hz};"));
+        assert!(latex.contains("at (0,2.8) {P 294.0 Hz};"));
+        assert!(!latex.contains("{M1 "));
+
+        // Lines are labelled as written, sounding in the default octave
+        let lower = parse(&content.replace("tuning: \"just\"", "default_octave: \"mandra\"")).unwrap();
+        let latex = generator.create_latex(&lower).unwrap();
+        assert!(latex.contains("BPM | Octave: mandra}"), "{}", latex);
+        assert!(latex.contains("at (0,1.2) {S 98.0 Hz};"), "{}", latex);
+    }
+
+    #[test]
+    fn test_curves_on_staff() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+G,G, R,,, ||
+ni-- kō-- ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let curves: CurveData = serde_json::from_str(r#"{"version": "1.0", "fileHash": "", "createdAt": "", "updatedAt": "",
+            "gamakas": [{"id": "g1", "type": "jaru", "swaraStart": "G", "swaraEnd": "R", "phraseIndex": 0, "sectionName": "pallavi",
+                "points": [{"x": 30, "y": 267.2, "pressure": 1}, {"x": 400, "y": 225.6, "pressure": 1}, {"x": 770, "y": 168.4, "pressure": 1}]}]}"#).unwrap();
+        let mut generator = LatexPdfGenerator::new("a4").unwrap();
+        generator.curves = Some(curves);
+        let latex = generator.create_latex(&doc).unwrap();
+
+        // Drawn from S to P, then to M' halfway between the canvas's G' and P'
+        // (its upper octaves leave out M), each on the line of its swara
+        let coordinates = "(0.000\\textwidth,1.20cm) (0.500\\textwidth,2.80cm) (1.000\\textwidth,5.20cm)";
+        assert!(latex.contains(&format!("\\stafflines[{{\\draw[blue, thick] plot[smooth] coordinates {{{}}};}}]", coordinates)), "{}", latex);
+    }
+
+    #[test]
+    fn test_gamaka_latex() {
+        let generator = LatexPdfGenerator::new("a4").unwrap();
+        assert_eq!(
+            generator.swara_latex("{R}G~P/D\\"),
+            "\\textsuperscript{R}G\\textsuperscript{$\\sim$}P$\\nearrow$D$\\searrow$"
+        );
+        assert_eq!(generator.swara_latex("S'R,"), "S'R,");
+    }
+
+    #[test]
+    fn test_eduppu_leading_blanks() {
+        let content = r#"---
+title: "Test Composition"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+@eduppu: 2
+G,G, R,,, | SSRR GGRR | SRGR SRSD. | SRGP GRSR ||
+ni-- kō-- | ri-- ---- | ---- ----- | ---- ---- ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let phrase = &doc.sections[0].phrases[0];
+        // Two of the eight aksharas come before the eduppu, at one token per akshara
+        assert_eq!(leading_blanks(&doc, &doc.sections[0], phrase), 2);
+
+        let mut atita = doc.clone();
+        atita.sections[0].eduppu = Some("-1".parse().unwrap());
+        assert_eq!(leading_blanks(&atita, &atita.sections[0], phrase), 7);
+    }
 }
\ No newline at end of file
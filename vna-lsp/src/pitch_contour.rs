@@ -0,0 +1,479 @@
+use crate::gamaka_sidecar::{CurveData, CurvePoint};
+use crate::raga::{self, RagaScales, Scale};
+use crate::ratio::Ratio;
+use crate::timeline::{self, Note, TimelineEvent};
+use crate::tuning::Tuning;
+use crate::types::*;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// The editor's phrase canvas (`StaffCanvas.tsx`) is 800×400, with the staff
+/// between these bounds
+pub const CANVAS_LEFT: f64 = 30.0;
+pub const CANVAS_RIGHT: f64 = 770.0;
+pub const CANVAS_TOP: f64 = 80.0;
+pub const CANVAS_BOTTOM: f64 = 340.0;
+
+/// Staff positions from the top of the canvas down, as the editor lays them out
+const STAFF: [&str; 26] = [
+    "S''", "R''", "G''", "P''", "D''", "N''", "S'", "R'", "G'", "P'", "D'", "N'", "N", "D", "P", "M", "G", "R", "S",
+    "N.", "D.", "P.", "M.", "G.", "R.", "S.",
+];
+
+/// Points per curved segment, as `InflectionCurveEngine.interpolateSegment`
+pub const RESOLUTION: u32 = 50;
+/// Part of a note an anuswaram takes to reach it
+const GRACE_FRACTION: f64 = 0.25;
+/// Part of a note a jaru takes to slide into the next one
+const SLIDE_FRACTION: f64 = 0.5;
+/// How far a kampita swings either side of its note, in cents
+const KAMPITA_CENTS: f64 = 100.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SegmentKind {
+    Linear,
+    Quadratic,
+    Cubic,
+    Sinusoidal,
+    Exponential,
+}
+
+/// A curve between two points, shaped as in `inflection-curve-engine.ts`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Segment {
+    pub start_point: CurvePoint,
+    pub end_point: CurvePoint,
+    #[serde(rename = "type")]
+    pub kind: SegmentKind,
+    /// Curve tightness, 0 to 1 in the editor
+    #[serde(default = "default_tension")]
+    pub tension: f64,
+    /// A cubic's own control points; it uses the first two if there are two
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub control_points: Vec<CurvePoint>,
+}
+
+fn default_tension() -> f64 {
+    0.5
+}
+
+impl Segment {
+    pub fn new(start_point: CurvePoint, end_point: CurvePoint, kind: SegmentKind) -> Self {
+        Self { start_point, end_point, kind, tension: default_tension(), control_points: Vec::new() }
+    }
+
+    /// The point a fraction `t` of the way along the segment
+    pub fn at(&self, t: f64) -> CurvePoint {
+        let (start, end) = (self.start_point, self.end_point);
+        let pressure = start.pressure + (end.pressure - start.pressure) * t;
+        let linear = |t: f64| (start.x + (end.x - start.x) * t, start.y + (end.y - start.y) * t);
+
+        let (x, y) = match self.kind {
+            SegmentKind::Linear => linear(t),
+            SegmentKind::Quadratic => {
+                // Through a control point above the middle, raised by the tension
+                let mid = ((start.x + end.x) / 2.0, (start.y + end.y) / 2.0 - self.tension * (end.x - start.x).abs() * 0.5);
+                let mt = 1.0 - t;
+                let bezier = |p0: f64, p1: f64, p2: f64| mt * mt * p0 + 2.0 * mt * t * p1 + t * t * p2;
+                (bezier(start.x, mid.0, end.x), bezier(start.y, mid.1, end.y))
+            }
+            SegmentKind::Cubic => {
+                // The segment's own control points, or ones a quarter of the
+                // way in from each end, pulled apart by the tension
+                let (cp1, cp2) = match self.control_points.as_slice() {
+                    [cp1, cp2, ..] => ((cp1.x, cp1.y), (cp2.x, cp2.y)),
+                    _ => {
+                        let dx = end.x - start.x;
+                        let dy = end.y - start.y;
+                        (
+                            (start.x + dx * 0.25, start.y + dy * 0.25 - self.tension * dx.abs() * 0.3),
+                            (end.x - dx * 0.25, end.y - dy * 0.25 + self.tension * dx.abs() * 0.3),
+                        )
+                    }
+                };
+                let mt = 1.0 - t;
+                let bezier = |p0: f64, p1: f64, p2: f64, p3: f64| {
+                    mt * mt * mt * p0 + 3.0 * mt * mt * t * p1 + 3.0 * mt * t * t * p2 + t * t * t * p3
+                };
+                (bezier(start.x, cp1.0, cp2.0, end.x), bezier(start.y, cp1.1, cp2.1, end.y))
+            }
+            SegmentKind::Sinusoidal => {
+                // One full wave over the segment
+                let (x, y) = linear(t);
+                let amplitude = self.tension * (end.x - start.x).abs() * 0.2;
+                (x, y + amplitude * (t * std::f64::consts::PI * 2.0).sin())
+            }
+            SegmentKind::Exponential => {
+                // Easing in above a tension of 0.5, out below it
+                let eased = if self.tension > 0.5 {
+                    t.powf(2.0 + (self.tension - 0.5) * 4.0)
+                } else {
+                    1.0 - (1.0 - t).powf(2.0 + (0.5 - self.tension) * 4.0)
+                };
+                linear(eased)
+            }
+        };
+        CurvePoint { x, y, pressure }
+    }
+
+    /// Points from the start at steps of `1 / resolution`, stepping as the
+    /// editor does so both give the same points
+    pub fn sample(&self, resolution: u32) -> Vec<CurvePoint> {
+        let step = 1.0 / resolution as f64;
+        let mut points = Vec::new();
+        let mut t = 0.0;
+        while t <= 1.0 {
+            points.push(self.at(t));
+            t += step;
+        }
+        points
+    }
+}
+
+/// A phrase's pitch over time
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Contour {
+    /// Frequency of the middle octave's S, from the document's key
+    pub tonic_hz: f64,
+    pub points: Vec<ContourPoint>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ContourPoint {
+    pub seconds: f64,
+    /// Cents above the tonic (middle S); `None` from the start of a rest
+    pub cents: Option<f64>,
+}
+
+impl Contour {
+    /// The pitch at a time, between the points on either side of it
+    pub fn cents_at(&self, seconds: f64) -> Option<f64> {
+        let after = self.points.iter().position(|p| p.seconds > seconds)?;
+        let before = self.points[..after].last()?;
+        let next = self.points[after];
+        match (before.cents, next.cents) {
+            (Some(from), Some(to)) if next.seconds > before.seconds => {
+                Some(from + (to - from) * (seconds - before.seconds) / (next.seconds - before.seconds))
+            }
+            (cents, _) => cents,
+        }
+    }
+
+    fn push(&mut self, point: CurvePoint) {
+        self.points.push(ContourPoint { seconds: point.x, cents: Some(point.y) });
+    }
+
+    fn extend(&mut self, segment: Segment, resolution: u32) {
+        for point in segment.sample(resolution) {
+            self.push(point);
+        }
+    }
+}
+
+/// The pitch contour of a phrase, in cents relative to the tonic over seconds
+/// from the start of the piece
+///
+/// Notes hold their swarasthana in the document's tuning, shaped by the gamakas
+/// written on them: an anuswaram rises or falls from its grace note, a
+/// kampita swings a semitone either side in one wave per unit and a jaru
+/// slides into the next note. Curves drawn in the editor replace the notes
+/// under them.
+pub fn phrase_contour(
+    document: &VnaDocument,
+    section_index: usize,
+    phrase_index: usize,
+    curves: Option<&CurveData>,
+) -> Result<Contour> {
+    let section = document
+        .sections
+        .get(section_index)
+        .ok_or_else(|| anyhow!("No section {} in the document", section_index))?;
+    let phrase = section
+        .phrases
+        .get(phrase_index)
+        .ok_or_else(|| anyhow!("No line {} in [{}]", phrase_index, section.header()))?;
+    let scale = RagaScales::new(&document.metadata).get(raga::effective(document, section, phrase));
+
+    let timeline = timeline::build(document);
+    let events: Vec<&TimelineEvent> = timeline
+        .events
+        .iter()
+        .filter(|e| e.section == section_index && e.line_number == phrase.line_number)
+        .collect();
+
+    let tuning = Tuning::new(&document.metadata);
+    let note_cents = |note: &Note| tuning.cents(note);
+    let octave = timeline::default_octave(&document.metadata);
+    let mut contour = Contour { tonic_hz: tuning.tonic_hz, points: Vec::new() };
+    for (i, event) in events.iter().enumerate() {
+        let start = event.onset_seconds;
+        let end = start + event.duration_seconds;
+        let Some(note) = &event.note else {
+            contour.points.push(ContourPoint { seconds: start, cents: None });
+            continue;
+        };
+        let cents = note_cents(note);
+        let point = |x: f64, y: f64| CurvePoint { x, y, pressure: 1.0 };
+
+        let mut body_start = start;
+        let mut body_end = end;
+        let mut slide = None;
+        let mut kampita = false;
+        for gamaka in &event.gamakas {
+            match gamaka {
+                Gamaka::Anuswaram(grace) => {
+                    let grace = Note::parse(grace)
+                        .map(|n| resolve(&scale, n.shifted(octave)))
+                        .map_or(cents, |n| note_cents(&n));
+                    body_start = start + event.duration_seconds * GRACE_FRACTION;
+                    contour.extend(Segment::new(point(start, grace), point(body_start, cents), SegmentKind::Linear), 1);
+                }
+                Gamaka::Jaru(_) => {
+                    // Into the next note, if it follows straight on
+                    slide = events
+                        .get(i + 1)
+                        .filter(|next| (next.onset_seconds - end).abs() < 1e-9)
+                        .and_then(|next| next.note.as_ref())
+                        .map(note_cents);
+                    if slide.is_some() {
+                        body_end = end - event.duration_seconds * SLIDE_FRACTION;
+                    }
+                }
+                Gamaka::Kampita => kampita = true,
+            }
+        }
+
+        if kampita {
+            // One wave per unit the note lasts
+            let units = (event.duration * Ratio::from(event.gati as u64)).floor().max(1);
+            let width = (body_end - body_start) / units as f64;
+            for wave in 0..units {
+                let from = body_start + width * wave as f64;
+                let segment = Segment {
+                    tension: KAMPITA_CENTS / (0.2 * width),
+                    ..Segment::new(point(from, cents), point(from + width, cents), SegmentKind::Sinusoidal)
+                };
+                contour.extend(segment, RESOLUTION);
+            }
+        } else {
+            contour.extend(Segment::new(point(body_start, cents), point(body_end, cents), SegmentKind::Linear), 1);
+        }
+
+        if let Some(next) = slide {
+            // A plain slide, without the editor's tension bulge
+            let segment = Segment { tension: 0.0, ..Segment::new(point(body_end, cents), point(end, next), SegmentKind::Cubic) };
+            contour.extend(segment, RESOLUTION);
+        }
+    }
+
+    if let (Some(curves), Some(first), Some(last)) = (curves, events.first(), events.last()) {
+        let start = first.onset_seconds;
+        let end = last.onset_seconds + last.duration_seconds;
+        let staff = staff_cents(&scale, &tuning, octave);
+        for curve in curves.for_phrase(section, phrase_index).filter(|c| c.points.len() > 1) {
+            let points: Vec<CurvePoint> = curve
+                .points
+                .iter()
+                .map(|p| CurvePoint {
+                    x: start + (p.x - CANVAS_LEFT) / (CANVAS_RIGHT - CANVAS_LEFT) * (end - start),
+                    y: canvas_cents(&staff, p.y),
+                    pressure: p.pressure,
+                })
+                .collect();
+            let (from, to) = (points[0].x, points[points.len() - 1].x);
+            contour.points.retain(|p| p.seconds < from.min(to) || p.seconds > from.max(to));
+            for point in points {
+                contour.push(point);
+            }
+        }
+        contour.points.sort_by(|a, b| a.seconds.total_cmp(&b.seconds));
+    }
+
+    Ok(contour)
+}
+
+fn resolve(scale: &Option<Scale>, note: Note) -> Note {
+    match scale {
+        Some(scale) => scale.resolve(note),
+        None => note,
+    }
+}
+
+/// The pitch of each of the editor's staff positions in the raga, labelled
+/// as written from the default octave
+fn staff_cents(scale: &Option<Scale>, tuning: &Tuning, octave: i8) -> Vec<f64> {
+    STAFF
+        .iter()
+        .filter_map(|label| Note::parse(label))
+        .map(|note| tuning.cents(&resolve(scale, note.shifted(octave))))
+        .collect()
+}
+
+/// The pitch at a height on the canvas, between the staff positions around it
+fn canvas_cents(staff: &[f64], y: f64) -> f64 {
+    let (index, fraction) = canvas_position(y);
+    staff[index] + (staff[index + 1] - staff[index]) * fraction
+}
+
+/// The swara at a height on the canvas as written, in scale degrees from
+/// middle S (R is 1, S' is 7), between the staff positions around it
+pub fn canvas_degree(y: f64) -> f64 {
+    let degree = |label: &str| Note::parse(label).map_or(0, |note| swara_degree(&note)) as f64;
+    let (index, fraction) = canvas_position(y);
+    let (below, above) = (degree(STAFF[index]), degree(STAFF[index + 1]));
+    below + (above - below) * fraction
+}
+
+/// Scale degrees of a note from middle S, whatever its variant
+pub fn swara_degree(note: &Note) -> i32 {
+    let degree = "SRGMPDN".find(note.swara).unwrap_or(0) as i32;
+    note.octave as i32 * 7 + degree
+}
+
+/// The staff position just above a height on the canvas, counted from the top,
+/// and how far the height is from it towards the next one down
+fn canvas_position(y: f64) -> (usize, f64) {
+    let last = STAFF.len() - 1;
+    let spacing = (CANVAS_BOTTOM - CANVAS_TOP) / last as f64;
+    let position = ((y - CANVAS_TOP) / spacing).clamp(0.0, last as f64);
+    let index = (position.floor() as usize).min(last - 1);
+    (index, position - index as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    fn point(x: f64, y: f64) -> CurvePoint {
+        CurvePoint { x, y, pressure: 0.5 }
+    }
+
+    #[test]
+    fn test_segments_match_editor() {
+        // Values from InflectionCurveEngine with the same inputs
+        let cubic = Segment::new(point(0.0, 0.0), point(100.0, 50.0), SegmentKind::Cubic);
+        let mid = cubic.at(0.5);
+        assert!((mid.x - 50.0).abs() < 1e-9 && (mid.y - 25.0).abs() < 1e-9);
+        let quarter = cubic.at(0.25);
+        assert!((quarter.x - 22.65625).abs() < 1e-9, "{:?}", quarter);
+        assert!((quarter.y - 7.109375).abs() < 1e-9, "{:?}", quarter);
+
+        let wave = Segment::new(point(0.0, 10.0), point(100.0, 10.0), SegmentKind::Sinusoidal);
+        assert!((wave.at(0.25).y - 20.0).abs() < 1e-9);
+        assert!((wave.at(0.75).y - 0.0).abs() < 1e-9);
+
+        let line = Segment::new(point(0.0, 0.0), CurvePoint { x: 10.0, y: 20.0, pressure: 1.0 }, SegmentKind::Linear);
+        assert_eq!(line.at(0.5), CurvePoint { x: 5.0, y: 10.0, pressure: 0.75 });
+        // Stepping by 1/50 falls just short of 1, as in the editor
+        assert_eq!(line.sample(RESOLUTION).len(), 50);
+        assert_eq!(line.sample(1).len(), 2);
+
+        let arch = Segment::new(point(0.0, 0.0), point(100.0, 0.0), SegmentKind::Quadratic);
+        assert_eq!((arch.at(0.5).x, arch.at(0.5).y), (50.0, -12.5));
+
+        let ease_in = Segment { tension: 0.75, ..Segment::new(point(0.0, 0.0), point(100.0, 100.0), SegmentKind::Exponential) };
+        assert!((ease_in.at(0.5).y - 100.0 * 0.5f64.powi(3)).abs() < 1e-9);
+        let ease_out = Segment { tension: 0.25, ..ease_in.clone() };
+        assert!((ease_out.at(0.5).y - 100.0 * (1.0 - 0.5f64.powi(3))).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cubic_control_points() {
+        let json = r#"{"startPoint": {"x": 0, "y": 0, "pressure": 0.5}, "endPoint": {"x": 100, "y": 0, "pressure": 0.5},
+            "type": "cubic", "controlPoints": [{"x": 0, "y": 80, "pressure": 0.5}, {"x": 100, "y": 80, "pressure": 0.5}]}"#;
+        let segment: Segment = serde_json::from_str(json).unwrap();
+        assert_eq!(segment.control_points.len(), 2);
+
+        // cubicInterpolation: mt³·start + 3mt²t·cp1 + 3mt·t²·cp2 + t³·end
+        let t: f64 = 0.25;
+        let mt = 1.0 - t;
+        let y = 3.0 * mt * mt * t * 80.0 + 3.0 * mt * t * t * 80.0;
+        let x = 3.0 * mt * t * t * 100.0 + t * t * t * 100.0;
+        let point = segment.at(t);
+        assert!((point.x - x).abs() < 1e-9 && (point.y - y).abs() < 1e-9, "{:?}", point);
+        assert_eq!(segment.at(0.5).y, 60.0);
+
+        // With only one, the control points are worked out as without any
+        let one = Segment { control_points: vec![segment.control_points[0]], ..segment.clone() };
+        let auto = Segment { control_points: Vec::new(), ..segment.clone() };
+        assert_eq!(one.at(t), auto.at(t));
+
+        let kinds: Vec<SegmentKind> = serde_json::from_str(r#"["quadratic", "exponential"]"#).unwrap();
+        assert_eq!(kinds, [SegmentKind::Quadratic, SegmentKind::Exponential]);
+    }
+
+    const CONTENT: &str = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+tempo: 60
+---
+
+[pallavi]
+{S}G,,, P/,D, ---- ||
+ni---   ----  ---- ||
+
+G~,,, S,,, ||
+ni--- ---- ||
+"#;
+
+    #[test]
+    fn test_phrase_contour() {
+        let doc = parse(CONTENT).unwrap();
+        let contour = phrase_contour(&doc, 0, 0, None).unwrap();
+
+        // G3 reached from S over the first quarter of its second
+        assert_eq!(contour.cents_at(0.0), Some(0.0));
+        assert_eq!(contour.cents_at(0.5), Some(400.0));
+        // P slides into D2 over its second half, then a rest
+        assert_eq!(contour.cents_at(1.1), Some(700.0));
+        assert!(contour.cents_at(1.45).unwrap() > 800.0);
+        assert_eq!(contour.cents_at(1.75), Some(900.0));
+        assert_eq!(contour.cents_at(2.5), None);
+
+        // A kampita swings around its note
+        let contour = phrase_contour(&doc, 0, 1, None).unwrap();
+        let swing: Vec<f64> = (0..40).filter_map(|i| contour.cents_at(3.0 + i as f64 * 0.025)).collect();
+        assert!(swing.iter().any(|c| *c > 450.0) && swing.iter().any(|c| *c < 350.0), "{:?}", swing);
+        assert_eq!(contour.cents_at(4.5), Some(0.0));
+
+        // Notes follow the document's tuning
+        let mut doc = doc;
+        doc.metadata.key = Some("5 kattai".to_string());
+        doc.metadata.tuning = Some("just".to_string());
+        let contour = phrase_contour(&doc, 0, 0, None).unwrap();
+        assert!((contour.tonic_hz - 196.0).abs() < 0.01);
+        assert!((contour.cents_at(0.5).unwrap() - 386.31).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_drawn_curve_replaces_notes() {
+        let doc = parse(CONTENT).unwrap();
+        let curves: CurveData = serde_json::from_str(
+            r#"{"version": "1.0", "fileHash": "", "createdAt": "", "updatedAt": "", "gamakas": [{"id": "g1", "type": "jaru",
+                "swaraStart": "", "swaraEnd": "", "phraseIndex": 1, "sectionName": "pallavi",
+                "points": [{"x": 30, "y": 340, "pressure": 1}, {"x": 400, "y": 246.4, "pressure": 1}]}]}"#,
+        )
+        .unwrap();
+
+        let contour = phrase_contour(&doc, 0, 1, Some(&curves)).unwrap();
+        // The curve starts on S. (the bottom of the staff) and rises to G (7 positions up)
+        assert_eq!(contour.cents_at(3.0), Some(-1200.0));
+        assert!((contour.cents_at(3.5).unwrap() + 400.0).abs() < 1e-6, "{:?}", contour.cents_at(3.5));
+        assert!((contour.cents_at(3.999).unwrap() - 400.0).abs() < 5.0, "{:?}", contour.cents_at(3.999));
+
+        assert!(phrase_contour(&doc, 0, 2, None).is_err());
+    }
+
+    #[test]
+    fn test_canvas_degree() {
+        let spacing = (CANVAS_BOTTOM - CANVAS_TOP) / 25.0;
+        assert_eq!(canvas_degree(CANVAS_BOTTOM), -7.0);
+        assert!(canvas_degree(CANVAS_TOP + 18.0 * spacing).abs() < 1e-9);
+        // The upper octaves leave out M, so halfway from G' to P' is M'
+        assert!((canvas_degree(CANVAS_TOP + 8.5 * spacing) - 10.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,337 @@
+use crate::repeat;
+use crate::sahitya_parser::parse_sahitya_token_with_registry;
+use crate::sahitya_scheme::{detect_document_scheme, detect_token_scheme, to_iso, SahityaScheme};
+use crate::syllabifier::SyllabifierRegistry;
+use crate::types::*;
+use std::collections::HashMap;
+
+/// Prasa, yati and anuprasa analysis of a document's sahitya
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProsodyAnalysis {
+    pub sections: Vec<SectionProsody>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionProsody {
+    pub name: String,
+    /// The section's dominant second-akshara consonant, if at least two lines agree
+    pub prasa: Option<String>,
+    pub lines: Vec<LineProsody>,
+}
+
+/// Analysis of one sahitya line (lines made only of dashes are skipped)
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineProsody {
+    pub line_number: usize,
+    /// Syllables of the line in ISO 15919, without dashes
+    pub syllables: Vec<String>,
+    /// Consonants between the first and second vowel ("nn" for both nin`nu and ni`nnu)
+    pub prasa_consonant: Option<String>,
+    /// The line's prasa consonant differs from the section's
+    pub breaks_prasa: bool,
+    /// Initial sound shared by the first syllable and the syllable starting the second half
+    pub yati: Option<String>,
+    /// Consonants beginning three or more syllables in the line, most frequent first
+    pub anuprasa: Vec<(String, usize)>,
+}
+
+const ANUPRASA_MIN_COUNT: usize = 3;
+
+pub fn analyze(document: &VnaDocument, registry: &SyllabifierRegistry) -> ProsodyAnalysis {
+    let document = &repeat::expand(document);
+    let scheme = detect_document_scheme(document);
+    let language = document.metadata.language.as_deref();
+
+    let sections = document
+        .sections
+        .iter()
+        .map(|section| {
+            let mut lines: Vec<LineProsody> = section
+                .phrases
+                .iter()
+                .filter_map(|phrase| analyze_line(phrase, registry, language, scheme))
+                .collect();
+
+            let prasa = dominant_prasa(&lines);
+            if let Some(prasa) = &prasa {
+                for line in &mut lines {
+                    line.breaks_prasa = line.prasa_consonant.as_ref().is_some_and(|c| c != prasa);
+                }
+            }
+
+            SectionProsody {
+                name: section.header(),
+                prasa,
+                lines,
+            }
+        })
+        .collect();
+
+    ProsodyAnalysis { sections }
+}
+
+/// Info diagnostics for lines that break their section's prasa
+pub fn issues(analysis: &ProsodyAnalysis) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    for section in &analysis.sections {
+        let Some(prasa) = &section.prasa else {
+            continue;
+        };
+        for line in section.lines.iter().filter(|l| l.breaks_prasa) {
+            issues.push(ValidationIssue {
+                severity: Severity::Info,
+                message: format!(
+                    "Line breaks the prasa of [{}]: second akshara has '{}', expected '{}'",
+                    section.name,
+                    line.prasa_consonant.as_deref().unwrap_or(""),
+                    prasa
+                ),
+                line: line.line_number + 1,
+                column: None,
+                code: Some("prasa_break".to_string()),
+                range: None,
+            });
+        }
+    }
+
+    issues
+}
+
+fn analyze_line(
+    phrase: &Phrase,
+    registry: &SyllabifierRegistry,
+    language: Option<&str>,
+    scheme: SahityaScheme,
+) -> Option<LineProsody> {
+    // Syllables per token, so the yati position can be found by token index
+    let token_syllables: Vec<Vec<String>> = phrase
+        .sahitya
+        .iter()
+        .map(|token| {
+            parse_sahitya_token_with_registry(token, registry, language, scheme)
+                .into_iter()
+                .filter(|unit| unit != "-")
+                .map(|unit| romanise(&unit, scheme))
+                .collect()
+        })
+        .collect();
+
+    let syllables: Vec<String> = token_syllables.iter().flatten().cloned().collect();
+    if syllables.is_empty() {
+        return None;
+    }
+
+    let prasa_consonant = match syllables.as_slice() {
+        [first, second, ..] => {
+            let consonant = format!("{}{}", split_syllable(first).2, split_syllable(second).0);
+            Some(consonant)
+        }
+        _ => None,
+    };
+
+    // Yati: the second half starts at the beat marker closest to the middle of the line
+    let middle = phrase.sahitya.len() / 2;
+    let yati = phrase
+        .beat_positions
+        .iter()
+        .min_by_key(|&&pos| pos.abs_diff(middle))
+        .and_then(|&pos| token_syllables[pos.min(token_syllables.len())..].iter().flatten().next())
+        .and_then(|yati_syllable| {
+            let first = initial_sound(&syllables[0]);
+            (first == initial_sound(yati_syllable)).then_some(first)
+        });
+
+    let mut onset_counts: HashMap<String, usize> = HashMap::new();
+    for syllable in &syllables {
+        let onset = split_syllable(syllable).0;
+        if !onset.is_empty() {
+            *onset_counts.entry(onset.to_string()).or_insert(0) += 1;
+        }
+    }
+    let mut anuprasa: Vec<(String, usize)> = onset_counts
+        .into_iter()
+        .filter(|(_, count)| *count >= ANUPRASA_MIN_COUNT)
+        .collect();
+    anuprasa.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    Some(LineProsody {
+        line_number: phrase.line_number,
+        syllables,
+        prasa_consonant,
+        breaks_prasa: false,
+        yati,
+        anuprasa,
+    })
+}
+
+fn dominant_prasa(lines: &[LineProsody]) -> Option<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for consonant in lines.iter().filter_map(|l| l.prasa_consonant.as_deref()) {
+        *counts.entry(consonant).or_insert(0) += 1;
+    }
+
+    let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    counts
+        .first()
+        .filter(|(_, count)| *count >= 2)
+        .map(|(consonant, _)| consonant.to_string())
+}
+
+/// Syllables from native-script or non-ISO sahitya are compared in ISO
+fn romanise(unit: &str, scheme: SahityaScheme) -> String {
+    let scheme = detect_token_scheme(unit).unwrap_or(scheme);
+    to_iso(unit, scheme).unwrap_or_else(|_| unit.to_string()).to_lowercase()
+}
+
+/// Onset consonant, or the vowel for vowel-initial syllables
+fn initial_sound(syllable: &str) -> String {
+    let (onset, nucleus, _) = split_syllable(syllable);
+    if onset.is_empty() {
+        nucleus.to_string()
+    } else {
+        onset.to_string()
+    }
+}
+
+/// Split an ISO syllable into (onset consonants, vowel, coda consonants)
+fn split_syllable(syllable: &str) -> (&str, &str, &str) {
+    let chars: Vec<(usize, char)> = syllable.char_indices().collect();
+    let is_vowel_at = |i: usize| {
+        let ch = chars[i].1;
+        is_vowel(ch)
+            || is_vowel_mark(ch)
+            // Vocalic r̥ and l̥
+            || (matches!(ch, 'r' | 'l') && chars.get(i + 1).is_some_and(|(_, next)| *next == '\u{325}'))
+    };
+
+    let first_vowel = (0..chars.len()).find(|&i| is_vowel_at(i));
+    let Some(first_vowel) = first_vowel else {
+        return (syllable, "", "");
+    };
+    let last_vowel = (first_vowel..chars.len()).rev().find(|&i| is_vowel_at(i)).unwrap_or(first_vowel);
+
+    let nucleus_start = chars[first_vowel].0;
+    let coda_start = chars.get(last_vowel + 1).map(|(pos, _)| *pos).unwrap_or(syllable.len());
+    (
+        &syllable[..nucleus_start],
+        &syllable[nucleus_start..coda_start],
+        &syllable[coda_start..],
+    )
+}
+
+fn is_vowel(ch: char) -> bool {
+    matches!(ch, 'a' | 'ā' | 'i' | 'ī' | 'u' | 'ū' | 'e' | 'ē' | 'o' | 'ō')
+}
+
+fn is_vowel_mark(ch: char) -> bool {
+    matches!(ch, '\u{304}' | '\u{325}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    const VARNAM: &str = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+language: "telugu"
+---
+
+[pallavi]
+G,G,      R,,,  | SSRR  GGRR ||
+`nin`-nu- kō--- | ri--- ---- ||
+
+GPGG  RSRG   | RRSD. SRGR ||
+ni--- --khi- | ----  la--- ||
+
+[anupallavi]
+G,G,      P,,,   | GGPP   DDP, ||
+`nan`-nu- pā---  | `nin`--- ---- ||
+
+SRGR | SRGR ||
+pa-- | ---- ||
+"#;
+
+    #[test]
+    fn test_split_syllable() {
+        assert_eq!(split_syllable("nin"), ("n", "i", "n"));
+        assert_eq!(split_syllable("nnu"), ("nn", "u", ""));
+        assert_eq!(split_syllable("kō"), ("k", "ō", ""));
+        assert_eq!(split_syllable("a"), ("", "a", ""));
+        assert_eq!(split_syllable("kr\u{325}"), ("k", "r\u{325}", ""));
+    }
+
+    #[test]
+    fn test_prasa() {
+        let doc = parse(VARNAM).unwrap();
+        let analysis = analyze(&doc, SyllabifierRegistry::shared());
+
+        let pallavi = &analysis.sections[0];
+        assert_eq!(pallavi.lines[0].prasa_consonant.as_deref(), Some("nn"));
+        assert_eq!(pallavi.lines[1].prasa_consonant.as_deref(), Some("kh"));
+        // Two lines that disagree: no dominant prasa, nothing flagged
+        assert_eq!(pallavi.prasa, None);
+
+        let anupallavi = &analysis.sections[1];
+        // Single-syllable lines have no second akshara
+        assert_eq!(anupallavi.lines[1].prasa_consonant, None);
+        assert_eq!(anupallavi.prasa, None);
+        assert!(issues(&analysis).is_empty());
+    }
+
+    #[test]
+    fn test_prasa_break() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+G,G,      R,,,  | SSRR GGRR ||
+`nin`-nu- kō--- | ri-- ---- ||
+
+G,G,     R,,,  | SSRR GGRR ||
+`an`-nu- pā--- | ri-- ---- ||
+
+G,G,     R,,,  | SSRR GGRR ||
+`ma`-ka- kō--- | ri-- ---- ||
+"#;
+        let doc = parse(content).unwrap();
+        let analysis = analyze(&doc, SyllabifierRegistry::shared());
+
+        let pallavi = &analysis.sections[0];
+        assert_eq!(pallavi.prasa.as_deref(), Some("nn"));
+        assert!(pallavi.lines[2].breaks_prasa);
+        assert_eq!(pallavi.lines.iter().filter(|l| l.breaks_prasa).count(), 1);
+
+        let issues = issues(&analysis);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code.as_deref(), Some("prasa_break"));
+        assert_eq!(issues[0].severity, Severity::Info);
+        assert_eq!(issues[0].line, pallavi.lines[2].line_number + 1);
+    }
+
+    #[test]
+    fn test_yati_and_anuprasa() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+SRG SRG | SRG SRG ||
+sa`ma`sa sa`ra`sa | sa`ra`sa sa`ma`na ||
+"#;
+        let doc = parse(content).unwrap();
+        let analysis = analyze(&doc, SyllabifierRegistry::shared());
+        let line = &analysis.sections[0].lines[0];
+        assert_eq!(line.yati.as_deref(), Some("s"));
+        assert_eq!(line.anuprasa[0], ("s".to_string(), 7));
+    }
+}
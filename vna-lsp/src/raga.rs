@@ -0,0 +1,239 @@
+use crate::swara_parser::parse_swara_units;
+use crate::timeline::Note;
+use crate::types::*;
+use std::fmt;
+
+const SWARAS: [char; 7] = ['S', 'R', 'G', 'M', 'P', 'D', 'N'];
+
+/// Arohanam and avarohanam of common ragas, used when a file doesn't give
+/// them, keyed by every spelling in `normalize`d form
+const RAGAS: &[(&[&str], &str, &str)] = &[
+    (&["mohana"], "S R2 G3 P D2 S'", "S' D2 P G3 R2 S"),
+    (&["hamsadhwani", "hamsadhvani"], "S R2 G3 P N3 S'", "S' N3 P G3 R2 S"),
+    (&["hindola"], "S G2 M1 D1 N2 S'", "S' N2 D1 M1 G2 S"),
+    (&["madhyamavati", "madhyamavathi"], "S R2 M1 P N2 S'", "S' N2 P M1 R2 S"),
+    (&["suddhasaveri", "shuddhasaveri"], "S R2 M1 P D2 S'", "S' D2 P M1 R2 S"),
+    (&["abhogi"], "S R2 G2 M1 D2 S'", "S' D2 M1 G2 R2 S"),
+    (&["kalyani"], "S R2 G3 M2 P D2 N3 S'", "S' N3 D2 P M2 G3 R2 S"),
+    (&["sankarabharana", "shankarabharana", "dhirasankarabharana"], "S R2 G3 M1 P D2 N3 S'", "S' N3 D2 P M1 G3 R2 S"),
+    (&["kharaharapriya"], "S R2 G2 M1 P D2 N2 S'", "S' N2 D2 P M1 G2 R2 S"),
+    (&["mayamalavagaula", "mayamalavagowla"], "S R1 G3 M1 P D1 N3 S'", "S' N3 D1 P M1 G3 R1 S"),
+    (&["todi", "thodi", "hanumatodi", "hanumathodi"], "S R1 G2 M1 P D1 N2 S'", "S' N2 D1 P M1 G2 R1 S"),
+    (&["kambhoji", "kambodhi"], "S R2 G3 M1 P D2 S'", "S' N2 D2 P M1 G3 R2 S"),
+    (&["bhairavi"], "S R2 G2 M1 P D2 N2 S'", "S' N2 D1 P M1 G2 R2 S"),
+    (&["kedaragaula", "kedaragowla"], "S R2 M1 P N2 S'", "S' N2 D2 P M1 G3 R2 S"),
+    (&["saveri"], "S R1 M1 P D1 S'", "S' N3 D1 P M1 G3 R1 S"),
+];
+
+/// The swarasthanas (variants of each swara) a raga uses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scale {
+    /// Per swara in `SWARAS` order, bit `v` set when variant `v` is used and
+    /// bit 0 when the swara is used without a stated variant
+    swarasthanas: [u8; 7],
+}
+
+impl Scale {
+    /// Read an arohanam or avarohanam such as `S R2 G3 P D2 S'`
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut swarasthanas = [0u8; 7];
+        for unit in text.split_whitespace().flat_map(parse_swara_units) {
+            let note = Note::parse(&unit)?;
+            swarasthanas[index(note.swara)] |= 1 << note.variant.unwrap_or(0);
+        }
+        swarasthanas.iter().any(|s| *s != 0).then_some(Self { swarasthanas })
+    }
+
+    /// Both directions together
+    pub fn from_arohanam(arohanam: &str, avarohanam: &str) -> Option<Self> {
+        match (Self::parse(arohanam), Self::parse(avarohanam)) {
+            (Some(up), Some(down)) => Some(up.union(&down)),
+            (up, down) => up.or(down),
+        }
+    }
+
+    /// A built-in scale, looked up by any common spelling of the raga's name
+    pub fn builtin(raga: &str) -> Option<Self> {
+        let name = normalize(raga);
+        RAGAS
+            .iter()
+            .find(|(names, _, _)| names.contains(&name.as_str()))
+            .and_then(|(_, arohanam, avarohanam)| Self::from_arohanam(arohanam, avarohanam))
+    }
+
+    fn union(&self, other: &Scale) -> Scale {
+        let mut swarasthanas = self.swarasthanas;
+        for (mine, theirs) in swarasthanas.iter_mut().zip(other.swarasthanas) {
+            *mine |= theirs;
+        }
+        Scale { swarasthanas }
+    }
+
+    /// Whether the raga uses the note; a note without a variant only needs the swara
+    pub fn contains(&self, note: &Note) -> bool {
+        let used = self.swarasthanas[index(note.swara)];
+        match note.variant {
+            Some(variant) => used & (1 << variant) != 0 || used == 1,
+            None => used != 0,
+        }
+    }
+
+    /// The note with its swarasthana filled in, when the raga uses exactly one
+    /// variant of the swara
+    pub fn resolve(&self, note: Note) -> Note {
+        let used = self.swarasthanas[index(note.swara)] & !1;
+        let variant = note
+            .variant
+            .or_else(|| used.is_power_of_two().then(|| used.trailing_zeros() as u8));
+        Note { variant, ..note }
+    }
+}
+
+impl fmt::Display for Scale {
+    /// `S R2 G3 P D2`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut notes = Vec::new();
+        for (swara, used) in SWARAS.iter().zip(self.swarasthanas) {
+            if used & 1 != 0 {
+                notes.push(swara.to_string());
+            }
+            notes.extend((1..=3).filter(|v| used & (1 << v) != 0).map(|v| format!("{}{}", swara, v)));
+        }
+        write!(f, "{}", notes.join(" "))
+    }
+}
+
+/// Scales for the ragas of a document: the metadata's arohanam and avarohanam
+/// for the file's raga, built-in scales for the rest
+#[derive(Debug, Clone, Default)]
+pub struct RagaScales {
+    raga: String,
+    scale: Option<Scale>,
+}
+
+impl RagaScales {
+    pub fn new(metadata: &Metadata) -> Self {
+        let given = match (&metadata.arohanam, &metadata.avarohanam) {
+            (Some(arohanam), Some(avarohanam)) => Scale::from_arohanam(arohanam, avarohanam),
+            (Some(one), None) | (None, Some(one)) => Scale::parse(one),
+            (None, None) => None,
+        };
+        Self {
+            raga: normalize(&metadata.raga),
+            scale: given.or_else(|| Scale::builtin(&metadata.raga)),
+        }
+    }
+
+    /// The scale of a raga, or `None` when it isn't known
+    pub fn get(&self, raga: &str) -> Option<Scale> {
+        if normalize(raga) == self.raga {
+            self.scale.clone()
+        } else {
+            Scale::builtin(raga)
+        }
+    }
+}
+
+/// The raga in effect for a line: its own `@raga`, else the section's, else the file's
+pub fn effective<'a>(document: &'a VnaDocument, section: &'a Section, phrase: &'a Phrase) -> &'a str {
+    phrase
+        .raga
+        .as_deref()
+        .or(section.raga.as_deref())
+        .unwrap_or(&document.metadata.raga)
+}
+
+fn index(swara: char) -> usize {
+    SWARAS.iter().position(|s| *s == swara).unwrap_or(0)
+}
+
+/// Lowercase ASCII letters only, with diacritics folded and a final `-am`
+/// shortened: `Mōhanam` and `mohana` are both `mohana`
+fn normalize(raga: &str) -> String {
+    let folded: String = raga
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            'ā' | 'á' => Some('a'),
+            'ī' | 'í' => Some('i'),
+            'ū' | 'ú' => Some('u'),
+            'ē' | 'é' => Some('e'),
+            'ō' | 'ó' => Some('o'),
+            'ṃ' | 'ṁ' => Some('m'),
+            'ṅ' | 'ñ' | 'ṇ' => Some('n'),
+            'ś' | 'ṣ' => Some('s'),
+            'ṭ' => Some('t'),
+            'ḍ' => Some('d'),
+            'ḷ' => Some('l'),
+            'ṛ' | 'ṟ' => Some('r'),
+            c if c.is_ascii_alphabetic() => Some(c),
+            _ => None,
+        })
+        .collect();
+    match folded.strip_suffix("am") {
+        Some(stem) => format!("{}a", stem),
+        None => folded,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_lookup_and_resolution() {
+        let mohanam = Scale::builtin("Mōhanam").unwrap();
+        assert_eq!(Scale::builtin("mohana"), Some(mohanam.clone()));
+        assert_eq!(mohanam.to_string(), "S R2 G3 P D2");
+        assert!(mohanam.contains(&Note::parse("G").unwrap()));
+        assert!(!mohanam.contains(&Note::parse("M").unwrap()));
+        assert!(!mohanam.contains(&Note::parse("R1").unwrap()));
+        assert_eq!(mohanam.resolve(Note::parse("R.").unwrap()).variant, Some(2));
+
+        // Bhairavi uses both dhaivatas, so a plain D stays unresolved
+        let bhairavi = Scale::builtin("bhairavi").unwrap();
+        assert_eq!(bhairavi.resolve(Note::parse("D").unwrap()).variant, None);
+        assert!(bhairavi.contains(&Note::parse("D1").unwrap()));
+
+        assert_eq!(Scale::builtin("ragamalika"), None);
+    }
+
+    #[test]
+    fn test_swarasthanas_follow_raga_changes() {
+        let content = r#"---
+title: "Ragamalika"
+raga: "kalyani"
+tala: "+234+0+0"
+arohanam: "S R2 G3 M2 P D2 N3 S'"
+---
+
+[pallavi]
+SRGM PDNS' | S'NDP MGRS ||
+---- ---- | ---- ---- ||
+
+[charanam]
+@raga: "mayamalavagowla"
+SRGM PDNS' | S'NDP MGRS ||
+---- ---- | ---- ---- ||
+
+@raga: "bhairavi"
+SRGM PDNS' | S'NDP MGRS ||
+---- ---- | ---- ---- ||
+"#;
+
+        let doc = crate::parser::parse(content).unwrap();
+        let timeline = crate::timeline::build(&doc);
+        let madhyamas: Vec<Option<u8>> = timeline
+            .events
+            .iter()
+            .filter_map(|e| e.note.as_ref())
+            .filter(|n| n.swara == 'M')
+            .map(|n| n.variant)
+            .collect();
+        assert_eq!(madhyamas, vec![Some(2), Some(2), Some(1), Some(1), Some(1), Some(1)]);
+
+        let dhaivatas = timeline.events.iter().filter_map(|e| e.note.as_ref()).filter(|n| n.swara == 'D');
+        assert_eq!(dhaivatas.map(|n| n.variant).next_back(), Some(None));
+        assert_eq!(timeline.report().sections[1].raga, "mayamalavagowla");
+    }
+}
@@ -0,0 +1,229 @@
+use serde::{Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Rem, Sub};
+use std::str::FromStr;
+
+/// Exact non-negative fraction, always kept in lowest terms
+/// Durations in aksharas are sums of unit/gati fractions, so floats would drift
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ratio {
+    numer: u64,
+    denom: u64,
+}
+
+impl Ratio {
+    pub const ZERO: Ratio = Ratio { numer: 0, denom: 1 };
+
+    /// Panics if `denom` is zero
+    pub fn new(numer: u64, denom: u64) -> Self {
+        assert!(denom != 0, "Ratio with zero denominator");
+        let divisor = gcd(numer, denom);
+        Self {
+            numer: numer / divisor,
+            denom: denom / divisor,
+        }
+    }
+
+    pub fn from_integer(value: u64) -> Self {
+        Self { numer: value, denom: 1 }
+    }
+
+    pub fn numer(&self) -> u64 {
+        self.numer
+    }
+
+    pub fn denom(&self) -> u64 {
+        self.denom
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numer == 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom == 1
+    }
+
+    pub fn floor(&self) -> u64 {
+        self.numer / self.denom
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.numer as f64 / self.denom as f64
+    }
+
+    /// `self - other`, or None when `other` is larger
+    pub fn checked_sub(self, other: Ratio) -> Option<Ratio> {
+        let denom = lcm(self.denom, other.denom);
+        let left = self.numer * (denom / self.denom);
+        let right = other.numer * (denom / other.denom);
+        left.checked_sub(right).map(|numer| Ratio::new(numer, denom))
+    }
+
+    /// `self - other`, or zero when `other` is larger
+    pub fn saturating_sub(self, other: Ratio) -> Ratio {
+        self.checked_sub(other).unwrap_or(Ratio::ZERO)
+    }
+}
+
+impl Default for Ratio {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl From<u64> for Ratio {
+    fn from(value: u64) -> Self {
+        Self::from_integer(value)
+    }
+}
+
+impl Add for Ratio {
+    type Output = Ratio;
+
+    fn add(self, other: Ratio) -> Ratio {
+        let denom = lcm(self.denom, other.denom);
+        Ratio::new(self.numer * (denom / self.denom) + other.numer * (denom / other.denom), denom)
+    }
+}
+
+impl AddAssign for Ratio {
+    fn add_assign(&mut self, other: Ratio) {
+        *self = *self + other;
+    }
+}
+
+/// Panics in debug builds when `other` is larger, and saturates at zero in
+/// release; use `checked_sub` or `saturating_sub` where that can happen
+impl Sub for Ratio {
+    type Output = Ratio;
+
+    fn sub(self, other: Ratio) -> Ratio {
+        debug_assert!(self >= other, "Ratio subtraction underflow: {} - {}", self, other);
+        self.saturating_sub(other)
+    }
+}
+
+impl Mul for Ratio {
+    type Output = Ratio;
+
+    fn mul(self, other: Ratio) -> Ratio {
+        // Cross-reduce first to keep the intermediates small
+        let a = gcd(self.numer, other.denom);
+        let b = gcd(other.numer, self.denom);
+        Ratio::new(
+            (self.numer / a) * (other.numer / b),
+            (self.denom / b) * (other.denom / a),
+        )
+    }
+}
+
+/// Panics when dividing by zero
+impl Div for Ratio {
+    type Output = Ratio;
+
+    fn div(self, other: Ratio) -> Ratio {
+        self.mul(Ratio::new(other.denom, other.numer))
+    }
+}
+
+/// Remainder after whole multiples, e.g. a position within a tala cycle
+/// Panics when the divisor is zero
+impl Rem for Ratio {
+    type Output = Ratio;
+
+    fn rem(self, other: Ratio) -> Ratio {
+        self - other * Ratio::from((self / other).floor())
+    }
+}
+
+impl Ord for Ratio {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.numer as u128 * other.denom as u128).cmp(&(other.numer as u128 * self.denom as u128))
+    }
+}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.denom == 1 {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+/// Parses `3` or `1/2`
+impl FromStr for Ratio {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("'{}' is not a whole number or fraction such as 3/2", text);
+        let (numer, denom) = text.trim().split_once('/').unwrap_or((text.trim(), "1"));
+        let numer = numer.trim().parse::<u64>().map_err(|_| invalid())?;
+        let denom = denom.trim().parse::<u64>().ok().filter(|d| *d > 0).ok_or_else(invalid)?;
+        Ok(Ratio::new(numer, denom))
+    }
+}
+
+/// Serialized as a plain number, for spreadsheets and JSON consumers
+impl Serialize for Ratio {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.is_integer() {
+            serializer.serialize_u64(self.numer)
+        } else {
+            serializer.serialize_f64(self.to_f64())
+        }
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.max(1)
+}
+
+fn lcm(a: u64, b: u64) -> u64 {
+    a / gcd(a, b) * b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ratio_arithmetic() {
+        let third = Ratio::new(1, 3);
+        let quarter = Ratio::new(2, 8);
+        assert_eq!(quarter, Ratio::new(1, 4));
+        assert_eq!(third + quarter, Ratio::new(7, 12));
+        assert_eq!(third - quarter, Ratio::new(1, 12));
+        assert_eq!(quarter.checked_sub(third), None);
+        assert_eq!(quarter.saturating_sub(third), Ratio::ZERO);
+        assert_eq!(third.checked_sub(quarter), Some(Ratio::new(1, 12)));
+        assert_eq!(third * Ratio::from(3), Ratio::from(1));
+        assert_eq!(quarter / third, Ratio::new(3, 4));
+        assert!(third > quarter);
+        assert_eq!(Ratio::new(7, 2).floor(), 3);
+        assert_eq!(Ratio::new(7, 2).to_string(), "7/2");
+        assert_eq!(Ratio::new(4, 2).to_string(), "2");
+        assert_eq!(Ratio::new(19, 2) % Ratio::from(8), Ratio::new(3, 2));
+        assert_eq!("3/6".parse::<Ratio>(), Ok(Ratio::new(1, 2)));
+        assert_eq!("5".parse::<Ratio>(), Ok(Ratio::from(5)));
+        assert!("1/0".parse::<Ratio>().is_err() && "-1".parse::<Ratio>().is_err());
+    }
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "underflow")]
+    fn test_ratio_sub_underflow() {
+        let _ = Ratio::new(1, 4) - Ratio::new(1, 3);
+    }
+}
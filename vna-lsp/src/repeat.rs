@@ -0,0 +1,231 @@
+use crate::types::*;
+use anyhow::{anyhow, Result};
+
+/// Most times a group may repeat, so that a typo can't blow up the phrase
+pub const MAX_REPEAT_COUNT: usize = 64;
+
+/// A group of tokens sung several times: `(SRG, PDS)x3`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepeatGroup {
+    pub tokens: Vec<String>,
+    pub count: usize,
+}
+
+/// Whether a token is written as a repeat group
+pub fn is_group(token: &str) -> bool {
+    token.starts_with('(')
+}
+
+/// Parse a repeat group token: `(SRG, PDS)x3` → tokens ["SRG,", "PDS"], count 3
+pub fn parse_group(token: &str) -> Result<RepeatGroup> {
+    let inner = token
+        .strip_prefix('(')
+        .ok_or_else(|| anyhow!("'{}' is not a repeat group", token))?;
+    let (body, count) = inner
+        .rsplit_once(')')
+        .ok_or_else(|| anyhow!("Repeat group '{}' is missing its closing ')'", token))?;
+
+    let count = count
+        .strip_prefix('x')
+        .and_then(|n| n.parse::<usize>().ok())
+        .filter(|n| *n > 0)
+        .ok_or_else(|| anyhow!("Repeat group '{}' needs a count after ')', e.g. ')x3'", token))?;
+    if count > MAX_REPEAT_COUNT {
+        return Err(anyhow!(
+            "Repeat group '{}' repeats {} times; at most {} are allowed",
+            token,
+            count,
+            MAX_REPEAT_COUNT
+        ));
+    }
+    if body.contains('|') {
+        return Err(anyhow!("Repeat group '{}' can't contain beat markers", token));
+    }
+    if body.contains('(') || body.contains(')') {
+        return Err(anyhow!("Repeat group '{}' can't contain another group", token));
+    }
+
+    let tokens: Vec<String> = body.split_whitespace().map(|t| t.to_string()).collect();
+    if tokens.is_empty() {
+        return Err(anyhow!("Repeat group '{}' is empty", token));
+    }
+
+    Ok(RepeatGroup { tokens, count })
+}
+
+/// Write a repeat group back in its compact form
+pub fn format_group(group: &RepeatGroup) -> String {
+    format!("({})x{}", group.tokens.join(" "), group.count)
+}
+
+/// Rewrite the tokens of a token, inside the group if it is one
+pub fn map_tokens(token: &str, mut f: impl FnMut(&str) -> Result<String>) -> Result<String> {
+    if !is_group(token) {
+        return f(token);
+    }
+
+    let mut group = parse_group(token)?;
+    for inner in &mut group.tokens {
+        *inner = f(inner)?;
+    }
+    Ok(format_group(&group))
+}
+
+/// Write out every repeat group in a phrase, moving beat markers to match
+///
+/// Each swara group must be matched by a sahitya group with the same count at
+/// the same position, so that the two lines stay aligned as written.
+pub fn expand_phrase(phrase: &Phrase) -> Result<Phrase> {
+    let extra_tokens = phrase.sahitya_lines.iter().flat_map(|l| &l.syllables);
+    if !phrase.swaras.iter().chain(&phrase.sahitya).chain(extra_tokens).any(|t| is_group(t)) {
+        return Ok(phrase.clone());
+    }
+
+    check_groups(&phrase.swaras, &phrase.sahitya, "sahitya")?;
+    for line in phrase.sahitya_lines.iter().filter(|l| l.is_aligned()) {
+        check_groups(&phrase.swaras, &line.syllables, &line.tag())?;
+    }
+
+    let (swaras, offsets) = expand_tokens(&phrase.swaras)?;
+    let (sahitya, _) = expand_tokens(&phrase.sahitya)?;
+    let mut sahitya_lines = phrase.sahitya_lines.clone();
+    for line in &mut sahitya_lines {
+        line.syllables = expand_tokens(&line.syllables)?.0;
+    }
+    let beat_positions = phrase
+        .beat_positions
+        .iter()
+        .map(|&p| offsets.get(p).copied().unwrap_or(swaras.len()))
+        .collect();
+
+    Ok(Phrase {
+        swaras,
+        sahitya,
+        sahitya_lines,
+        beat_positions,
+        ..phrase.clone()
+    })
+}
+
+/// Each swara group must be matched by a group with the same count in the words line
+fn check_groups(swaras: &[String], words: &[String], name: &str) -> Result<()> {
+    for (i, (swara, word)) in swaras.iter().zip(words).enumerate() {
+        let count = |token: &str| -> Result<Option<usize>> {
+            Ok(if is_group(token) { Some(parse_group(token)?.count) } else { None })
+        };
+        if count(swara)? != count(word)? {
+            return Err(anyhow!(
+                "Repeat group at position {} doesn't match: swara '{}' vs {} '{}'",
+                i + 1,
+                swara,
+                name,
+                word
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Write out every repeat group in the document
+///
+/// Phrases whose groups are malformed are left as written; the validator
+/// reports them.
+pub fn expand(document: &VnaDocument) -> VnaDocument {
+    let mut expanded = document.clone();
+    for phrase in expanded.sections.iter_mut().flat_map(|s| s.phrases.iter_mut()) {
+        if let Ok(written_out) = expand_phrase(phrase) {
+            *phrase = written_out;
+        }
+    }
+    expanded
+}
+
+/// Expanded tokens, with the expanded index at which each original token starts
+fn expand_tokens(tokens: &[String]) -> Result<(Vec<String>, Vec<usize>)> {
+    let mut expanded = Vec::new();
+    let mut offsets = Vec::new();
+
+    for token in tokens {
+        offsets.push(expanded.len());
+        if is_group(token) {
+            let group = parse_group(token)?;
+            for _ in 0..group.count {
+                expanded.extend(group.tokens.iter().cloned());
+            }
+        } else {
+            expanded.push(token.clone());
+        }
+    }
+
+    Ok((expanded, offsets))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    const CONTENT: &str = r#"---
+title: "Korvai"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[korvai]
+(S,,R G,,P)x3   | D,,,   | (PDS')x2 ||
+(ta--- ka---)x3 | dhi--- | (ta-ka)x2 ||
+"#;
+
+    #[test]
+    fn test_expand_phrase() {
+        let doc = parse(CONTENT).unwrap();
+        let phrase = &doc.sections[0].phrases[0];
+        assert_eq!(phrase.swaras, vec!["(S,,R G,,P)x3", "D,,,", "(PDS')x2"]);
+        assert_eq!(phrase.beat_positions, vec![1, 2]);
+
+        let expanded = expand_phrase(phrase).unwrap();
+        assert_eq!(expanded.swaras.len(), 9);
+        assert_eq!(expanded.swaras[..2], ["S,,R", "G,,P"]);
+        assert_eq!(expanded.sahitya[6], "dhi---");
+        assert_eq!(expanded.beat_positions, vec![6, 7]);
+
+        // The formatter keeps the compact form
+        let formatted = crate::formatter::format(&doc).unwrap();
+        assert!(formatted.contains("(S,,R G,,P)x3"));
+        assert_eq!(parse(&formatted).unwrap(), doc);
+
+        // Checks and timing see the written-out phrase
+        let issues = crate::validator::validate(&doc).unwrap();
+        assert!(issues.iter().all(|i| i.severity != Severity::Error), "{:?}", issues);
+        assert_eq!(crate::timeline::build(&doc).length, crate::ratio::Ratio::new(17, 2));
+    }
+
+    #[test]
+    fn test_malformed_groups() {
+        assert!(parse_group("(SRG)").is_err());
+        assert!(parse_group("(SRG)x0").is_err());
+        assert!(parse_group("(SRG").is_err());
+        assert!(parse_group("(S|R)x2").is_err());
+        assert_eq!(parse_group("(SRGP)x64").unwrap().count, MAX_REPEAT_COUNT);
+        assert!(parse_group("(SRGP)x65").is_err());
+        assert_eq!(map_tokens("(sa ri)x2", |t| Ok(t.to_uppercase())).unwrap(), "(SA RI)x2");
+
+        let doc = parse(&CONTENT.replace("(ta-ka)x2", "(ta-ka)x3")).unwrap();
+        let error = expand_phrase(&doc.sections[0].phrases[0]).unwrap_err();
+        assert!(error.to_string().contains("position 3"), "{}", error);
+        let issues = crate::validator::validate(&doc).unwrap();
+        assert!(issues.iter().any(|i| i.code.as_deref() == Some("invalid_repeat")));
+    }
+
+    #[test]
+    fn test_repeat_count_limit() {
+        let content = CONTENT.replace("(PDS')x2", "(PDS')x20000000").replace("(ta-ka)x2", "(ta-ka)x20000000");
+        let doc = parse(&content).unwrap();
+        let issues = crate::validator::validate(&doc).unwrap();
+        let issue = issues.iter().find(|i| i.code.as_deref() == Some("invalid_repeat")).unwrap();
+        assert!(issue.message.contains("at most 64"), "{}", issue.message);
+
+        // Left as written rather than expanded
+        assert_eq!(expand(&doc).sections[0].phrases[0].swaras.len(), 3);
+    }
+}
@@ -0,0 +1,355 @@
+use crate::ratio::Ratio;
+use crate::repeat;
+use crate::swara_parser::{is_note, parse_swara_units, split_token_gati};
+use crate::timeline::{kalam_speed, tala_aksharas, DEFAULT_GATI};
+use crate::types::*;
+
+/// Korvais and moras found in each section
+#[derive(Debug, Clone, PartialEq)]
+pub struct RhythmAnalysis {
+    pub sections: Vec<SectionRhythm>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionRhythm {
+    pub name: String,
+    /// Aksharas per avartanam of the section's tala
+    pub cycle: Option<u64>,
+    /// Where structures should end
+    pub target: Eduppu,
+    pub structures: Vec<Korvai>,
+}
+
+/// A pattern played three times, with an optional gap (karvai) between repetitions
+///
+/// Positions count aksharas from the start of the section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Korvai {
+    /// Swara line where the first repetition starts
+    pub line_number: usize,
+    /// Written as a `(...)x3` repeat group rather than found by comparing notes
+    pub marked: bool,
+    /// Length of one repetition without its gap
+    pub pattern: Ratio,
+    pub gap: Ratio,
+    /// The last repetition is followed by its gap (only when marked)
+    pub final_gap: bool,
+    pub start: Ratio,
+    pub length: Ratio,
+    /// Gati at the start of the structure
+    pub gati: u8,
+    /// Where the structure ends within the avartanam, in aksharas after samam,
+    /// counting from the section's eduppu
+    pub landing: Option<Ratio>,
+    /// Ends on the section's target
+    pub lands: bool,
+}
+
+const REPETITIONS: u64 = 3;
+
+impl Korvai {
+    pub fn end(&self) -> Ratio {
+        self.start + self.length
+    }
+
+    /// The arithmetic, e.g. `3 × (5 + 2 gap) − 2 = 19 aksharas`
+    /// Counted in units of the starting gati when aksharas aren't whole:
+    /// `3 × (5 + 2 gap) − 2 = 19 units of gati 4 (19/4 aksharas)`
+    pub fn breakdown(&self) -> String {
+        let gati = Ratio::from(self.gati as u64);
+        let in_aksharas = self.pattern.is_integer() && self.gap.is_integer();
+        let in_units = (self.pattern * gati).is_integer() && (self.gap * gati).is_integer();
+        let scale = if !in_aksharas && in_units { gati } else { Ratio::from(1) };
+        let (pattern, gap, length) = (self.pattern * scale, self.gap * scale, self.length * scale);
+
+        let sum = if gap.is_zero() {
+            format!("{} × {}", REPETITIONS, pattern)
+        } else if self.final_gap {
+            format!("{} × ({} + {} gap)", REPETITIONS, pattern, gap)
+        } else {
+            format!("{} × ({} + {} gap) − {}", REPETITIONS, pattern, gap, gap)
+        };
+
+        if !in_aksharas && in_units {
+            format!("{} = {} units of gati {} ({} aksharas)", sum, length, self.gati, self.length)
+        } else {
+            format!("{} = {} aksharas", sum, length)
+        }
+    }
+}
+
+/// One swara unit of a section, with repeat groups written out
+#[derive(Debug, Clone)]
+struct Unit {
+    swara: String,
+    size: Ratio,
+    line_number: usize,
+    gati: u8,
+    /// Index of the `(...)x3` group the unit was written in
+    group: Option<usize>,
+}
+
+impl Unit {
+    fn is_filler(&self) -> bool {
+        !is_note(&self.swara)
+    }
+
+    fn same(&self, other: &Unit) -> bool {
+        self.swara == other.swara && self.size == other.size
+    }
+}
+
+/// Find korvais and moras: `(...)x3` repeat groups, and patterns of at least
+/// one akshara that are played exactly three times, separated by equal gaps of
+/// sustains or rests. Each is checked to end on `eduppu`, or by default on the
+/// section's own eduppu (samam without one).
+pub fn analyze(document: &VnaDocument, eduppu: Option<Eduppu>) -> RhythmAnalysis {
+    let file_gati = document.metadata.gati.filter(|g| *g > 0).unwrap_or(DEFAULT_GATI);
+
+    let sections = document
+        .sections
+        .iter()
+        .map(|section| {
+            let section_gati = section.gati.filter(|g| *g > 0).unwrap_or(file_gati);
+            let cycle = tala_aksharas(section.tala.as_deref().unwrap_or(&document.metadata.tala));
+            let section_eduppu = section.eduppu.or(document.metadata.eduppu).unwrap_or(Eduppu::SAMAM);
+            let target = eduppu.unwrap_or(section_eduppu);
+            let units = section_units(section, section_gati);
+
+            let mut structures = marked_structures(&units);
+            structures.extend(found_structures(&units));
+            structures.sort_by_key(|k| k.start);
+            if let Some(cycle) = cycle {
+                let start = section_eduppu.offset_in(cycle);
+                for korvai in &mut structures {
+                    let landing = (start + korvai.end()) % Ratio::from(cycle);
+                    korvai.landing = Some(landing);
+                    korvai.lands = landing == target.offset_in(cycle);
+                }
+            }
+
+            SectionRhythm {
+                name: section.header(),
+                cycle,
+                target,
+                structures,
+            }
+        })
+        .collect();
+
+    RhythmAnalysis { sections }
+}
+
+fn section_units(section: &Section, section_gati: u8) -> Vec<Unit> {
+    let mut units = Vec::new();
+    let mut groups = 0;
+
+    for phrase in &section.phrases {
+        let line_gati = phrase.gati.filter(|g| *g > 0).unwrap_or(section_gati);
+        // A faster kalam plays the same units as a multiple of the gati
+        let speed = kalam_speed(phrase.kalam.or(section.kalam).unwrap_or(1));
+
+        for token in &phrase.swaras {
+            let (tokens, group) = match repeat::parse_group(token) {
+                Ok(group) => {
+                    let marked = (group.count as u64 == REPETITIONS).then(|| {
+                        groups += 1;
+                        groups - 1
+                    });
+                    (vec![group.tokens; group.count].concat(), marked)
+                }
+                Err(_) => (vec![token.clone()], None),
+            };
+
+            for token in tokens {
+                let (text, token_gati) = split_token_gati(&token);
+                let gati = token_gati
+                    .and_then(|g| g.parse::<u8>().ok())
+                    .filter(|g| *g > 0)
+                    .unwrap_or(line_gati)
+                    .saturating_mul(speed as u8);
+                for swara in parse_swara_units(text) {
+                    units.push(Unit {
+                        swara,
+                        size: Ratio::new(1, gati as u64),
+                        line_number: phrase.line_number,
+                        gati,
+                        group,
+                    });
+                }
+            }
+        }
+    }
+
+    units
+}
+
+fn position(units: &[Unit], index: usize) -> Ratio {
+    units[..index].iter().fold(Ratio::ZERO, |sum, u| sum + u.size)
+}
+
+fn length(units: &[Unit]) -> Ratio {
+    position(units, units.len())
+}
+
+/// `(...)x3` groups, with the group's trailing sustains and rests as the gap
+fn marked_structures(units: &[Unit]) -> Vec<Korvai> {
+    let mut structures = Vec::new();
+    let mut start = 0;
+
+    while start < units.len() {
+        let Some(group) = units[start].group else {
+            start += 1;
+            continue;
+        };
+        let end = start + units[start..].iter().take_while(|u| u.group == Some(group)).count();
+        let repetition = &units[start..start + (end - start) / REPETITIONS as usize];
+        let sounded = repetition.iter().rposition(|u| !u.is_filler()).map_or(0, |i| i + 1);
+
+        structures.push(Korvai {
+            line_number: units[start].line_number,
+            marked: true,
+            pattern: length(&repetition[..sounded]),
+            gap: length(&repetition[sounded..]),
+            final_gap: true,
+            start: position(units, start),
+            length: length(&units[start..end]),
+            gati: units[start].gati,
+            landing: None,
+            lands: false,
+        });
+        start = end;
+    }
+
+    structures
+}
+
+/// Patterns repeated exactly three times outside marked groups, longest first
+/// at each starting point
+fn found_structures(units: &[Unit]) -> Vec<Korvai> {
+    let mut structures = Vec::new();
+    let mut start = 0;
+
+    while start < units.len() {
+        let best = (1..=units.len().saturating_sub(start) / REPETITIONS as usize)
+            .rev()
+            .find_map(|pattern| match_at(units, start, pattern));
+
+        match best {
+            Some((pattern, gap)) => {
+                let end = start + REPETITIONS as usize * pattern + (REPETITIONS as usize - 1) * gap;
+                structures.push(Korvai {
+                    line_number: units[start].line_number,
+                    marked: false,
+                    pattern: length(&units[start..start + pattern]),
+                    gap: length(&units[start + pattern..start + pattern + gap]),
+                    final_gap: false,
+                    start: position(units, start),
+                    length: length(&units[start..end]),
+                    gati: units[start].gati,
+                    landing: None,
+                    lands: false,
+                });
+                start = end;
+            }
+            None => start += 1,
+        }
+    }
+
+    structures
+}
+
+/// The gap length (in units) if `pattern` units from `start` are played three
+/// times with equal filler gaps, and not a fourth time on either side
+fn match_at(units: &[Unit], start: usize, pattern: usize) -> Option<(usize, usize)> {
+    let first = &units[start..start + pattern];
+    if first[0].is_filler() || first[pattern - 1].is_filler() || first.iter().any(|u| u.group.is_some()) {
+        return None;
+    }
+    if length(first) < Ratio::from(1) {
+        return None;
+    }
+
+    let gap = units[start + pattern..].iter().take_while(|u| u.is_filler()).count();
+    // Without gaps, a pattern made of a shorter repeated block is just that block repeated
+    if gap == 0 && (1..pattern).any(|d| pattern.is_multiple_of(d) && (d..pattern).all(|i| first[i].same(&first[i % d]))) {
+        return None;
+    }
+    let gap_units = &units[start + pattern..start + pattern + gap];
+    let step = pattern + gap;
+
+    let repeats_at = |at: usize| -> bool {
+        at + pattern <= units.len()
+            && units[at..at + pattern].iter().zip(first).all(|(a, b)| a.group.is_none() && a.same(b))
+    };
+    let gap_at = |at: usize| -> bool {
+        at + gap <= units.len()
+            && units[at..at + gap].iter().zip(gap_units).all(|(a, b)| a.group.is_none() && a.same(b))
+    };
+
+    let thrice = (1..REPETITIONS as usize).all(|i| gap_at(start + i * step - gap) && repeats_at(start + i * step));
+    if !thrice {
+        return None;
+    }
+
+    let end = start + REPETITIONS as usize * step - gap;
+    let fourth_after = gap_at(end) && repeats_at(end + gap);
+    let fourth_before = start >= step && gap_at(start - gap) && repeats_at(start - step);
+    (!fourth_after && !fourth_before).then_some((pattern, gap))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    const CONTENT: &str = r#"---
+title: "Korvai"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[mora]
+SRGM PDNS' ,,,, SRGM | PDNS' ,,,, | SRGM PDNS' ||
+sa-- ---- ---- ---- | ----- ---- | ---- ----- ||
+
+[korvai]
+(S,R, G,,,)x3 | SRGM PDNS' ||
+(sa-- ----)x3 | ---- ----- ||
+
+[sangati]
+SRGM SRGM | SRGM SRGM | SRGM SRGM ||
+---- ---- | ---- ---- | ---- ---- ||
+"#;
+
+    #[test]
+    fn test_mora_lands_on_samam() {
+        let doc = parse(CONTENT).unwrap();
+        let analysis = analyze(&doc, None);
+        let mora = &analysis.sections[0].structures;
+
+        assert_eq!(mora.len(), 1);
+        assert!(!mora[0].marked);
+        assert_eq!(mora[0].breakdown(), "3 × (2 + 1 gap) − 1 = 8 aksharas");
+        assert_eq!(mora[0].landing, Some(Ratio::ZERO));
+        assert!(mora[0].lands);
+
+        // Six repetitions of one akshara are not a korvai
+        assert!(analysis.sections[2].structures.is_empty());
+    }
+
+    #[test]
+    fn test_marked_korvai_and_eduppu() {
+        let doc = parse(CONTENT).unwrap();
+        let korvai = &analyze(&doc, None).sections[1].structures;
+
+        assert_eq!(korvai.len(), 1);
+        assert!(korvai[0].marked);
+        assert_eq!(korvai[0].breakdown(), "3 × (5 + 3 gap) = 24 units of gati 4 (6 aksharas)");
+        assert_eq!(korvai[0].landing, Some(Ratio::from(6)));
+        assert!(!korvai[0].lands);
+
+        let korvai = &analyze(&doc, Some("6".parse().unwrap())).sections[1].structures;
+        assert!(korvai[0].lands);
+    }
+}
@@ -1,29 +1,103 @@
-use shlesha::Shlesha;
+use crate::lyrics::{WORD_BREAK, WORD_JOIN};
+use crate::sahitya_scheme::{detect_token_scheme, SahityaScheme};
+use crate::syllabifier::SyllabifierRegistry;
 
 /// Parse sahitya tokens into syllable units
 /// Each dash counts as a separate unit
 /// Backticks (`) mark explicit syllable boundaries
+/// Word breaks (_) separate words for lyrics; they split syllables but count no units
 /// If backticks exist in token, ONLY split on backticks (ignore auto syllabification)
 /// Examples:
 ///   "nin`nu" → ["nin", "nu"]
 ///   "yun`---" → ["yun", "-", "-", "-"]
 ///   "ninnu" → ["ni", "nnu"] (auto split based on script)
+///   "నిన్ను" → ["ని", "న్ను"] (native script, split into aksharas)
 pub fn parse_sahitya_token(token: &str) -> Vec<String> {
     parse_sahitya_token_with_lang(token, None)
 }
 
 /// Parse sahitya tokens with optional language hint for better syllabification
+/// The romanisation scheme is detected from the token itself, defaulting to ISO 15919
 pub fn parse_sahitya_token_with_lang(token: &str, language: Option<&str>) -> Vec<String> {
+    parse_sahitya_token_in_scheme(token, language, SahityaScheme::Iso)
+}
+
+/// Parse sahitya tokens written in a known scheme
+/// `default_scheme` is used when the token itself is ambiguous (e.g. "ri", "nā")
+pub fn parse_sahitya_token_in_scheme(
+    token: &str,
+    language: Option<&str>,
+    default_scheme: SahityaScheme,
+) -> Vec<String> {
+    parse_sahitya_token_with_registry(token, SyllabifierRegistry::shared(), language, default_scheme)
+}
+
+/// Parse sahitya tokens using the language's registered syllabifier and the registry's exception dictionary
+pub fn parse_sahitya_token_with_registry(
+    token: &str,
+    registry: &SyllabifierRegistry,
+    language: Option<&str>,
+    default_scheme: SahityaScheme,
+) -> Vec<String> {
+    // A word join only ties the token to the one before
+    if let Some(token) = token.strip_prefix(WORD_JOIN) {
+        return parse_sahitya_token_with_registry(token, registry, language, default_scheme);
+    }
+
+    // A word break is also a syllable boundary; words are parsed separately
+    if token.contains(WORD_BREAK) {
+        return token
+            .split(WORD_BREAK)
+            .flat_map(|word| parse_sahitya_token_with_registry(word, registry, language, default_scheme))
+            .collect();
+    }
+
     // Check if token contains any backticks
     if token.contains('`') {
         // Manual mode: split ONLY on backticks, then handle dashes
         parse_with_backticks(token)
     } else {
-        // Automatic mode: use shlesha for syllabification
-        parse_automatic_with_shlesha(token, language)
+        // Automatic mode: exception dictionary, then the language's syllabifier
+        let scheme = detect_token_scheme(token).unwrap_or(default_scheme);
+        parse_automatic(token, registry, language, scheme)
     }
 }
 
+/// Parse a sahitya token, also marking the syllables that start a new word
+/// A token starts a word unless it begins with `=`, and so does each `_`; the
+/// break goes on the next syllable, which `pending_break` carries across
+/// tokens without one
+pub(crate) fn parse_sahitya_token_with_word_breaks(
+    token: &str,
+    registry: &SyllabifierRegistry,
+    language: Option<&str>,
+    default_scheme: SahityaScheme,
+    pending_break: &mut bool,
+) -> (Vec<String>, Vec<bool>) {
+    let mut units = Vec::new();
+    let mut word_breaks = Vec::new();
+    let token = match token.strip_prefix(WORD_JOIN) {
+        Some(rest) => {
+            *pending_break = false;
+            rest
+        }
+        None => {
+            *pending_break = true;
+            token
+        }
+    };
+
+    for (i, word) in token.split(WORD_BREAK).enumerate() {
+        *pending_break |= i > 0;
+        for unit in parse_sahitya_token_with_registry(word, registry, language, default_scheme) {
+            word_breaks.push(unit != "-" && std::mem::take(pending_break));
+            units.push(unit);
+        }
+    }
+
+    (units, word_breaks)
+}
+
 /// Parse token with manual backtick boundaries
 fn parse_with_backticks(token: &str) -> Vec<String> {
     let mut units = Vec::new();
@@ -77,8 +151,13 @@ fn split_on_dashes(segment: &str) -> Vec<String> {
     result
 }
 
-/// Parse token with automatic syllabification using shlesha
-fn parse_automatic_with_shlesha(token: &str, language: Option<&str>) -> Vec<String> {
+/// Parse token with automatic syllabification
+fn parse_automatic(
+    token: &str,
+    registry: &SyllabifierRegistry,
+    language: Option<&str>,
+    scheme: SahityaScheme,
+) -> Vec<String> {
     let mut units = Vec::new();
     
     // Special case: if token is all dashes, just split each dash
@@ -87,14 +166,13 @@ fn parse_automatic_with_shlesha(token: &str, language: Option<&str>) -> Vec<Stri
     }
     
     // Otherwise, handle mixed content
-    let mut chars = token.chars().peekable();
     let mut current_segment = String::new();
     
-    while let Some(ch) = chars.next() {
+    for ch in token.chars() {
         if ch == '-' {
             // Process any accumulated segment
             if !current_segment.is_empty() {
-                units.extend(syllabify_segment(&current_segment, language));
+                units.extend(registry.syllabify(&current_segment, language, scheme));
                 current_segment.clear();
             }
             // Add the dash
@@ -106,142 +184,12 @@ fn parse_automatic_with_shlesha(token: &str, language: Option<&str>) -> Vec<Stri
     
     // Process final segment if any
     if !current_segment.is_empty() {
-        units.extend(syllabify_segment(&current_segment, language));
+        units.extend(registry.syllabify(&current_segment, language, scheme));
     }
     
     units
 }
 
-/// Syllabify a segment (no dashes) using shlesha
-fn syllabify_segment(segment: &str, language: Option<&str>) -> Vec<String> {
-    if segment.is_empty() {
-        return vec![];
-    }
-    
-    let transliterator = Shlesha::new();
-    
-    // Determine target script based on language
-    let target_script = match language {
-        Some("telugu") => "telugu",
-        Some("tamil") => "tamil",
-        Some("kannada") => "devanagari", // Kannada might not be supported yet
-        Some("malayalam") => "devanagari", // Malayalam might not be supported yet
-        Some("sanskrit") | Some("hindi") => "devanagari",
-        _ => "devanagari", // Default to Devanagari
-    };
-    
-    // Transliterate to the target script to get proper syllable boundaries
-    match transliterator.transliterate(segment, "iso", target_script) {
-        Ok(native_script) => {
-            // Split by graphemes (which correspond to syllables in native scripts)
-            use unicode_segmentation::UnicodeSegmentation;
-            let graphemes: Vec<&str> = native_script.graphemes(true).collect();
-            
-            let mut syllables = Vec::new();
-            
-            // Transliterate each grapheme back to ISO
-            for grapheme in graphemes {
-                match transliterator.transliterate(grapheme, target_script, "iso") {
-                    Ok(syllable) => syllables.push(syllable),
-                    Err(_) => {
-                        // If we can't transliterate back, fall back to simple parsing
-                        return parse_vowel_based(segment);
-                    }
-                }
-            }
-            
-            syllables
-        }
-        Err(_) => {
-            // Fallback to simple vowel-based parsing if transliteration fails
-            parse_vowel_based(segment)
-        }
-    }
-}
-
-/// Simple vowel-based syllabification
-fn parse_vowel_based(segment: &str) -> Vec<String> {
-    let mut syllables = Vec::new();
-    let mut current = String::new();
-    let mut chars = segment.chars().peekable();
-    
-    while let Some(ch) = chars.next() {
-        current.push(ch);
-        
-        // Check if this character is a vowel
-        if is_simple_vowel(ch) {
-            // Look ahead to see if we should continue the syllable
-            let mut should_end = true;
-            
-            if let Some(&next_ch) = chars.peek() {
-                // If next is also a vowel (like 'aa', 'ii'), keep together
-                if is_simple_vowel(next_ch) && could_be_long_vowel(ch, next_ch) {
-                    should_end = false;
-                }
-                // If next is a consonant, we might want to include it
-                else if !is_simple_vowel(next_ch) {
-                    // Check further ahead
-                    let mut temp_chars = chars.clone();
-                    temp_chars.next(); // Skip the consonant
-                    
-                    if let Some(&after_cons) = temp_chars.peek() {
-                        // If pattern is vowel-consonant-vowel, end here
-                        if is_simple_vowel(after_cons) {
-                            should_end = true;
-                        } else {
-                            // Pattern is vowel-consonant-consonant, include first consonant
-                            should_end = false;
-                        }
-                    } else {
-                        // End of string after consonant, include it
-                        should_end = false;
-                    }
-                }
-            }
-            
-            if should_end && !current.is_empty() {
-                syllables.push(current.clone());
-                current.clear();
-            }
-        }
-    }
-    
-    // Add any remaining content
-    if !current.is_empty() {
-        syllables.push(current);
-    }
-    
-    // If no syllables were created, just return the whole segment
-    if syllables.is_empty() {
-        vec![segment.to_string()]
-    } else {
-        syllables
-    }
-}
-
-/// Check if character is a simple vowel
-fn is_simple_vowel(ch: char) -> bool {
-    matches!(ch, 'a' | 'ā' | 'i' | 'ī' | 'u' | 'ū' | 'e' | 'ē' | 'o' | 'ō' | 
-                 'A' | 'I' | 'U' | 'E' | 'O')
-}
-
-/// Check if two vowels could form a long vowel
-fn could_be_long_vowel(first: char, second: char) -> bool {
-    matches!((first, second), 
-        ('a', 'a') | ('a', 'ā') | ('ā', 'a') |
-        ('i', 'i') | ('i', 'ī') | ('ī', 'i') |
-        ('u', 'u') | ('u', 'ū') | ('ū', 'u') |
-        ('e', 'e') | ('e', 'ē') | ('ē', 'e') |
-        ('o', 'o') | ('o', 'ō') | ('ō', 'o')
-    )
-}
-
-/// Fallback parsing when shlesha is not available or fails
-fn parse_automatic_fallback(segment: &str) -> Vec<String> {
-    // Simple fallback: just return the segment as a single unit
-    vec![segment.to_string()]
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -271,6 +219,11 @@ mod tests {
         assert_eq!(parse_sahitya_token("yun`---"), vec!["yun", "-", "-", "-"]);
         assert_eq!(parse_sahitya_token("nā`---"), vec!["nā", "-", "-", "-"]);
         assert_eq!(parse_sahitya_token("nin`nu`ko`ri"), vec!["nin", "nu", "ko", "ri"]);
+
+        // Word breaks count no units
+        assert_eq!(parse_sahitya_token("nu-_"), vec!["nu", "-"]);
+        assert_eq!(parse_sahitya_token("nin`nu_kō"), vec!["nin", "nu", "kō"]);
+        assert_eq!(parse_sahitya_token("nu_kō--"), vec!["nu", "kō", "-", "-"]);
         
         // Mixed with dashes (automatic mode)
         // "nin" splits as ["ni", "n"] because final 'n' with halant is separate grapheme
@@ -285,4 +238,36 @@ mod tests {
         // "saṅgīta" → ["स", "ङ्गी", "त"] → ["sa", "ṅgī", "ta"]
         assert_eq!(parse_sahitya_token("saṅgīta"), vec!["sa", "ṅgī", "ta"]);
     }
+
+    #[test]
+    fn test_parse_sahitya_token_other_schemes() {
+        // Harvard-Kyoto is detected from the token and read as ISO
+        assert_eq!(parse_sahitya_token("ukO-"), vec!["u", "kō", "-"]);
+        // Decomposed diacritics syllabify like precomposed ones
+        assert_eq!(parse_sahitya_token("uko\u{304}-"), vec!["u", "kō", "-"]);
+        // Ambiguous tokens follow the document's scheme
+        assert_eq!(
+            parse_sahitya_token_in_scheme("zri", None, SahityaScheme::HarvardKyoto),
+            vec!["śri"]
+        );
+    }
+
+    #[test]
+    fn test_parse_sahitya_token_native_script() {
+        assert_eq!(parse_sahitya_token("నిన్ను-"), vec!["ని", "న్ను", "-"]);
+        assert_eq!(parse_sahitya_token("కో---"), vec!["కో", "-", "-", "-"]);
+        assert_eq!(parse_sahitya_token("நின்னு-"), vec!["நின்", "னு", "-"]);
+        assert_eq!(parse_sahitya_token("ನಿನ್ನು"), vec!["ನಿ", "ನ್ನು"]);
+        assert_eq!(parse_sahitya_token("നിന്നു"), vec!["നി", "ന്നു"]);
+        assert_eq!(parse_sahitya_token("`नि`न्नु-"), vec!["नि", "न्नु", "-"]);
+    }
+
+    #[test]
+    fn test_parse_sahitya_token_kannada_language() {
+        // Kannada is used directly instead of falling back to Devanagari
+        assert_eq!(
+            parse_sahitya_token_with_lang("ninnukōri", Some("kannada")),
+            vec!["ni", "nnu", "kō", "ri"]
+        );
+    }
 }
\ No newline at end of file
@@ -0,0 +1,420 @@
+use crate::repeat;
+use crate::script::{self, transliterator, Script};
+use crate::types::VnaDocument;
+use anyhow::{anyhow, Result};
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+/// Romanisation schemes and native scripts found in sahitya lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SahityaScheme {
+    /// ISO 15919 (`kō`, `r̥`, `ṁ`)
+    Iso,
+    /// IAST (`ṛ`, `ṃ`, no short/long e/o distinction)
+    Iast,
+    /// Harvard-Kyoto (`kO`, `nAma`)
+    HarvardKyoto,
+    /// Plain ASCII without any diacritics (`ko`)
+    Ascii,
+    /// Native script (`కో`, `கோ`)
+    Native(Script),
+}
+
+impl SahityaScheme {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SahityaScheme::Iso => "iso",
+            SahityaScheme::Iast => "iast",
+            SahityaScheme::HarvardKyoto => "harvard-kyoto",
+            SahityaScheme::Ascii => "ascii",
+            SahityaScheme::Native(script) => script.name(),
+        }
+    }
+
+    /// Look up a scheme by name, accepting the usual aliases
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "iso" | "iso15919" | "iso-15919" => Some(SahityaScheme::Iso),
+            "iast" => Some(SahityaScheme::Iast),
+            "hk" | "harvard-kyoto" | "harvard_kyoto" => Some(SahityaScheme::HarvardKyoto),
+            "ascii" => Some(SahityaScheme::Ascii),
+            other => Script::from_name(other).map(SahityaScheme::Native),
+        }
+    }
+}
+
+/// Characters that only appear in ISO 15919 text
+const ISO_MARKERS: &[char] = &['ē', 'ō', 'ṁ', 'ṟ', 'ṉ', 'ḻ', '\u{325}'];
+
+/// ISO 15919 letters for Dravidian consonants, which IAST has no letters for
+const DRAVIDIAN_LETTERS: &[char] = &['ṟ', 'ṉ', 'ḻ', 'ṯ'];
+
+/// Characters that only appear in IAST text
+const IAST_MARKERS: &[char] = &['ṛ', 'ṝ', 'ḷ', 'ḹ', 'ṃ'];
+
+/// Uppercase letters Harvard-Kyoto uses for long vowels and retroflexes
+const HK_CAPITALS: &[char] = &['A', 'I', 'U', 'R', 'E', 'O', 'M', 'H', 'G', 'J', 'T', 'D', 'N', 'S'];
+
+/// Detect the scheme of a single sahitya token
+/// Returns None when the token is valid in more than one scheme (e.g. "ri", "nā", "----")
+pub fn detect_token_scheme(token: &str) -> Option<SahityaScheme> {
+    if let Some(script) = Script::detect(token) {
+        return Some(SahityaScheme::Native(script));
+    }
+
+    // ASCII is already NFC; skip the allocation for the common case
+    let text: Cow<str> = if token.is_ascii() {
+        Cow::Borrowed(token)
+    } else {
+        Cow::Owned(token.nfc().collect())
+    };
+
+    if text.chars().any(|c| ISO_MARKERS.contains(&c)) {
+        return Some(SahityaScheme::Iso);
+    }
+    if text.chars().any(|c| IAST_MARKERS.contains(&c)) {
+        return Some(SahityaScheme::Iast);
+    }
+
+    // Harvard-Kyoto: ASCII only, with a capital after the first letter of a
+    // word that has lowercase letters; titlecase (`Sri`, `Tyagaraja`) is plain
+    // ASCII
+    let hk_word = |word: &str| {
+        word.chars().any(|c| c.is_ascii_lowercase()) && word.chars().skip(1).any(|c| HK_CAPITALS.contains(&c))
+    };
+    if text.is_ascii() && text.split(|c: char| !c.is_ascii_alphabetic()).any(hk_word) {
+        return Some(SahityaScheme::HarvardKyoto);
+    }
+
+    None
+}
+
+/// Detect the dominant scheme across all sahitya in a document
+/// Documents without any distinguishing tokens are ISO if they use diacritics, ASCII otherwise
+pub fn detect_document_scheme(document: &VnaDocument) -> SahityaScheme {
+    let mut counts: HashMap<SahityaScheme, usize> = HashMap::new();
+    let mut has_diacritics = false;
+
+    for token in sahitya_tokens(document) {
+        if let Some(scheme) = detect_token_scheme(token) {
+            *counts.entry(scheme).or_insert(0) += 1;
+        }
+        if !token.is_ascii() {
+            has_diacritics = true;
+        }
+    }
+
+    // Most frequent wins; ties prefer ISO, then go by name so the result is deterministic
+    let mut detected: Vec<(SahityaScheme, usize)> = counts.into_iter().collect();
+    detected.sort_by_key(|(scheme, count)| {
+        (Reverse(*count), *scheme != SahityaScheme::Iso, scheme.name())
+    });
+
+    match detected.first() {
+        Some((scheme, _)) => *scheme,
+        None if has_diacritics => SahityaScheme::Iso,
+        None => SahityaScheme::Ascii,
+    }
+}
+
+/// Whether the text is already in Unicode NFC form
+pub fn is_normalized(text: &str) -> bool {
+    is_nfc(text)
+}
+
+/// Convert romanised or native-script text to NFC ISO 15919
+pub fn to_iso(text: &str, scheme: SahityaScheme) -> Result<String> {
+    let text: String = text.nfc().collect();
+    let iso = match scheme {
+        SahityaScheme::Iso | SahityaScheme::Ascii => text,
+        SahityaScheme::Iast => transliterator()
+            .transliterate(&text, "iast", "iso")
+            .map_err(|e| anyhow!("Failed to transliterate '{}' from iast: {}", text, e))?,
+        SahityaScheme::HarvardKyoto => harvard_kyoto_to_iso(&text),
+        SahityaScheme::Native(native) => script::to_iso(&text, native)?,
+    };
+    Ok(iso.nfc().collect())
+}
+
+/// Convert NFC ISO 15919 text to the target scheme or script
+pub fn from_iso(text: &str, scheme: SahityaScheme) -> Result<String> {
+    let converted = match scheme {
+        SahityaScheme::Iso => text.to_string(),
+        SahityaScheme::Iast => {
+            if let Some(letter) = text.chars().find(|c| DRAVIDIAN_LETTERS.contains(c)) {
+                return Err(anyhow!("'{}' has no IAST equivalent", letter));
+            }
+            transliterator()
+                .transliterate(text, "iso", "iast")
+                .map_err(|e| anyhow!("Failed to transliterate '{}' to iast: {}", text, e))?
+        }
+        SahityaScheme::HarvardKyoto => iso_to_harvard_kyoto(text)?,
+        SahityaScheme::Native(native) => script::from_iso(text, native)?,
+        SahityaScheme::Ascii => {
+            return Err(anyhow!("Cannot convert to ascii: diacritics would be lost"));
+        }
+    };
+    Ok(converted.nfc().collect())
+}
+
+/// Transliterate a sahitya token, leaving dashes, backticks, word breaks and joins in place
+pub fn transliterate_token(token: &str, from: SahityaScheme, to: SahityaScheme) -> Result<String> {
+    let mut result = String::new();
+    let mut segment = String::new();
+
+    for ch in token.chars() {
+        if matches!(ch, '-' | '`' | crate::lyrics::WORD_BREAK | crate::lyrics::WORD_JOIN) {
+            if !segment.is_empty() {
+                result.push_str(&from_iso(&to_iso(&segment, from)?, to)?);
+                segment.clear();
+            }
+            result.push(ch);
+        } else {
+            segment.push(ch);
+        }
+    }
+
+    if !segment.is_empty() {
+        result.push_str(&from_iso(&to_iso(&segment, from)?, to)?);
+    }
+
+    Ok(result)
+}
+
+/// Rewrite every sahitya token in the document to the target scheme
+/// Each token is read in its own detected scheme, falling back to the document's dominant one
+/// Returns the number of tokens that changed
+pub fn convert_document(document: &mut VnaDocument, target: SahityaScheme) -> Result<usize> {
+    let document_scheme = detect_document_scheme(document);
+    let mut changed = 0;
+
+    for section in &mut document.sections {
+        for phrase in &mut section.phrases {
+            // Further verses are written like the main sahitya; script and meaning lines keep their own
+            let verses = phrase.sahitya_lines.iter_mut().filter(|l| l.verse().is_some());
+            let tokens = phrase.sahitya.iter_mut().chain(verses.flat_map(|l| l.syllables.iter_mut()));
+            for token in tokens {
+                let converted = repeat::map_tokens(token, |token| {
+                    let from = detect_token_scheme(token).unwrap_or(document_scheme);
+                    transliterate_token(token, from, target)
+                })?;
+                if converted != *token {
+                    *token = converted;
+                    changed += 1;
+                }
+            }
+        }
+    }
+
+    Ok(changed)
+}
+
+fn sahitya_tokens(document: &VnaDocument) -> impl Iterator<Item = &String> {
+    document
+        .sections
+        .iter()
+        .flat_map(|s| s.phrases.iter())
+        .flat_map(|p| {
+            let verses = p.sahitya_lines.iter().filter(|l| l.verse().is_some());
+            p.sahitya.iter().chain(verses.flat_map(|l| l.syllables.iter()))
+        })
+}
+
+/// Harvard-Kyoto to ISO 15919, longest match first
+/// `E`/`O` are read as long ē/ō, the common convention for Dravidian texts
+fn harvard_kyoto_to_iso(text: &str) -> String {
+    const TABLE: &[(&str, &str)] = &[
+        ("lRR", "l̥̄"),
+        ("lR", "l̥"),
+        ("RR", "r̥̄"),
+        ("R", "r̥"),
+        ("A", "ā"),
+        ("I", "ī"),
+        ("U", "ū"),
+        ("E", "ē"),
+        ("O", "ō"),
+        ("M", "ṁ"),
+        ("H", "ḥ"),
+        ("G", "ṅ"),
+        ("J", "ñ"),
+        ("T", "ṭ"),
+        ("D", "ḍ"),
+        ("N", "ṇ"),
+        ("z", "ś"),
+        ("S", "ṣ"),
+    ];
+
+    let mut result = String::new();
+    let mut rest = text;
+    'outer: while !rest.is_empty() {
+        for (hk, iso) in TABLE {
+            if let Some(tail) = rest.strip_prefix(hk) {
+                result.push_str(iso);
+                rest = tail;
+                continue 'outer;
+            }
+        }
+        let ch = rest.chars().next().unwrap();
+        result.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+    result
+}
+
+/// ISO 15919 to Harvard-Kyoto
+fn iso_to_harvard_kyoto(text: &str) -> Result<String> {
+    // Decompose so vocalic r/l (base + ring below) can be matched uniformly
+    let decomposed: Vec<char> = text.nfd().collect();
+    let mut result = String::new();
+    let mut i = 0;
+
+    while i < decomposed.len() {
+        let ch = decomposed[i];
+        let mut marks = Vec::new();
+        while i + 1 < decomposed.len() && is_combining_mark(decomposed[i + 1]) {
+            marks.push(decomposed[i + 1]);
+            i += 1;
+        }
+        i += 1;
+
+        let hk = match (ch, marks.as_slice()) {
+            ('r', ['\u{325}', '\u{304}']) => "RR",
+            ('r', ['\u{325}']) => "R",
+            ('l', ['\u{325}', '\u{304}']) => "lRR",
+            ('l', ['\u{325}']) => "lR",
+            ('a', ['\u{304}']) => "A",
+            ('i', ['\u{304}']) => "I",
+            ('u', ['\u{304}']) => "U",
+            ('e', ['\u{304}']) => "E",
+            ('o', ['\u{304}']) => "O",
+            ('m', ['\u{307}']) | ('m', ['\u{323}']) => "M",
+            ('h', ['\u{323}']) => "H",
+            ('n', ['\u{307}']) => "G",
+            ('n', ['\u{303}']) => "J",
+            ('t', ['\u{323}']) => "T",
+            ('d', ['\u{323}']) => "D",
+            ('n', ['\u{323}']) => "N",
+            ('s', ['\u{301}']) => "z",
+            ('s', ['\u{323}']) => "S",
+            (c, []) => {
+                result.push(c);
+                continue;
+            }
+            (c, _) => {
+                let original: String = std::iter::once(c).chain(marks).nfc().collect();
+                return Err(anyhow!(
+                    "'{}' has no Harvard-Kyoto equivalent",
+                    original
+                ));
+            }
+        };
+        result.push_str(hk);
+    }
+
+    Ok(result)
+}
+
+fn is_combining_mark(ch: char) -> bool {
+    matches!(ch, '\u{300}'..='\u{36f}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_detect_token_scheme() {
+        assert_eq!(detect_token_scheme("kō---"), Some(SahityaScheme::Iso));
+        assert_eq!(detect_token_scheme("kṟu"), Some(SahityaScheme::Iso));
+        assert_eq!(detect_token_scheme("kṛ`ṣṇa"), Some(SahityaScheme::Iast));
+        assert_eq!(detect_token_scheme("kO---"), Some(SahityaScheme::HarvardKyoto));
+        assert_eq!(detect_token_scheme("SrI-"), Some(SahityaScheme::HarvardKyoto));
+        // Titlecase ASCII is not Harvard-Kyoto
+        assert_eq!(detect_token_scheme("Sri-"), None);
+        assert_eq!(detect_token_scheme("Tyagaraja"), None);
+        assert_eq!(detect_token_scheme("కో---"), Some(SahityaScheme::Native(Script::Telugu)));
+        // Decomposed ō (o + combining macron) is still ISO
+        assert_eq!(detect_token_scheme("ko\u{304}"), Some(SahityaScheme::Iso));
+        // Ambiguous tokens
+        assert_eq!(detect_token_scheme("ri--"), None);
+        assert_eq!(detect_token_scheme("nā"), None);
+        assert_eq!(detect_token_scheme("----"), None);
+    }
+
+    #[test]
+    fn test_detect_document_scheme() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+G,G, R,,, ||
+nin- kO-- ||
+"#;
+        let doc = parse(content).unwrap();
+        assert_eq!(detect_document_scheme(&doc), SahityaScheme::HarvardKyoto);
+
+        let content = content.replace("kO--", "ko--");
+        let doc = parse(&content).unwrap();
+        assert_eq!(detect_document_scheme(&doc), SahityaScheme::Ascii);
+    }
+
+    #[test]
+    fn test_transliterate_token() {
+        assert_eq!(
+            transliterate_token("`nin`-nu-", SahityaScheme::Iso, SahityaScheme::Iso).unwrap(),
+            "`nin`-nu-"
+        );
+        assert_eq!(
+            transliterate_token("kO---", SahityaScheme::HarvardKyoto, SahityaScheme::Iso).unwrap(),
+            "kō---"
+        );
+        assert_eq!(
+            transliterate_token("kō`ri", SahityaScheme::Iso, SahityaScheme::HarvardKyoto).unwrap(),
+            "kO`ri"
+        );
+        assert_eq!(
+            transliterate_token("śrī", SahityaScheme::Iso, SahityaScheme::Iast).unwrap(),
+            "śrī"
+        );
+        // Decomposed input comes out precomposed
+        assert_eq!(
+            transliterate_token("ko\u{304}-", SahityaScheme::Iso, SahityaScheme::Iso).unwrap(),
+            "kō-"
+        );
+        assert!(transliterate_token("kṟu", SahityaScheme::Iso, SahityaScheme::HarvardKyoto).is_err());
+        assert!(transliterate_token("kṟu", SahityaScheme::Iso, SahityaScheme::Iast).is_err());
+        assert_eq!(
+            transliterate_token("kṟu-", SahityaScheme::Iso, SahityaScheme::Native(Script::Telugu)).unwrap(),
+            "క్ఱు-"
+        );
+        assert!(transliterate_token("ṉi", SahityaScheme::Iso, SahityaScheme::Native(Script::Telugu)).is_err());
+        assert_eq!(
+            transliterate_token("`nin`-nu-", SahityaScheme::Iso, SahityaScheme::Native(Script::Telugu)).unwrap(),
+            "`నిన్`-ను-"
+        );
+    }
+
+    #[test]
+    fn test_convert_document() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+G,G, R,,, | S,,, ||
+nin- kO-- | kō-- ||
+"#;
+        let mut doc = parse(content).unwrap();
+        let changed = convert_document(&mut doc, SahityaScheme::Iso).unwrap();
+        assert_eq!(changed, 1);
+        assert_eq!(doc.sections[0].phrases[0].sahitya, vec!["nin-", "kō--", "kō--"]);
+    }
+}
@@ -0,0 +1,434 @@
+use anyhow::{anyhow, Result};
+use shlesha::Shlesha;
+use std::sync::OnceLock;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Native Indic scripts accepted in sahitya lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Script {
+    Devanagari,
+    Telugu,
+    Tamil,
+    Kannada,
+    Malayalam,
+}
+
+const ALL_SCRIPTS: [Script; 5] = [
+    Script::Devanagari,
+    Script::Telugu,
+    Script::Tamil,
+    Script::Kannada,
+    Script::Malayalam,
+];
+
+// Offsets within a script block; all five blocks share the ISCII layout
+const CONSONANT_FIRST: u32 = 0x15;
+const CONSONANT_LAST: u32 = 0x39;
+const VIRAMA: u32 = 0x4d;
+
+/// Vowel signs by offset, with the vowel they write in ISO 15919; the virama
+/// writes none
+const VOWEL_SIGNS: [(u32, &str); 14] = [
+    (0x3e, "ā"),
+    (0x3f, "i"),
+    (0x40, "ī"),
+    (0x41, "u"),
+    (0x42, "ū"),
+    (0x43, "r̥"),
+    (0x44, "r̥̄"),
+    (0x46, "e"),
+    (0x47, "ē"),
+    (0x48, "ai"),
+    (0x4a, "o"),
+    (0x4b, "ō"),
+    (0x4c, "au"),
+    (VIRAMA, ""),
+];
+
+/// Independent vowels by offset, with the offset of their vowel sign (none for a)
+const INDEPENDENT_VOWELS: [(u32, Option<u32>); 14] = [
+    (0x05, None),
+    (0x06, Some(0x3e)),
+    (0x07, Some(0x3f)),
+    (0x08, Some(0x40)),
+    (0x09, Some(0x41)),
+    (0x0a, Some(0x42)),
+    (0x0b, Some(0x43)),
+    (0x60, Some(0x44)),
+    (0x0e, Some(0x46)),
+    (0x0f, Some(0x47)),
+    (0x10, Some(0x48)),
+    (0x12, Some(0x4a)),
+    (0x13, Some(0x4b)),
+    (0x14, Some(0x4c)),
+];
+
+/// Tamil long ē and ō, which shlesha writes as placeholders going to Tamil
+/// and leaves after an inherent a coming from it
+const TAMIL_LONG_VOWELS: [(&str, &str, &str); 4] = [
+    ("[VowelSignEe]", "aே", "ē"),
+    ("[VowelSignOo]", "aோ", "ō"),
+    ("[VowelEe]", "ஏ", "ē"),
+    ("[VowelOo]", "ஓ", "ō"),
+];
+
+impl Script {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Script::Devanagari => "devanagari",
+            Script::Telugu => "telugu",
+            Script::Tamil => "tamil",
+            Script::Kannada => "kannada",
+            Script::Malayalam => "malayalam",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "devanagari" | "deva" => Some(Script::Devanagari),
+            "telugu" => Some(Script::Telugu),
+            "tamil" => Some(Script::Tamil),
+            "kannada" => Some(Script::Kannada),
+            "malayalam" => Some(Script::Malayalam),
+            _ => None,
+        }
+    }
+
+    /// Script conventionally used to write a sahitya language, by name or ISO 639-1 code
+    pub fn for_language(language: &str) -> Option<Self> {
+        match language.trim().to_lowercase().as_str() {
+            "telugu" | "te" => Some(Script::Telugu),
+            "tamil" | "ta" => Some(Script::Tamil),
+            "kannada" | "kn" => Some(Script::Kannada),
+            "malayalam" | "ml" => Some(Script::Malayalam),
+            "sanskrit" | "hindi" | "marathi" | "sa" | "hi" | "mr" => Some(Script::Devanagari),
+            _ => None,
+        }
+    }
+
+    /// Detect the script of the first native character in the text
+    pub fn detect(text: &str) -> Option<Self> {
+        text.chars().find_map(|ch| {
+            ALL_SCRIPTS
+                .iter()
+                .copied()
+                .find(|script| script.contains(ch))
+        })
+    }
+
+    fn block_start(&self) -> u32 {
+        match self {
+            Script::Devanagari => 0x0900,
+            Script::Tamil => 0x0b80,
+            Script::Telugu => 0x0c00,
+            Script::Kannada => 0x0c80,
+            Script::Malayalam => 0x0d00,
+        }
+    }
+
+    fn contains(&self, ch: char) -> bool {
+        let start = self.block_start();
+        (start..start + 0x80).contains(&(ch as u32))
+    }
+
+    fn offset(&self, ch: char) -> Option<u32> {
+        if self.contains(ch) {
+            Some(ch as u32 - self.block_start())
+        } else {
+            None
+        }
+    }
+
+    fn is_consonant(&self, ch: char) -> bool {
+        matches!(self.offset(ch), Some(o) if (CONSONANT_FIRST..=CONSONANT_LAST).contains(&o))
+            || self.dravidian_consonants().iter().any(|(native, _)| *native == ch)
+    }
+
+    /// Consonants of the Dravidian languages (ṟ, ṉ, ḻ, ṯ) with their ISO 15919
+    /// letter; shlesha leaves these untransliterated, so they're converted here
+    fn dravidian_consonants(&self) -> &'static [(char, char)] {
+        match self {
+            Script::Devanagari => &[('ऱ', 'ṟ'), ('ऩ', 'ṉ'), ('ऴ', 'ḻ')],
+            Script::Tamil => &[('ற', 'ṟ'), ('ன', 'ṉ'), ('ழ', 'ḻ')],
+            Script::Telugu => &[('ఱ', 'ṟ'), ('ఴ', 'ḻ')],
+            Script::Kannada => &[('ಱ', 'ṟ'), ('ೞ', 'ḻ')],
+            Script::Malayalam => &[('റ', 'ṟ'), ('ഩ', 'ṉ'), ('ഴ', 'ḻ'), ('ഺ', 'ṯ')],
+        }
+    }
+
+    fn character(&self, offset: u32) -> char {
+        char::from_u32(self.block_start() + offset).unwrap_or_default()
+    }
+
+    fn is_virama(&self, ch: char) -> bool {
+        self.offset(ch) == Some(VIRAMA)
+    }
+
+    /// Whether a virama joins the following consonant into a conjunct
+    /// Tamil writes a visible pulli instead
+    fn forms_conjuncts(&self) -> bool {
+        !matches!(self, Script::Tamil)
+    }
+
+    /// Shlesha lists Kannada and Malayalam but has no converters for them;
+    /// both go through Telugu, whose block has the same layout
+    fn shlesha_name(&self) -> &'static str {
+        match self {
+            Script::Devanagari => "devanagari",
+            Script::Tamil => "tamil",
+            Script::Telugu | Script::Kannada | Script::Malayalam => "telugu",
+        }
+    }
+}
+
+/// Shared transliterator; constructing a Shlesha is ~100x the cost of a transliteration
+pub(crate) fn transliterator() -> &'static Shlesha {
+    static TRANSLITERATOR: OnceLock<Shlesha> = OnceLock::new();
+    TRANSLITERATOR.get_or_init(Shlesha::new)
+}
+
+/// Transliterate native-script text to ISO 15919
+pub fn to_iso(text: &str, script: Script) -> Result<String> {
+    let source = dravidian_to_iso(text, script);
+    let source = match script {
+        Script::Kannada | Script::Malayalam => shift_block(&source, script, Script::Telugu),
+        _ => source,
+    };
+
+    let mut iso = transliterator()
+        .transliterate(&source, script.shlesha_name(), "iso")
+        .map_err(|e| anyhow!("Failed to transliterate '{}' from {}: {}", text, script.name(), e))?;
+    if script == Script::Tamil {
+        for (_, tamil, vowel) in TAMIL_LONG_VOWELS {
+            iso = iso.replace(tamil, vowel);
+        }
+    }
+    Ok(iso)
+}
+
+/// Transliterate ISO 15919 text to a native script
+/// Fails on letters the script can't write, such as ṉ in Telugu
+pub fn from_iso(text: &str, script: Script) -> Result<String> {
+    let mut native = transliterator()
+        .transliterate(text, "iso", script.shlesha_name())
+        .map_err(|e| anyhow!("Failed to transliterate '{}' to {}: {}", text, script.name(), e))?;
+    match script {
+        Script::Kannada | Script::Malayalam => native = shift_block(&native, Script::Telugu, script),
+        Script::Tamil => {
+            for (placeholder, tamil, _) in TAMIL_LONG_VOWELS {
+                native = native.replace(placeholder, tamil.trim_start_matches('a'));
+            }
+        }
+        _ => {}
+    }
+    let native = dravidian_from_iso(&native, script);
+
+    match native.chars().find(|c| c.is_alphabetic() && !script.contains(*c)) {
+        Some(letter) => Err(anyhow!("'{}' has no {} equivalent in '{}'", letter, script.name(), text)),
+        None => Ok(native),
+    }
+}
+
+/// Write the script's Dravidian consonants in ISO 15919, with their vowel
+/// sign (or inherent a) and leaving the rest to shlesha: కఱ్ఱు → కṟṟu
+fn dravidian_to_iso(text: &str, script: Script) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        let Some(&(_, iso)) = script.dravidian_consonants().iter().find(|(native, _)| *native == ch) else {
+            result.push(ch);
+            continue;
+        };
+        result.push(iso);
+
+        let sign = chars
+            .peek()
+            .and_then(|&next| script.offset(next))
+            .and_then(|offset| VOWEL_SIGNS.iter().find(|(sign, _)| *sign == offset));
+        match sign {
+            Some((_, vowel)) => {
+                chars.next();
+                result.push_str(vowel);
+            }
+            None => result.push('a'),
+        }
+    }
+
+    result
+}
+
+/// Write the Dravidian consonants shlesha left in ISO 15919 natively, taking
+/// the independent vowel it wrote after one as its vowel sign: కṟṟఉ → కఱ్ఱు
+fn dravidian_from_iso(text: &str, script: Script) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        let Some(&(native, _)) = script.dravidian_consonants().iter().find(|(_, iso)| *iso == ch) else {
+            result.push(ch);
+            continue;
+        };
+        result.push(native);
+
+        let vowel = chars
+            .peek()
+            .and_then(|&next| script.offset(next))
+            .and_then(|offset| INDEPENDENT_VOWELS.iter().find(|(vowel, _)| *vowel == offset));
+        match vowel {
+            Some((_, sign)) => {
+                chars.next();
+                if let Some(sign) = sign {
+                    result.push(script.character(*sign));
+                }
+            }
+            None => result.push(script.character(VIRAMA)),
+        }
+    }
+
+    result
+}
+
+/// Split native-script text into aksharas (syllable units)
+/// Conjuncts stay together, and a dead consonant (consonant + virama, or a
+/// Malayalam chillu) that doesn't start a conjunct closes the preceding
+/// syllable: నిన్ → ["నిన్"], நின்னு → ["நின்", "னு"], അവൻ → ["അ", "വൻ"]
+pub fn aksharas(text: &str, script: Script) -> Vec<String> {
+    let clusters = conjunct_clusters(text, script);
+    let mut units: Vec<String> = Vec::new();
+
+    for cluster in clusters {
+        let is_dead_consonant = cluster
+            .chars()
+            .last()
+            .is_some_and(|c| script.is_virama(c) || chillu_base(c).is_some());
+        match units.last_mut() {
+            Some(previous) if is_dead_consonant => previous.push_str(&cluster),
+            _ => units.push(cluster),
+        }
+    }
+
+    units
+}
+
+/// Split native-script text into grapheme clusters, joining consonants across
+/// a virama where the script forms conjuncts
+/// Unicode only joins these for some scripts (not Kannada), so it's done explicitly
+pub fn conjunct_clusters(text: &str, script: Script) -> Vec<String> {
+    let mut clusters: Vec<String> = Vec::new();
+
+    for grapheme in text.graphemes(true) {
+        let joins_previous = script.forms_conjuncts()
+            && grapheme.chars().next().is_some_and(|c| script.is_consonant(c))
+            && clusters
+                .last()
+                .and_then(|prev| prev.chars().last())
+                .is_some_and(|c| script.is_virama(c));
+
+        match clusters.last_mut() {
+            Some(previous) if joins_previous => previous.push_str(grapheme),
+            _ => clusters.push(grapheme.to_string()),
+        }
+    }
+
+    clusters
+}
+
+/// Move characters between two script blocks, which share the ISCII layout
+fn shift_block(text: &str, from: Script, to: Script) -> String {
+    let mut result = String::new();
+
+    for ch in text.chars() {
+        // Malayalam chillu letters are atomic forms of consonant + virama
+        if from == Script::Malayalam {
+            if let Some(base) = chillu_base(ch) {
+                result.push(shift_char(base, from, to));
+                result.push(shift_char(char::from_u32(0x0d4d).unwrap(), from, to));
+                continue;
+            }
+        }
+        result.push(shift_char(ch, from, to));
+    }
+
+    result
+}
+
+fn shift_char(ch: char, from: Script, to: Script) -> char {
+    match from.offset(ch) {
+        Some(offset) => char::from_u32(to.block_start() + offset).unwrap_or(ch),
+        None => ch,
+    }
+}
+
+fn chillu_base(ch: char) -> Option<char> {
+    match ch {
+        'ൺ' => Some('ണ'),
+        'ൻ' => Some('ന'),
+        'ർ' => Some('ര'),
+        'ൽ' => Some('ല'),
+        'ൾ' => Some('ള'),
+        'ൿ' => Some('ക'),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Script::detect("నిన్నుకోరి"), Some(Script::Telugu));
+        assert_eq!(Script::detect("-நின்னு"), Some(Script::Tamil));
+        assert_eq!(Script::detect("ನಿನ್ನು"), Some(Script::Kannada));
+        assert_eq!(Script::detect("നിന്നു"), Some(Script::Malayalam));
+        assert_eq!(Script::detect("सा"), Some(Script::Devanagari));
+        assert_eq!(Script::detect("ninnu"), None);
+    }
+
+    #[test]
+    fn test_aksharas() {
+        assert_eq!(aksharas("నిన్నుకోరి", Script::Telugu), vec!["ని", "న్ను", "కో", "రి"]);
+        assert_eq!(aksharas("నిన్", Script::Telugu), vec!["నిన్"]);
+        assert_eq!(aksharas("நின்னு", Script::Tamil), vec!["நின்", "னு"]);
+        assert_eq!(aksharas("ನಿನ್ನು", Script::Kannada), vec!["ನಿ", "ನ್ನು"]);
+        assert_eq!(aksharas("നിന്നു", Script::Malayalam), vec!["നി", "ന്നു"]);
+        assert_eq!(aksharas("संगीत", Script::Devanagari), vec!["सं", "गी", "त"]);
+        // Chillu letters close the syllable like a dead consonant
+        assert_eq!(aksharas("അവൻ", Script::Malayalam), vec!["അ", "വൻ"]);
+        assert_eq!(aksharas("ಕೞ್ಕು", Script::Kannada), vec!["ಕ", "ೞ್ಕು"]);
+    }
+
+    #[test]
+    fn test_kannada_malayalam_roundtrip() {
+        assert_eq!(from_iso("ninnukōri", Script::Kannada).unwrap(), "ನಿನ್ನುಕೋರಿ");
+        assert_eq!(to_iso("ನಿನ್ನುಕೋರಿ", Script::Kannada).unwrap(), "ninnukōri");
+        assert_eq!(to_iso("നിന്നു", Script::Malayalam).unwrap(), "ninnu");
+        // Letters outside the Telugu block survive the trip
+        assert_eq!(to_iso("ഴ", Script::Malayalam).unwrap(), "ḻa");
+        assert_eq!(from_iso("ḻa", Script::Malayalam).unwrap(), "ഴ");
+        assert_eq!(from_iso("ṯa", Script::Malayalam).unwrap(), "ഺ");
+        assert_eq!(to_iso("ಕೞು", Script::Kannada).unwrap(), "kaḻu");
+        assert_eq!(from_iso("kaḻu", Script::Kannada).unwrap(), "ಕೞು");
+    }
+
+    #[test]
+    fn test_dravidian_consonants() {
+        assert_eq!(from_iso("nāmīdakṟupa", Script::Telugu).unwrap(), "నామీదక్ఱుప");
+        assert_eq!(to_iso("కఱ్ఱు", Script::Telugu).unwrap(), "kaṟṟu");
+        assert_eq!(from_iso("kaṟṟu", Script::Telugu).unwrap(), "కఱ్ఱు");
+        assert_eq!(to_iso("னு", Script::Tamil).unwrap(), "ṉu");
+        assert_eq!(from_iso("ṉṟa", Script::Tamil).unwrap(), "ன்ற");
+        assert_eq!(from_iso("ṉu", Script::Devanagari).unwrap(), "ऩु");
+        // Telugu has no letter for ṉ
+        assert!(from_iso("śrīṉivāsa", Script::Telugu).is_err());
+    }
+
+    #[test]
+    fn test_tamil_long_vowels() {
+        assert_eq!(from_iso("kōrē", Script::Tamil).unwrap(), "கோரே");
+        assert_eq!(from_iso("ōṭu", Script::Tamil).unwrap(), "ஓடு");
+        assert_eq!(to_iso("கோரே", Script::Tamil).unwrap(), "kōrē");
+        assert_eq!(to_iso("ஏ", Script::Tamil).unwrap(), "ē");
+    }
+}
@@ -0,0 +1,230 @@
+use crate::types::{Gamaka, Slide};
+use anyhow::{anyhow, Result};
+
+/// Split a token-level gati override from a swara token: "SRG:3" → ("SRG", Some("3"))
+pub fn split_token_gati(token: &str) -> (&str, Option<&str>) {
+    match token.split_once(':') {
+        Some((text, gati)) => (text, Some(gati)),
+        None => (token, None),
+    }
+}
+
+/// A unit of a swara token with the gamakas written on it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwaraNote {
+    /// The unit without gamaka markers: "R2", "S'", ","
+    pub swara: String,
+    /// The unit as written: "{S}R2~"
+    pub written: String,
+    pub gamakas: Vec<Gamaka>,
+}
+
+/// Parse swara token into individual units
+/// Each note counts as one unit, including octave markers; gamaka markers
+/// don't count
+/// Examples:
+///   "SRG," → ["S", "R", "G", ","]
+///   "R2G3-" → ["R2", "G3", "-"]
+///   "D.S'" → ["D.", "S'"]
+///   "{R}G~P/" → ["G", "P"]
+pub fn parse_swara_units(token: &str) -> Vec<String> {
+    scan(token).0.into_iter().map(|n| n.swara).collect()
+}
+
+/// Units as written, each note with its gamaka markers, so they can be
+/// regrouped into new tokens without losing them
+///   "{R}G~P," → ["{R}G~", "P", ","]
+pub fn parse_written_units(token: &str) -> Vec<String> {
+    scan(token).0.into_iter().map(|n| n.written).collect()
+}
+
+/// Every unit of a swara token with the gamakas written on it, ignoring
+/// malformed markers (which validation reports)
+pub fn swara_notes(token: &str) -> Vec<SwaraNote> {
+    scan(token).0
+}
+
+/// Every unit of a swara token with its gamakas
+/// Kampita is written `~` after the note, a jaru `/` (up) or `\` (down)
+/// after the note it slides from, and an anuswaram `{R}` before the note
+pub fn parse_swara_notes(token: &str) -> Result<Vec<SwaraNote>> {
+    match scan(token) {
+        (notes, None) => Ok(notes),
+        (_, Some(problem)) => Err(anyhow!("Invalid gamaka in token '{}': {}", token, problem)),
+    }
+}
+
+/// Move every note of a swara token, anuswarams included, by whole octaves,
+/// keeping everything else as written: `shift_octave("{S'}N.S,", 1)` is
+/// `"{S''}NS',"`
+pub fn shift_octave(token: &str, octaves: i8) -> String {
+    let mut shifted = String::new();
+    let mut chars = token.chars().peekable();
+    while let Some(ch) = chars.next() {
+        shifted.push(ch);
+        if !is_swara_letter(ch) {
+            continue;
+        }
+        if let Some(&variant) = chars.peek().filter(|c| ['1', '2', '3'].contains(c)) {
+            shifted.push(variant);
+            chars.next();
+        }
+        let mut octave = octaves;
+        while let Some(&mark) = chars.peek().filter(|c| **c == '.' || **c == '\'') {
+            octave += if mark == '.' { -1 } else { 1 };
+            chars.next();
+        }
+        let mark = if octave < 0 { "." } else { "'" };
+        shifted.push_str(&mark.repeat(octave.unsigned_abs() as usize));
+    }
+    shifted
+}
+
+/// Split a token into units, noting the first malformed gamaka
+fn scan(token: &str) -> (Vec<SwaraNote>, Option<String>) {
+    let mut notes: Vec<SwaraNote> = Vec::new();
+    let mut problem = None;
+    let mut grace: Option<String> = None;
+    let mut chars = token.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch == ',' || ch == '-' {
+            // Comma is a sustain marker, dash a rest marker, each one unit
+            if let Some(note) = grace.take() {
+                problem.get_or_insert(format!("anuswaram '{{{}}}' must come before a note", note));
+            }
+            notes.push(SwaraNote { swara: ch.to_string(), written: ch.to_string(), gamakas: Vec::new() });
+        } else if ch == '{' {
+            // Grace note for the note after it
+            let mut inner = String::new();
+            let mut closed = false;
+            for next_ch in chars.by_ref() {
+                if next_ch == '}' {
+                    closed = true;
+                    break;
+                }
+                inner.push(next_ch);
+            }
+            if !closed {
+                problem.get_or_insert("unclosed '{'".to_string());
+            } else if !is_single_note(&inner) {
+                problem.get_or_insert(format!("'{{{}}}' must hold one swara", inner));
+            } else if grace.is_some() {
+                problem.get_or_insert("a note can have only one anuswaram".to_string());
+            }
+            grace = Some(inner);
+        } else if let Some(gamaka) = marker_gamaka(ch) {
+            match notes.last_mut().filter(|n| is_note(&n.swara)) {
+                Some(note) => {
+                    if note.gamakas.iter().any(|g| g.name() == gamaka.name()) {
+                        problem.get_or_insert(format!("'{}' has more than one {}", note.swara, gamaka.name()));
+                    }
+                    note.written.push(ch);
+                    note.gamakas.push(gamaka);
+                }
+                None => {
+                    problem.get_or_insert(format!("'{}' must follow a note", ch));
+                }
+            }
+        } else if is_swara_letter(ch) {
+            // Swara note
+            let mut note = String::from(ch);
+
+            // Check for variant (1, 2, 3)
+            if let Some(&next_ch) = chars.peek() {
+                if ['1', '2', '3'].contains(&next_ch) {
+                    note.push(chars.next().unwrap());
+                }
+            }
+
+            // Check for octave markers (., ')
+            while let Some(&next_ch) = chars.peek() {
+                if next_ch == '.' || next_ch == '\'' {
+                    note.push(chars.next().unwrap());
+                } else {
+                    break;
+                }
+            }
+
+            let (written, gamakas) = match grace.take() {
+                Some(grace) => (format!("{{{}}}{}", grace, note), vec![Gamaka::Anuswaram(grace)]),
+                None => (note.clone(), Vec::new()),
+            };
+            notes.push(SwaraNote { swara: note, written, gamakas });
+        }
+    }
+
+    if let Some(note) = grace {
+        problem.get_or_insert(format!("anuswaram '{{{}}}' must come before a note", note));
+    }
+    (notes, problem)
+}
+
+fn marker_gamaka(ch: char) -> Option<Gamaka> {
+    match ch {
+        '~' => Some(Gamaka::Kampita),
+        '/' => Some(Gamaka::Jaru(Slide::Up)),
+        '\\' => Some(Gamaka::Jaru(Slide::Down)),
+        _ => None,
+    }
+}
+
+fn is_single_note(text: &str) -> bool {
+    let (notes, problem) = scan(text);
+    problem.is_none() && notes.len() == 1 && notes[0].written == text && is_note(text)
+}
+
+/// Whether a swara unit starts a note (as opposed to a sustain or rest),
+/// as parsed or as written with an anuswaram
+pub fn is_note(unit: &str) -> bool {
+    let unit = unit.strip_prefix('{').and_then(|u| u.split_once('}')).map_or(unit, |(_, note)| note);
+    unit.chars().next().is_some_and(is_swara_letter)
+}
+
+fn is_swara_letter(ch: char) -> bool {
+    ['S', 'R', 'G', 'M', 'P', 'D', 'N'].contains(&ch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_swara_units() {
+        assert_eq!(parse_swara_units("SRG,"), vec!["S", "R", "G", ","]);
+        assert_eq!(parse_swara_units("R2G3-"), vec!["R2", "G3", "-"]);
+        assert_eq!(parse_swara_units("D.S'R'"), vec!["D.", "S'", "R'"]);
+        assert_eq!(split_token_gati("SRG:3"), ("SRG", Some("3")));
+        assert_eq!(split_token_gati("SRG"), ("SRG", None));
+        assert!(is_note("S'") && !is_note(",") && !is_note("-"));
+        assert!(is_note("{R}G~"));
+    }
+
+    #[test]
+    fn test_parse_gamakas() {
+        // Markers don't count as units
+        assert_eq!(parse_swara_units("{R}G~P/,"), vec!["G", "P", ","]);
+        assert_eq!(parse_written_units("{R}G~P/,"), vec!["{R}G~", "P/", ","]);
+
+        let notes = parse_swara_notes("{S'}N2~D\\,").unwrap();
+        assert_eq!(notes[0].swara, "N2");
+        assert_eq!(notes[0].gamakas, vec![Gamaka::Anuswaram("S'".to_string()), Gamaka::Kampita]);
+        assert_eq!(notes[1].gamakas, vec![Gamaka::Jaru(Slide::Down)]);
+        assert!(notes[2].gamakas.is_empty());
+
+        let error = |token| parse_swara_notes(token).unwrap_err().to_string();
+        assert_eq!(error(",~"), "Invalid gamaka in token ',~': '~' must follow a note");
+        assert_eq!(error("G{R}"), "Invalid gamaka in token 'G{R}': anuswaram '{R}' must come before a note");
+        assert_eq!(error("{RG}S"), "Invalid gamaka in token '{RG}S': '{RG}' must hold one swara");
+        assert_eq!(error("{RS"), "Invalid gamaka in token '{RS': unclosed '{'");
+        assert_eq!(error("G~~"), "Invalid gamaka in token 'G~~': 'G' has more than one kampita");
+    }
+
+    #[test]
+    fn test_shift_octave() {
+        assert_eq!(shift_octave("{S'}N.S,", 1), "{S''}NS',");
+        assert_eq!(shift_octave("(G2~P'D.)x2:3", -1), "(G2.~PD..)x2:3");
+        assert_eq!(shift_octave(shift_octave("S''R.-", -2).as_str(), 2), "S''R.-");
+        assert_eq!(shift_octave("||", 1), "||");
+    }
+}
@@ -0,0 +1,558 @@
+use crate::sahitya_scheme::{detect_token_scheme, to_iso, SahityaScheme};
+use crate::script::{self, aksharas, conjunct_clusters, Script};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+use unicode_normalization::UnicodeNormalization;
+
+/// Name of the project-level exception dictionary, looked up next to a .vna file and in its parent directories
+pub const EXCEPTIONS_FILE_NAME: &str = "vna-syllables.yaml";
+
+/// Splits a word segment (no dashes or backticks) into syllable units
+pub trait Syllabifier: Send + Sync {
+    fn syllabify(&self, segment: &str, scheme: SahityaScheme) -> Vec<String>;
+}
+
+/// Syllabifier for Indic languages
+/// Native-script segments are split into aksharas directly; romanised segments are
+/// normalised to NFC ISO 15919, rendered in the language's script via shlesha,
+/// split there and transliterated back, so syllables come back in ISO
+pub struct IndicSyllabifier {
+    script: Script,
+}
+
+impl IndicSyllabifier {
+    pub fn new(script: Script) -> Self {
+        Self { script }
+    }
+}
+
+impl Syllabifier for IndicSyllabifier {
+    fn syllabify(&self, segment: &str, scheme: SahityaScheme) -> Vec<String> {
+        if segment.is_empty() {
+            return vec![];
+        }
+
+        if let Some(native) = Script::detect(segment) {
+            return aksharas(segment, native);
+        }
+        
+        let segment = match to_iso(segment, scheme) {
+            Ok(iso) => iso,
+            Err(_) => segment.to_string(),
+        };
+        let segment = segment.as_str();
+        
+        // Transliterate to the target script to get proper syllable boundaries
+        match script::from_iso(segment, self.script) {
+            Ok(native_script) => {
+                // Split into clusters (which correspond to syllables in native scripts)
+                let clusters = conjunct_clusters(&native_script, self.script);
+                
+                let mut syllables = Vec::new();
+                
+                // Transliterate each cluster back to ISO
+                for cluster in clusters {
+                    match script::to_iso(&cluster, self.script) {
+                        Ok(syllable) => syllables.push(syllable),
+                        Err(_) => {
+                            // If we can't transliterate back, fall back to simple parsing
+                            return parse_vowel_based(segment);
+                        }
+                    }
+                }
+                
+                syllables
+            }
+            Err(_) => {
+                // Fallback to simple vowel-based parsing if transliteration fails
+                parse_vowel_based(segment)
+            }
+        }
+    }
+}
+
+/// Syllabifier for English lyrics in fusion pieces
+/// Splits around vowel groups (`y` included), keeps a silent final `e` with its
+/// syllable, and divides consonant clusters between syllables: "melody" → ["me", "lo", "dy"]
+pub struct EnglishSyllabifier;
+
+impl Syllabifier for EnglishSyllabifier {
+    fn syllabify(&self, segment: &str, _scheme: SahityaScheme) -> Vec<String> {
+        let chars: Vec<char> = segment.chars().collect();
+        let is_vowel = |c: char| matches!(c.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+
+        // Vowel groups are syllable nuclei: (start, end) exclusive
+        let mut nuclei: Vec<(usize, usize)> = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            // A leading 'y' before a vowel is a consonant ("yes")
+            if is_vowel(chars[i]) && !(chars[i].eq_ignore_ascii_case(&'y') && chars.get(i + 1).is_some_and(|&c| is_vowel(c))) {
+                let start = i;
+                while i < chars.len() && is_vowel(chars[i]) {
+                    i += 1;
+                }
+                nuclei.push((start, i));
+            } else {
+                i += 1;
+            }
+        }
+
+        // A final 'e' after a consonant is silent ("tune"), unless it's the only vowel
+        if nuclei.len() > 1 {
+            let (start, end) = nuclei[nuclei.len() - 1];
+            let silent_e = end == chars.len()
+                && end - start == 1
+                && chars[start].eq_ignore_ascii_case(&'e')
+                && !(start >= 2 && chars[start - 1].eq_ignore_ascii_case(&'l') && !is_vowel(chars[start - 2]));
+            if silent_e {
+                nuclei.pop();
+            }
+        }
+
+        if nuclei.len() <= 1 {
+            return vec![segment.to_string()];
+        }
+
+        // Split between nuclei: V-CV, VC-CV, VC-CCV
+        let mut boundaries = Vec::new();
+        for pair in nuclei.windows(2) {
+            let consonants = pair[1].0 - pair[0].1;
+            let boundary = if consonants <= 1 {
+                pair[0].1
+            } else {
+                pair[0].1 + 1
+            };
+            boundaries.push(boundary);
+        }
+
+        let mut syllables = Vec::new();
+        let mut start = 0;
+        for boundary in boundaries {
+            syllables.push(chars[start..boundary].iter().collect());
+            start = boundary;
+        }
+        syllables.push(chars[start..].iter().collect());
+        syllables
+    }
+}
+
+/// Project-level dictionary of explicit word splits, applied before the automatic syllabifier
+/// The file is a YAML mapping from word to a backtick-separated split:
+/// ```yaml
+/// ninnukori: "nin`nu`kō`ri"
+/// ```
+/// Words are kept in ISO 15919, so an entry matches the word in any scheme or script
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExceptionDictionary {
+    entries: HashMap<String, Vec<String>>,
+}
+
+impl ExceptionDictionary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let raw: HashMap<String, String> = serde_yaml::from_str(content)
+            .map_err(|e| anyhow!("Invalid syllable exception dictionary: {}", e))?;
+
+        let mut dictionary = Self::new();
+        for (word, split) in raw {
+            dictionary.insert(&word, &split)?;
+        }
+        Ok(dictionary)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse(&content)
+    }
+
+    /// Find the dictionary for a .vna file by searching its directory and the parents
+    pub fn find_for(vna_file: &Path) -> Option<PathBuf> {
+        let vna_file = vna_file.canonicalize().unwrap_or_else(|_| vna_file.to_path_buf());
+        vna_file
+            .parent()
+            .into_iter()
+            .flat_map(Path::ancestors)
+            .map(|dir| dir.join(EXCEPTIONS_FILE_NAME))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Add an entry; the split uses backticks between syllables
+    pub fn insert(&mut self, word: &str, split: &str) -> Result<()> {
+        let units: Vec<String> = split
+            .split('`')
+            .filter(|unit| !unit.is_empty())
+            .map(|unit| unit.nfc().collect())
+            .collect();
+
+        if units.is_empty() || units.iter().any(|unit| unit.contains('-')) {
+            return Err(anyhow!("Invalid split '{}' for '{}': expected syllables separated by backticks", split, word));
+        }
+
+        self.entries.insert(Self::key(word, SahityaScheme::Iso), units);
+        Ok(())
+    }
+
+    /// Look up a word, read in its own detected scheme or else the given one
+    pub fn lookup(&self, word: &str, scheme: SahityaScheme) -> Option<&[String]> {
+        self.entries.get(&Self::key(word, scheme)).map(|units| units.as_slice())
+    }
+
+    /// A word in NFC ISO 15919, or just NFC if it can't be transliterated
+    fn key(word: &str, scheme: SahityaScheme) -> String {
+        let scheme = detect_token_scheme(word).unwrap_or(scheme);
+        to_iso(word, scheme).unwrap_or_else(|_| word.nfc().collect())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Segments a `CachedSyllabifier` keeps by default, a few hundred files' worth
+pub const DEFAULT_CACHE_CAPACITY: usize = 10_000;
+
+/// Memoises another syllabifier's output per (segment, scheme)
+/// Safe to share across threads, so one instance can serve every document in a run
+/// The cache is emptied when it reaches its capacity, so a long LSP session
+/// doesn't grow it without bound
+pub struct CachedSyllabifier<S> {
+    inner: S,
+    cache: RwLock<HashMap<SahityaScheme, HashMap<String, Vec<String>>>>,
+    capacity: usize,
+}
+
+impl<S: Syllabifier> CachedSyllabifier<S> {
+    pub fn new(inner: S) -> Self {
+        Self::with_capacity(inner, DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Cache keeping at most `capacity` segments
+    pub fn with_capacity(inner: S, capacity: usize) -> Self {
+        Self {
+            inner,
+            cache: RwLock::new(HashMap::new()),
+            capacity,
+        }
+    }
+
+    /// Number of memoised segments
+    pub fn len(&self) -> usize {
+        self.cache.read().map(|c| c.values().map(HashMap::len).sum()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<S: Syllabifier> Syllabifier for CachedSyllabifier<S> {
+    fn syllabify(&self, segment: &str, scheme: SahityaScheme) -> Vec<String> {
+        if let Ok(cache) = self.cache.read() {
+            if let Some(units) = cache.get(&scheme).and_then(|words| words.get(segment)) {
+                return units.clone();
+            }
+        }
+
+        let units = self.inner.syllabify(segment, scheme);
+        if let Ok(mut cache) = self.cache.write() {
+            if cache.values().map(HashMap::len).sum::<usize>() >= self.capacity {
+                cache.clear();
+            }
+            cache
+                .entry(scheme)
+                .or_default()
+                .insert(segment.to_string(), units.clone());
+        }
+        units
+    }
+}
+
+/// Languages with a built-in syllabifier
+const BUILTIN_LANGUAGES: [&str; 8] = [
+    "telugu", "tamil", "kannada", "malayalam", "sanskrit", "hindi", "marathi", "english",
+];
+
+/// Process-wide cached instances of the built-in syllabifiers
+/// Every default registry points at these, so the cache is shared across documents
+fn builtin(language: &str) -> Arc<dyn Syllabifier> {
+    static BUILTINS: OnceLock<HashMap<&'static str, Arc<dyn Syllabifier>>> = OnceLock::new();
+    let builtins = BUILTINS.get_or_init(|| {
+        let mut by_script: HashMap<Script, Arc<dyn Syllabifier>> = HashMap::new();
+        let mut builtins: HashMap<&'static str, Arc<dyn Syllabifier>> = HashMap::new();
+        for language in BUILTIN_LANGUAGES {
+            let syllabifier = match Script::for_language(language) {
+                Some(script) => by_script
+                    .entry(script)
+                    .or_insert_with(|| Arc::new(CachedSyllabifier::new(IndicSyllabifier::new(script))))
+                    .clone(),
+                None => Arc::new(CachedSyllabifier::new(EnglishSyllabifier)),
+            };
+            builtins.insert(language, syllabifier);
+        }
+        builtins
+    });
+    builtins[language].clone()
+}
+
+/// Syllabifiers registered per sahitya `language`, plus the project exception dictionary
+pub struct SyllabifierRegistry {
+    syllabifiers: HashMap<String, Arc<dyn Syllabifier>>,
+    fallback: Arc<dyn Syllabifier>,
+    exceptions: ExceptionDictionary,
+}
+
+impl Default for SyllabifierRegistry {
+    /// Registry with the built-in languages; unknown languages use Devanagari
+    fn default() -> Self {
+        let mut registry = Self {
+            syllabifiers: HashMap::new(),
+            fallback: builtin("sanskrit"),
+            exceptions: ExceptionDictionary::new(),
+        };
+        for language in BUILTIN_LANGUAGES {
+            registry.syllabifiers.insert(language.to_string(), builtin(language));
+        }
+        registry
+    }
+}
+
+impl SyllabifierRegistry {
+    /// Registry with no languages registered
+    pub fn empty(fallback: impl Syllabifier + 'static) -> Self {
+        Self {
+            syllabifiers: HashMap::new(),
+            fallback: Arc::new(fallback),
+            exceptions: ExceptionDictionary::new(),
+        }
+    }
+
+    /// Built-in languages plus the exception dictionary found for a .vna file, if any
+    pub fn for_file(vna_file: &Path) -> Result<Self> {
+        let registry = Self::default();
+        match ExceptionDictionary::find_for(vna_file) {
+            Some(path) => Ok(registry.with_exceptions(ExceptionDictionary::load(&path)?)),
+            None => Ok(registry),
+        }
+    }
+
+    /// Shared registry with the built-in languages and no exceptions
+    pub fn shared() -> &'static SyllabifierRegistry {
+        static REGISTRY: OnceLock<SyllabifierRegistry> = OnceLock::new();
+        REGISTRY.get_or_init(SyllabifierRegistry::default)
+    }
+
+    /// Register a syllabifier for a language, replacing any built-in one
+    /// Wrap it in a `CachedSyllabifier` if it is expensive
+    pub fn register(&mut self, language: &str, syllabifier: impl Syllabifier + 'static) {
+        self.syllabifiers.insert(language.trim().to_lowercase(), Arc::new(syllabifier));
+    }
+
+    pub fn with_exceptions(mut self, exceptions: ExceptionDictionary) -> Self {
+        self.exceptions = exceptions;
+        self
+    }
+
+    pub fn exceptions(&self) -> &ExceptionDictionary {
+        &self.exceptions
+    }
+
+    /// Syllabifier for a language, or the fallback when it isn't registered
+    pub fn get(&self, language: Option<&str>) -> &dyn Syllabifier {
+        let Some(language) = language else {
+            return self.fallback.as_ref();
+        };
+        // Metadata is normally already lowercase, so try that before allocating
+        self.syllabifiers
+            .get(language)
+            .or_else(|| self.syllabifiers.get(&language.trim().to_lowercase()))
+            .unwrap_or(&self.fallback)
+            .as_ref()
+    }
+
+    /// Split a segment, consulting the exception dictionary first
+    pub fn syllabify(&self, segment: &str, language: Option<&str>, scheme: SahityaScheme) -> Vec<String> {
+        if !self.exceptions.is_empty() {
+            if let Some(units) = self.exceptions.lookup(segment, scheme) {
+                return units.to_vec();
+            }
+        }
+        self.get(language).syllabify(segment, scheme)
+    }
+}
+
+/// Simple vowel-based syllabification
+pub(crate) fn parse_vowel_based(segment: &str) -> Vec<String> {
+    let mut syllables = Vec::new();
+    let mut current = String::new();
+    let mut chars = segment.chars().peekable();
+    
+    while let Some(ch) = chars.next() {
+        current.push(ch);
+        
+        // Check if this character is a vowel
+        if is_simple_vowel(ch) {
+            // Look ahead to see if we should continue the syllable
+            let mut should_end = true;
+            
+            if let Some(&next_ch) = chars.peek() {
+                // If next is also a vowel (like 'aa', 'ii'), keep together
+                if is_simple_vowel(next_ch) && could_be_long_vowel(ch, next_ch) {
+                    should_end = false;
+                }
+                // If next is a consonant, we might want to include it
+                else if !is_simple_vowel(next_ch) {
+                    // Check further ahead
+                    let mut temp_chars = chars.clone();
+                    temp_chars.next(); // Skip the consonant
+                    
+                    if let Some(&after_cons) = temp_chars.peek() {
+                        // If pattern is vowel-consonant-vowel, end here
+                        if is_simple_vowel(after_cons) {
+                            should_end = true;
+                        } else {
+                            // Pattern is vowel-consonant-consonant, include first consonant
+                            should_end = false;
+                        }
+                    } else {
+                        // End of string after consonant, include it
+                        should_end = false;
+                    }
+                }
+            }
+            
+            if should_end && !current.is_empty() {
+                syllables.push(current.clone());
+                current.clear();
+            }
+        }
+    }
+    
+    // Add any remaining content
+    if !current.is_empty() {
+        syllables.push(current);
+    }
+    
+    // If no syllables were created, just return the whole segment
+    if syllables.is_empty() {
+        vec![segment.to_string()]
+    } else {
+        syllables
+    }
+}
+
+/// Check if character is a simple vowel
+fn is_simple_vowel(ch: char) -> bool {
+    matches!(ch, 'a' | 'ā' | 'i' | 'ī' | 'u' | 'ū' | 'e' | 'ē' | 'o' | 'ō' | 
+                 'A' | 'I' | 'U' | 'E' | 'O')
+}
+
+/// Check if two vowels could form a long vowel
+fn could_be_long_vowel(first: char, second: char) -> bool {
+    matches!((first, second), 
+        ('a', 'a') | ('a', 'ā') | ('ā', 'a') |
+        ('i', 'i') | ('i', 'ī') | ('ī', 'i') |
+        ('u', 'u') | ('u', 'ū') | ('ū', 'u') |
+        ('e', 'e') | ('e', 'ē') | ('ē', 'e') |
+        ('o', 'o') | ('o', 'ō') | ('ō', 'o')
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_english_syllabifier() {
+        let english = EnglishSyllabifier;
+        assert_eq!(english.syllabify("melody", SahityaScheme::Ascii), vec!["me", "lo", "dy"]);
+        assert_eq!(english.syllabify("tune", SahityaScheme::Ascii), vec!["tune"]);
+        assert_eq!(english.syllabify("silver", SahityaScheme::Ascii), vec!["sil", "ver"]);
+        assert_eq!(english.syllabify("gentle", SahityaScheme::Ascii), vec!["gen", "tle"]);
+        assert_eq!(english.syllabify("yes", SahityaScheme::Ascii), vec!["yes"]);
+    }
+
+    #[test]
+    fn test_registry_languages() {
+        let registry = SyllabifierRegistry::default();
+        assert_eq!(registry.syllabify("ninnu", Some("Telugu"), SahityaScheme::Iso), vec!["ni", "nnu"]);
+        assert_eq!(registry.syllabify("melody", Some("english"), SahityaScheme::Ascii), vec!["me", "lo", "dy"]);
+        // Unknown languages fall back to Devanagari
+        assert_eq!(registry.syllabify("saṅgīta", Some("klingon"), SahityaScheme::Iso), vec!["sa", "ṅgī", "ta"]);
+    }
+
+    #[test]
+    fn test_cached_syllabifier() {
+        let cached = CachedSyllabifier::new(IndicSyllabifier::new(Script::Telugu));
+        assert!(cached.is_empty());
+
+        let first = cached.syllabify("ninnukōri", SahityaScheme::Iso);
+        assert_eq!(first, vec!["ni", "nnu", "kō", "ri"]);
+        assert_eq!(cached.syllabify("ninnukōri", SahityaScheme::Iso), first);
+        assert_eq!(cached.len(), 1);
+
+        // The scheme is part of the key
+        cached.syllabify("ninnukOri", SahityaScheme::HarvardKyoto);
+        assert_eq!(cached.len(), 2);
+
+        // A full cache starts over
+        let bounded = CachedSyllabifier::with_capacity(IndicSyllabifier::new(Script::Telugu), 2);
+        for word in ["ninnu", "kōri", "yunnānu"] {
+            bounded.syllabify(word, SahityaScheme::Iso);
+        }
+        assert_eq!(bounded.len(), 1);
+        assert_eq!(bounded.syllabify("ninnu", SahityaScheme::Iso), vec!["ni", "nnu"]);
+    }
+
+    #[test]
+    fn test_registries_share_builtin_cache() {
+        let a = SyllabifierRegistry::default();
+        let b = SyllabifierRegistry::default();
+        assert!(std::ptr::addr_eq(a.get(Some("telugu")), b.get(Some("telugu"))));
+        // Sanskrit, Hindi and the fallback all use the Devanagari instance
+        assert!(std::ptr::addr_eq(a.get(Some("hindi")), a.get(None)));
+    }
+
+    #[test]
+    fn test_exception_dictionary() {
+        let exceptions = ExceptionDictionary::parse("ninnukori: \"nin`nu`kō`ri\"\n").unwrap();
+        assert_eq!(exceptions.len(), 1);
+
+        let registry = SyllabifierRegistry::default().with_exceptions(exceptions);
+        assert_eq!(
+            registry.syllabify("ninnukori", Some("telugu"), SahityaScheme::Iso),
+            vec!["nin", "nu", "kō", "ri"]
+        );
+        assert_eq!(registry.syllabify("ninnu", Some("telugu"), SahityaScheme::Iso), vec!["ni", "nnu"]);
+
+        // Entries match the word in any scheme or script
+        let exceptions = ExceptionDictionary::parse("ninnukOri: \"nin`nu`kō`ri\"\n").unwrap();
+        assert!(exceptions.lookup("ninnukōri", SahityaScheme::Iso).is_some());
+        assert!(exceptions.lookup("నిన్నుకోరి", SahityaScheme::Iso).is_some());
+        assert!(exceptions.lookup("ninnuko\u{304}ri", SahityaScheme::Iso).is_some());
+        assert!(exceptions.lookup("ninnukori", SahityaScheme::Iso).is_none());
+
+        assert!(ExceptionDictionary::parse("ninnu: \"nin-nu\"\n").is_err());
+        assert!(ExceptionDictionary::parse("- not a mapping\n").is_err());
+    }
+
+    #[test]
+    fn test_find_for() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("varnams");
+        std::fs::create_dir(&nested).unwrap();
+        std::fs::write(dir.path().join(EXCEPTIONS_FILE_NAME), "ninnukori: \"nin`nu`kō`ri\"\n").unwrap();
+
+        let found = ExceptionDictionary::find_for(&nested.join("ninnukori.vna"));
+        assert_eq!(found, Some(dir.path().join(EXCEPTIONS_FILE_NAME)));
+    }
+}
@@ -0,0 +1,790 @@
+use crate::raga::{self, RagaScales, Scale};
+use crate::ratio::Ratio;
+use crate::repeat;
+use crate::sahitya_parser::parse_sahitya_token_with_registry;
+use crate::sahitya_scheme::{detect_document_scheme, SahityaScheme};
+use crate::swara_parser::{is_note, swara_notes, split_token_gati};
+use crate::syllabifier::SyllabifierRegistry;
+use crate::types::*;
+use serde::Serialize;
+use std::fmt;
+
+pub const DEFAULT_GATI: u8 = 4;
+pub const DEFAULT_TEMPO: u32 = 60;
+
+/// A document flattened into timed events
+///
+/// A swara unit lasts 1/gati aksharas, so `SSRR` in catusra gati and `SRG:3`
+/// both take one akshara. Sustains (`,`) lengthen the preceding event, rests
+/// (`-`) are events without a note, and time runs on across sections.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeline {
+    pub events: Vec<TimelineEvent>,
+    pub sections: Vec<SectionSpan>,
+    /// File tempo in aksharas (beats) per minute
+    pub tempo: u32,
+    /// The tempo at each point, following `@tempo` changes
+    pub tempo_map: TempoMap,
+    /// Total length in aksharas
+    pub length: Ratio,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineEvent {
+    /// Index into `VnaDocument::sections`
+    pub section: usize,
+    /// Line number of the swara line
+    pub line_number: usize,
+    /// Index of the swara token within its line
+    pub token: usize,
+    /// `None` for a rest
+    pub note: Option<Note>,
+    pub syllable: Option<String>,
+    /// Onset and duration in aksharas
+    pub onset: Ratio,
+    pub duration: Ratio,
+    pub onset_seconds: f64,
+    pub duration_seconds: f64,
+    /// Gamakas written on the note (`G~`, `G/`, `{R}G`)
+    pub gamakas: Vec<Gamaka>,
+    /// Avartanam (tala cycle) containing the onset, counted from 0
+    pub avartanam: usize,
+    /// Akshara within the avartanam, counted from 0
+    pub beat: usize,
+    pub gati: u8,
+}
+
+/// Where a section sits on the timeline
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionSpan {
+    pub name: String,
+    pub start: Ratio,
+    pub length: Ratio,
+    /// Tala pattern in effect at the start of the section
+    pub tala: String,
+    pub gati: u8,
+    /// Start offset from samam, from `@eduppu` or the metadata
+    pub eduppu: Option<Eduppu>,
+    /// Raga of the section, from `@raga` or the metadata
+    pub raga: String,
+    /// Tempo of the section, from `@tempo` or the metadata
+    pub tempo: Tempo,
+    /// Speed of the section, from `@kalam` (1 by default)
+    pub kalam: u8,
+}
+
+/// Section and total durations at the file's tempo
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimingReport {
+    pub tempo: u32,
+    pub sections: Vec<SectionTiming>,
+    pub total_aksharas: Ratio,
+    /// Whole composition in first speed
+    pub first_speed_seconds: f64,
+    /// Whole composition in second speed, every section at twice its density
+    /// and the tempo changes following the notes
+    pub second_speed_seconds: f64,
+    /// A typical varnam rendition: first speed followed by second speed
+    pub both_speeds_seconds: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SectionTiming {
+    pub name: String,
+    pub tala: String,
+    pub eduppu: Option<Eduppu>,
+    pub raga: String,
+    pub tempo: Tempo,
+    pub kalam: u8,
+    pub aksharas: Ratio,
+    /// Complete avartanams
+    pub avartanams: u64,
+    /// Aksharas in a trailing incomplete avartanam, if any
+    pub incomplete_aksharas: Option<Ratio>,
+    pub seconds: f64,
+}
+
+impl Timeline {
+    /// At the file's tempo, before any `@tempo` changes
+    pub fn seconds_per_akshara(&self) -> f64 {
+        60.0 / self.tempo as f64
+    }
+
+    /// Summarise each section's length in avartanams, aksharas and seconds
+    pub fn report(&self) -> TimingReport {
+        let sections: Vec<SectionTiming> = self
+            .sections
+            .iter()
+            .map(|span| {
+                let (avartanams, incomplete_aksharas) = match tala_aksharas(&span.tala) {
+                    Some(cycle) => {
+                        let avartanams = (span.length / Ratio::from(cycle)).floor();
+                        let remainder = span.length - Ratio::from(avartanams * cycle);
+                        (avartanams, (!remainder.is_zero()).then_some(remainder))
+                    }
+                    None => (0, None),
+                };
+                SectionTiming {
+                    name: span.name.clone(),
+                    tala: span.tala.clone(),
+                    eduppu: span.eduppu,
+                    raga: span.raga.clone(),
+                    tempo: span.tempo,
+                    kalam: span.kalam,
+                    aksharas: span.length,
+                    avartanams,
+                    incomplete_aksharas,
+                    seconds: self.tempo_map.seconds_at(span.start + span.length) - self.tempo_map.seconds_at(span.start),
+                }
+            })
+            .collect();
+
+        let first_speed_seconds = self.tempo_map.seconds_at(self.length);
+        let second_speed_seconds = self.tempo_map.doubled().seconds_at(self.length / Ratio::from(2));
+        TimingReport {
+            tempo: self.tempo,
+            sections,
+            total_aksharas: self.length,
+            first_speed_seconds,
+            second_speed_seconds,
+            both_speeds_seconds: first_speed_seconds + second_speed_seconds,
+        }
+    }
+}
+
+/// Tempo along a timeline, in aksharas per minute
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempoMap {
+    /// Changes in order of position, the first at zero
+    changes: Vec<TempoChange>,
+}
+
+/// From `start`, move from `from` to `to` BPM evenly over `ramp` aksharas (at
+/// once when zero), then hold `to`
+#[derive(Debug, Clone, PartialEq)]
+struct TempoChange {
+    start: Ratio,
+    from: f64,
+    to: f64,
+    ramp: Ratio,
+}
+
+impl TempoMap {
+    pub fn new(bpm: u32) -> Self {
+        let bpm = bpm as f64;
+        Self { changes: vec![TempoChange { start: Ratio::ZERO, from: bpm, to: bpm, ramp: Ratio::ZERO }] }
+    }
+
+    /// BPM at a position, in aksharas
+    pub fn bpm_at(&self, position: Ratio) -> f64 {
+        let change = self.change_at(position);
+        let into = (position - change.start).to_f64();
+        let ramp = change.ramp.to_f64();
+        if into >= ramp {
+            change.to
+        } else {
+            change.from + (change.to - change.from) * into / ramp
+        }
+    }
+
+    /// Seconds from the start to a position, in aksharas
+    pub fn seconds_at(&self, position: Ratio) -> f64 {
+        let mut seconds = 0.0;
+        for (i, change) in self.changes.iter().enumerate() {
+            if position <= change.start {
+                break;
+            }
+            let end = self.changes.get(i + 1).map_or(position, |next| next.start.min(position));
+            seconds += change.seconds((end - change.start).to_f64());
+        }
+        seconds
+    }
+
+    /// Change to `to` BPM at `start`, gradually over `ramp` aksharas
+    fn change(&mut self, start: Ratio, to: u32, ramp: Ratio) {
+        if self.changes.len() > 1 && self.changes.last().is_some_and(|c| c.start == start) {
+            self.changes.pop();
+        }
+        let from = self.bpm_at(start);
+        self.changes.push(TempoChange { start, from, to: to as f64, ramp });
+    }
+
+    /// The map for the same music at twice the density: each change comes at
+    /// half its position, still ramping over as many aksharas
+    fn doubled(&self) -> TempoMap {
+        let mut doubled = TempoMap { changes: vec![self.changes[0].clone()] };
+        for change in &self.changes[1..] {
+            let start = change.start / Ratio::from(2);
+            let from = doubled.bpm_at(start);
+            doubled.changes.push(TempoChange { start, from, ..change.clone() });
+        }
+        doubled
+    }
+
+    fn change_at(&self, position: Ratio) -> &TempoChange {
+        self.changes
+            .iter()
+            .rev()
+            .find(|c| c.start <= position)
+            .unwrap_or(&self.changes[0])
+    }
+}
+
+impl TempoChange {
+    /// Seconds taken by the first `aksharas` after the change
+    fn seconds(&self, aksharas: f64) -> f64 {
+        let ramp = self.ramp.to_f64();
+        let ramped = aksharas.min(ramp);
+        let mut seconds = 60.0 * (aksharas - ramped) / self.to;
+        if ramped > 0.0 {
+            let reached = self.from + (self.to - self.from) * ramped / ramp;
+            seconds += if self.to == self.from {
+                60.0 * ramped / self.from
+            } else {
+                // Time under a tempo that changes evenly with position
+                60.0 * ramp / (self.to - self.from) * (reached / self.from).ln()
+            };
+        }
+        seconds
+    }
+}
+
+/// Unit speed-up for a kalam: 1, 2 or 4 for first, second or third speed
+pub fn kalam_speed(kalam: u8) -> u64 {
+    1 << kalam.clamp(1, 3).saturating_sub(1)
+}
+
+/// A swara with its variant and octave resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note {
+    /// One of S R G M P D N
+    pub swara: char,
+    /// Variant (R1, G3, ...) as written, or in a timeline as resolved from the
+    /// raga when it uses only one variant of the swara
+    pub variant: Option<u8>,
+    /// 0 for the middle octave, -1 for `.`, +1 for `'`; in a timeline counted
+    /// from the middle octave whatever the file's `default_octave`
+    pub octave: i8,
+}
+
+/// Octaves by the names `default_octave` takes, from two below the middle one
+const OCTAVES: [(&[&str], i8); 5] = [
+    (&["anumandra", "-2"], -2),
+    (&["mandra", "lower", "-1"], -1),
+    (&["madhya", "middle", "0"], 0),
+    (&["tara", "upper", "1", "+1"], 1),
+    (&["atitara", "2", "+2"], 2),
+];
+
+/// An octave from its name (`mandra`, `lower`, `-1`), relative to the middle one
+pub fn parse_octave(name: &str) -> Option<i8> {
+    let name = name.trim().to_lowercase();
+    OCTAVES.iter().find(|(names, _)| names.contains(&name.as_str())).map(|(_, octave)| *octave)
+}
+
+/// The usual name of an octave: `mandra` for -1
+pub fn octave_name(octave: i8) -> Option<&'static str> {
+    OCTAVES.iter().find(|(_, o)| *o == octave).map(|(names, _)| names[0])
+}
+
+/// The octave unmarked swaras belong to, from `default_octave` (middle when
+/// unset or unknown); octave markers count from it
+pub fn default_octave(metadata: &Metadata) -> i8 {
+    metadata.default_octave.as_deref().and_then(parse_octave).unwrap_or(0)
+}
+
+impl fmt::Display for Note {
+    /// As written: `G3`, `N2.`, `S'`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.swara)?;
+        if let Some(variant) = self.variant {
+            write!(f, "{}", variant)?;
+        }
+        let mark = if self.octave < 0 { "." } else { "'" };
+        write!(f, "{}", mark.repeat(self.octave.unsigned_abs() as usize))
+    }
+}
+
+impl Note {
+    /// Parse a swara unit produced by `swara_parser::parse_swara_units`
+    pub fn parse(unit: &str) -> Option<Self> {
+        let mut chars = unit.chars();
+        let swara = chars.next().filter(|_| is_note(unit))?;
+        let mut variant = None;
+        let mut octave = 0i8;
+
+        for ch in chars {
+            match ch {
+                '1'..='3' => variant = ch.to_digit(10).map(|d| d as u8),
+                '.' => octave -= 1,
+                '\'' => octave += 1,
+                _ => {}
+            }
+        }
+
+        Some(Self { swara, variant, octave })
+    }
+
+    /// The same note some octaves higher (or lower, when negative)
+    pub fn shifted(&self, octaves: i8) -> Self {
+        Self { octave: self.octave + octaves, ..self.clone() }
+    }
+
+    /// Semitones above the middle octave's S, taking R2 G3 M1 D2 N3 where the
+    /// variant isn't known
+    pub fn semitones(&self) -> i32 {
+        let within = match (self.swara, self.variant) {
+            ('R', Some(1)) => 1,
+            ('R', Some(3)) | ('G', Some(2)) => 3,
+            ('R', _) | ('G', Some(1)) => 2,
+            ('G', _) => 4,
+            ('M', Some(2)) => 6,
+            ('M', _) => 5,
+            ('P', _) => 7,
+            ('D', Some(1)) => 8,
+            ('D', Some(3)) | ('N', Some(2)) => 10,
+            ('D', _) | ('N', Some(1)) => 9,
+            ('N', _) => 11,
+            _ => 0,
+        };
+        within + 12 * self.octave as i32
+    }
+}
+
+/// Number of aksharas in one avartanam of a tala pattern: one per symbol
+/// (`+234+0+0` → 8), or `None` for an empty pattern
+pub fn tala_aksharas(pattern: &str) -> Option<u64> {
+    let count = pattern
+        .chars()
+        .filter(|c| *c == '+' || *c == '0' || c.is_ascii_digit())
+        .count() as u64;
+    (count > 0).then_some(count)
+}
+
+pub fn build(document: &VnaDocument) -> Timeline {
+    build_with(document, SyllabifierRegistry::shared())
+}
+
+/// Build the timeline, syllabifying sahitya with the given registry
+pub fn build_with(document: &VnaDocument, registry: &SyllabifierRegistry) -> Timeline {
+    let document = &repeat::expand(document);
+    let tempo = document.metadata.tempo.filter(|t| *t > 0).unwrap_or(DEFAULT_TEMPO);
+    let mut builder = TimelineBuilder {
+        registry,
+        language: document.metadata.language.as_deref(),
+        scheme: detect_document_scheme(document),
+        tempo,
+        events: Vec::new(),
+        position: Ratio::ZERO,
+        avartanam: 0,
+        avartanam_start: Ratio::ZERO,
+        avartanam_length: None,
+        grid_shift: Ratio::ZERO,
+        tempo_map: TempoMap::new(tempo),
+        applied_tempo: Tempo::from(tempo),
+        octave: default_octave(&document.metadata),
+    };
+
+    let file_gati = valid_gati(document.metadata.gati).unwrap_or(DEFAULT_GATI);
+    let file_tempo = Tempo::from(builder.tempo);
+    let scales = RagaScales::new(&document.metadata);
+    let mut sections = Vec::new();
+
+    for (section_index, section) in document.sections.iter().enumerate() {
+        let section_gati = valid_gati(section.gati).unwrap_or(file_gati);
+        let section_tala = section.tala.as_deref().unwrap_or(&document.metadata.tala);
+        let eduppu = section.eduppu.or(document.metadata.eduppu);
+        if let (Some(eduppu), Some(cycle)) = (eduppu, tala_aksharas(section_tala)) {
+            builder.anchor(eduppu.offset_in(cycle), cycle);
+        }
+        let start = builder.position;
+        let section_tempo = valid_tempo(section.tempo).unwrap_or(file_tempo);
+        let section_kalam = section.kalam.unwrap_or(1);
+
+        for phrase in &section.phrases {
+            let gati = valid_gati(phrase.gati).unwrap_or(section_gati);
+            let tala = phrase.tala.as_deref().unwrap_or(section_tala);
+            let scale = scales.get(raga::effective(document, section, phrase));
+            let speed = kalam_speed(phrase.kalam.unwrap_or(section_kalam));
+            builder.set_tempo(valid_tempo(phrase.tempo).unwrap_or(section_tempo), tala_aksharas(tala));
+            builder.add_phrase(section_index, phrase, gati, tala, scale.as_ref(), speed);
+        }
+
+        sections.push(SectionSpan {
+            name: section.header(),
+            start,
+            length: builder.position - start,
+            tala: section_tala.to_string(),
+            gati: section_gati,
+            eduppu,
+            raga: section.raga.clone().unwrap_or_else(|| document.metadata.raga.clone()),
+            tempo: section_tempo,
+            kalam: section_kalam,
+        });
+    }
+
+    let mut timeline = Timeline {
+        events: builder.events,
+        sections,
+        tempo: builder.tempo,
+        tempo_map: builder.tempo_map,
+        length: builder.position,
+    };
+
+    for event in &mut timeline.events {
+        event.onset_seconds = timeline.tempo_map.seconds_at(event.onset);
+        event.duration_seconds = timeline.tempo_map.seconds_at(event.onset + event.duration) - event.onset_seconds;
+    }
+
+    timeline
+}
+
+struct TimelineBuilder<'a> {
+    registry: &'a SyllabifierRegistry,
+    language: Option<&'a str>,
+    scheme: SahityaScheme,
+    tempo: u32,
+    events: Vec<TimelineEvent>,
+    position: Ratio,
+    avartanam: usize,
+    avartanam_start: Ratio,
+    /// Length of the current avartanam, fixed by the tala in effect when it began
+    avartanam_length: Option<u64>,
+    /// Added to positions to place them on the beat grid, so that a document
+    /// starting after or before samam still starts at time zero
+    grid_shift: Ratio,
+    tempo_map: TempoMap,
+    /// The `@tempo` (or file tempo) the map last changed to
+    applied_tempo: Tempo,
+    /// Octave of unmarked swaras
+    octave: i8,
+}
+
+impl TimelineBuilder<'_> {
+    /// Add a phrase's events, resolving swarasthanas with the scale of its raga
+    /// and playing units `speed` times faster for its kalam
+    fn add_phrase(&mut self, section: usize, phrase: &Phrase, line_gati: u8, tala: &str, scale: Option<&Scale>, speed: u64) {
+        let tala_length = tala_aksharas(tala);
+
+        for (token_index, token) in phrase.swaras.iter().enumerate() {
+            let (text, token_gati) = split_token_gati(token);
+            let gati = valid_gati(token_gati.and_then(|g| g.parse().ok())).unwrap_or(line_gati);
+            let unit_length = Ratio::new(1, gati as u64 * speed);
+
+            let notes = swara_notes(text);
+            let swara_units: Vec<String> = notes.iter().map(|n| n.swara.clone()).collect();
+            let syllables = phrase
+                .sahitya
+                .get(token_index)
+                .map(|s| parse_sahitya_token_with_registry(s, self.registry, self.language, self.scheme))
+                .filter(|units| units.len() == swara_units.len());
+
+            for (unit_index, unit) in swara_units.iter().enumerate() {
+                let syllable = syllables
+                    .as_ref()
+                    .map(|units| units[unit_index].clone())
+                    .filter(|s| s != "-");
+
+                if unit == "," && syllable.is_none() {
+                    match self.events.last_mut() {
+                        Some(previous) => previous.duration += unit_length,
+                        None => self.push(section, phrase, token_index, None, None, unit_length, gati, tala_length),
+                    }
+                } else {
+                    let note = if unit == "," {
+                        // A syllable on a sustain re-articulates the held note
+                        self.events.last().and_then(|e| e.note.clone())
+                    } else {
+                        Note::parse(unit).map(|note| {
+                            let note = note.shifted(self.octave);
+                            match scale {
+                                Some(scale) => scale.resolve(note),
+                                None => note,
+                            }
+                        })
+                    };
+                    let syllable = syllable.filter(|_| note.is_some());
+                    self.push(section, phrase, token_index, note, syllable, unit_length, gati, tala_length);
+                    if let Some(event) = self.events.last_mut() {
+                        event.gamakas = notes[unit_index].gamakas.clone();
+                    }
+                }
+
+                self.position += unit_length;
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn push(
+        &mut self,
+        section: usize,
+        phrase: &Phrase,
+        token: usize,
+        note: Option<Note>,
+        syllable: Option<String>,
+        duration: Ratio,
+        gati: u8,
+        tala_length: Option<u64>,
+    ) {
+        let (avartanam, beat) = self.locate(tala_length);
+        self.events.push(TimelineEvent {
+            section,
+            line_number: phrase.line_number,
+            token,
+            note,
+            syllable,
+            onset: self.position,
+            duration,
+            onset_seconds: 0.0,
+            duration_seconds: 0.0,
+            gamakas: Vec::new(),
+            avartanam,
+            beat,
+            gati,
+        });
+    }
+
+    /// Move to the tempo in effect from here, over its avartanams of `cycle`
+    /// aksharas when gradual
+    fn set_tempo(&mut self, tempo: Tempo, cycle: Option<u64>) {
+        if std::mem::replace(&mut self.applied_tempo, tempo) == tempo {
+            return;
+        }
+        let ramp = tempo.over.map_or(0, |over| over as u64 * cycle.unwrap_or(1));
+        self.tempo_map.change(self.position, tempo.bpm, Ratio::from(ramp));
+    }
+
+    /// Start the next section `offset` aksharas after samam, beginning a new
+    /// avartanam unless the beat grid already puts it there
+    fn anchor(&mut self, offset: Ratio, cycle: u64) {
+        if !self.events.is_empty() {
+            self.locate(Some(cycle));
+            let on_grid = self.avartanam_length == Some(cycle)
+                && self.position + self.grid_shift - self.avartanam_start == offset;
+            if on_grid {
+                return;
+            }
+            self.avartanam += 1;
+        }
+
+        let grid = self.position + self.grid_shift;
+        if grid < offset {
+            self.grid_shift += offset - grid;
+        }
+        self.avartanam_start = self.position + self.grid_shift - offset;
+        self.avartanam_length = Some(cycle);
+    }
+
+    /// Avartanam and akshara of the current position, advancing whole cycles as needed
+    fn locate(&mut self, tala_length: Option<u64>) -> (usize, usize) {
+        let grid = self.position + self.grid_shift;
+        let Some(tala_length) = tala_length else {
+            return (0, (grid - self.avartanam_start).floor() as usize);
+        };
+
+        let mut length = *self.avartanam_length.get_or_insert(tala_length);
+        while grid >= self.avartanam_start + Ratio::from(length) {
+            self.avartanam_start += Ratio::from(length);
+            self.avartanam += 1;
+            length = tala_length;
+            self.avartanam_length = Some(length);
+        }
+
+        (self.avartanam, (grid - self.avartanam_start).floor() as usize)
+    }
+}
+
+fn valid_gati(gati: Option<u8>) -> Option<u8> {
+    gati.filter(|g| *g > 0)
+}
+
+fn valid_tempo(tempo: Option<Tempo>) -> Option<Tempo> {
+    tempo.filter(|t| t.bpm > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    const CONTENT: &str = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+tempo: 120
+language: "telugu"
+---
+
+[pallavi]
+G,G,      R,,,  | SSRR  GGRR | SRGR SRSD. | SRGP GRSR ||
+`nin`-nu- kō--- | ri--- ---- | ---- ----  | ---- ---- ||
+
+@gati: 3
+SRG PDP:3 DS':2 -,, ||
+--- ---   ---   --- ||
+"#;
+
+    #[test]
+    fn test_note_parse() {
+        assert_eq!(Note::parse("R2").unwrap(), Note { swara: 'R', variant: Some(2), octave: 0 });
+        assert_eq!(Note::parse("D.").unwrap().octave, -1);
+        assert_eq!(Note::parse("S''").unwrap().octave, 2);
+        assert_eq!(Note::parse(","), None);
+        assert_eq!(tala_aksharas("+234+0+0"), Some(8));
+        assert_eq!(tala_aksharas("+230+00"), Some(7));
+    }
+
+    #[test]
+    fn test_default_octave() {
+        assert_eq!(parse_octave("Mandra"), Some(-1));
+        assert_eq!(parse_octave("+1"), Some(1));
+        assert_eq!(parse_octave("low"), None);
+        assert_eq!(octave_name(2), Some("atitara"));
+        assert_eq!(Note::parse("N2.").unwrap().to_string(), "N2.");
+        assert_eq!(Note::parse("S").unwrap().shifted(2).to_string(), "S''");
+
+        // Markers count from the default octave
+        let mut doc = parse(CONTENT).unwrap();
+        doc.metadata.default_octave = Some("mandra".to_string());
+        let timeline = build(&doc);
+        assert_eq!(timeline.events[0].note.as_ref().unwrap().octave, -1);
+        let notes: Vec<&Note> = timeline.events.iter().filter_map(|e| e.note.as_ref()).collect();
+        assert!(notes.iter().any(|n| n.swara == 'D' && n.octave == -2));
+        assert!(notes.iter().all(|n| n.octave <= 0));
+    }
+
+    #[test]
+    fn test_build_timeline() {
+        let doc = parse(CONTENT).unwrap();
+        let timeline = build(&doc);
+
+        // G, G, R,,,
+        let first = &timeline.events[0];
+        assert_eq!(first.note.as_ref().unwrap().swara, 'G');
+        assert_eq!(first.syllable.as_deref(), Some("nin"));
+        assert_eq!(first.duration, Ratio::new(1, 2));
+        assert_eq!(timeline.events[1].onset, Ratio::new(1, 2));
+        assert_eq!(timeline.events[1].syllable.as_deref(), Some("nu"));
+        assert_eq!(timeline.events[2].duration, Ratio::from(1));
+        assert_eq!(timeline.events[2].syllable.as_deref(), Some("kō"));
+        assert_eq!(timeline.events[2].beat, 1);
+
+        // 120 BPM: half a second per akshara
+        assert_eq!(timeline.events[2].onset_seconds, 0.5);
+        assert_eq!(timeline.events[2].duration_seconds, 0.5);
+
+        // The second line starts the second avartanam in tisra gati
+        let second_line: Vec<_> = timeline.events.iter().filter(|e| e.line_number > 10).collect();
+        assert_eq!(second_line[0].onset, Ratio::from(8));
+        assert_eq!(second_line[0].avartanam, 1);
+        assert_eq!(second_line[0].beat, 0);
+        assert_eq!(second_line[0].gati, 3);
+        assert_eq!(second_line[0].duration, Ratio::new(1, 3));
+        // DS':2 takes one akshara in two halves
+        let upper_sa = second_line.iter().find(|e| e.note.as_ref().is_some_and(|n| n.octave == 1)).unwrap();
+        assert_eq!(upper_sa.duration, Ratio::new(1, 2));
+        assert_eq!(upper_sa.onset, Ratio::new(21, 2));
+        // The closing rest is sustained
+        let rest = second_line.last().unwrap();
+        assert_eq!(rest.note, None);
+        assert_eq!(rest.duration, Ratio::from(1));
+
+        assert_eq!(timeline.length, Ratio::from(12));
+        assert_eq!(timeline.sections[0].length, Ratio::from(12));
+    }
+
+    #[test]
+    fn test_timing_report() {
+        let doc = parse(CONTENT).unwrap();
+        let report = build(&doc).report();
+
+        let pallavi = &report.sections[0];
+        assert_eq!(pallavi.avartanams, 1);
+        assert_eq!(pallavi.incomplete_aksharas, Some(Ratio::from(4)));
+        assert_eq!(pallavi.seconds, 6.0);
+        assert_eq!(report.first_speed_seconds, 6.0);
+        assert_eq!(report.second_speed_seconds, 3.0);
+        assert_eq!(report.both_speeds_seconds, 9.0);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["sections"][0]["aksharas"], 12);
+        assert_eq!(json["sections"][0]["incomplete_aksharas"], 4);
+    }
+
+    #[test]
+    fn test_eduppu() {
+        let content = CONTENT.replace("[pallavi]\n", "[pallavi]\n@eduppu: 1/2\n");
+        let doc = parse(&content).unwrap();
+        let timeline = build(&doc);
+
+        // The first note is still at time zero, half an akshara after samam
+        assert_eq!(timeline.events[0].onset, Ratio::ZERO);
+        assert_eq!(timeline.events[0].beat, 0);
+        let samam = timeline.events.iter().find(|e| e.onset == Ratio::new(15, 2)).unwrap();
+        assert_eq!((samam.avartanam, samam.beat), (1, 0));
+
+        // Atita: a quarter akshara before samam
+        let doc = parse(&content.replace("1/2", "-0.25")).unwrap();
+        let timeline = build(&doc);
+        assert_eq!((timeline.events[0].avartanam, timeline.events[0].beat), (0, 7));
+        assert_eq!(timeline.events[1].avartanam, 1);
+
+        let report = timeline.report();
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["sections"][0]["eduppu"], "-1/4");
+    }
+
+    #[test]
+    fn test_tempo_and_kalam_changes() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+tempo: 60
+---
+
+[pallavi]
+SRGR GRSR | SRGR GRSR | SRGR GRSR | SRGR GRSR ||
+---- ---- | ---- ---- | ---- ---- | ---- ---- ||
+
+[muktayisvaram]
+@kalam: 2
+SRGR GRSR | SRGR GRSR | SRGR GRSR | SRGR GRSR ||
+---- ---- | ---- ---- | ---- ---- | ---- ---- ||
+
+@tempo: 120
+SRGR GRSR | SRGR GRSR | SRGR GRSR | SRGR GRSR ||
+---- ---- | ---- ---- | ---- ---- | ---- ---- ||
+
+[charanam]
+@tempo: 40 over 1
+SRGR GRSR | SRGR GRSR | SRGR GRSR | SRGR GRSR ||
+---- ---- | ---- ---- | ---- ---- | ---- ---- ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let timeline = build(&doc);
+        let report = timeline.report();
+
+        // Second speed halves each line; the line at 120 BPM halves it again
+        assert_eq!(report.sections[1].aksharas, Ratio::from(8));
+        assert_eq!(report.sections[1].kalam, 2);
+        assert!((report.sections[1].seconds - 6.0).abs() < 1e-9);
+
+        // Slowing evenly from the 120 BPM just played to 40 BPM over one avartanam of 8 aksharas
+        let expected = 60.0 * 8.0 / -80.0 * (40.0f64 / 120.0).ln();
+        assert!((report.sections[2].seconds - expected).abs() < 1e-9);
+        assert_eq!(report.sections[2].tempo.to_string(), "40 over 1");
+        assert!((timeline.tempo_map.bpm_at(Ratio::from(20)) - 80.0).abs() < 1e-9);
+
+        let last = timeline.events.last().unwrap();
+        assert!((last.onset_seconds + last.duration_seconds - (14.0 + expected)).abs() < 1e-9);
+
+        // In second speed the charanam ends halfway through the slowing
+        // avartanam, at 80 BPM
+        let second = 7.0 + 60.0 * 8.0 / -80.0 * (80.0f64 / 120.0).ln();
+        assert!((report.second_speed_seconds - second).abs() < 1e-9);
+        assert!((report.both_speeds_seconds - (14.0 + expected + second)).abs() < 1e-9);
+    }
+}
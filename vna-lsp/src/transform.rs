@@ -0,0 +1,644 @@
+use crate::align::encode_token;
+use crate::parser::TYPED_DIRECTIVES;
+use crate::ratio::Ratio;
+use crate::repeat;
+use crate::sahitya_parser::parse_sahitya_token_with_word_breaks;
+use crate::sahitya_scheme::{detect_document_scheme, SahityaScheme};
+use crate::swara_parser::{is_note, parse_swara_units, parse_written_units, shift_octave, split_token_gati};
+use crate::syllabifier::SyllabifierRegistry;
+use crate::timeline::{default_octave, octave_name, tala_aksharas, DEFAULT_GATI};
+use crate::types::*;
+use anyhow::{anyhow, Result};
+
+/// Re-express sections at 2× or 4× density (second or third speed)
+///
+/// Every unit keeps its note and syllable but takes 1/factor of its time, so
+/// the section's gati is multiplied by the factor. The section is then
+/// re-flowed: tokens are merged per akshara, lines hold one avartanam and `|`
+/// markers fall on the tala's anga boundaries. With `section` set only that
+/// section is converted. Repeat groups are written out in the result.
+pub fn change_kalam(
+    document: &VnaDocument,
+    factor: u8,
+    section: Option<&str>,
+    registry: &SyllabifierRegistry,
+) -> Result<VnaDocument> {
+    if !matches!(factor, 2 | 4) {
+        return Err(anyhow!("Kalam factor must be 2 or 4, got {}", factor));
+    }
+
+    let expanded = repeat::expand(document);
+    let mut result = expanded.clone();
+    let context = Context::new(&expanded, registry);
+
+    for target in selected_sections(&mut result, section)? {
+        let section_gati = context.section_gati(target);
+        let new_gati = section_gati
+            .checked_mul(factor)
+            .ok_or_else(|| anyhow!("Gati {} in [{}] is too large to speed up", section_gati, target.name))?;
+
+        let mut units = context.section_units(target)?;
+        for unit in &mut units {
+            unit.size = unit.size / Ratio::from(factor as u64);
+        }
+
+        let tala = context.section_tala(target).to_string();
+        target.phrases = context.reflow(&target.phrases, units, Ratio::ZERO, new_gati, &tala)?;
+        target.gati = Some(new_gati);
+    }
+
+    Ok(result)
+}
+
+/// Rewrite sections, or the phrase on one line, in another gati with the same durations
+///
+/// Each note, rest or syllable keeps its onset and length: it becomes one unit
+/// of the new gati followed by sustains (`,`, or `-` after a rest). Lengths that
+/// aren't whole units of the gati use a faster grouping of the same gati
+/// (`:8` or `:16` in catusra); anything else is refused. A converted section
+/// gets `@gati`; a converted line gets a line-level `@gati` unless it now
+/// matches its section.
+pub fn change_gati(
+    document: &VnaDocument,
+    gati: u8,
+    section: Option<&str>,
+    line: Option<usize>,
+    registry: &SyllabifierRegistry,
+) -> Result<VnaDocument> {
+    if gati == 0 {
+        return Err(anyhow!("Gati must be at least 1"));
+    }
+
+    let expanded = repeat::expand(document);
+    let mut result = expanded.clone();
+    let context = Context::new(&expanded, registry);
+    let mut found_line = false;
+
+    for target in selected_sections(&mut result, section)? {
+        let tala = context.section_tala(target).to_string();
+        let units = context.section_units(target)?;
+
+        let Some(line) = line else {
+            let units = regroup(&target.phrases, units, gati)?;
+            target.phrases = context.reflow(&target.phrases, units, Ratio::ZERO, gati, &tala)?;
+            target.gati = Some(gati);
+            continue;
+        };
+
+        let Some(index) = target.phrases.iter().position(|p| p.line_number == line) else {
+            continue;
+        };
+        found_line = true;
+
+        let start = units
+            .iter()
+            .take_while(|u| u.phrase < index)
+            .fold(Ratio::ZERO, |sum, u| sum + u.size);
+        let phrase_units: Vec<Unit> = units
+            .into_iter()
+            .filter(|u| u.phrase == index)
+            .map(|u| Unit { phrase: 0, ..u })
+            .collect();
+        let original = &target.phrases[index..=index];
+        let phrase_units = regroup(original, phrase_units, gati)?;
+
+        let line_gati = (gati != context.section_gati(target)).then_some(gati);
+        // A first-line @gati would read back as the section's, so state the section's
+        if index == 0 && line_gati.is_some() && target.gati.is_none() {
+            target.gati = Some(context.section_gati(target));
+        }
+        let mut phrases = context.reflow(original, phrase_units, start, gati, &tala)?;
+        for phrase in &mut phrases {
+            phrase.gati = line_gati;
+        }
+        target.phrases.splice(index..=index, phrases);
+    }
+
+    match line {
+        Some(line) if !found_line => Err(anyhow!("No swara line at line {}", line)),
+        _ => Ok(result),
+    }
+}
+
+/// Rewrite octave markers to count from another default octave, keeping
+/// every note's pitch
+///
+/// `S` under `default_octave: mandra` becomes `S.` rebased to madhya and back
+/// again, so files can move between conventions without loss. Repeat groups
+/// and gamakas are kept as written; the result states the new default octave,
+/// leaving it out for madhya.
+pub fn rebase_octave(document: &VnaDocument, octave: i8) -> Result<VnaDocument> {
+    let name = octave_name(octave).ok_or_else(|| anyhow!("Octave must be from -2 (anumandra) to 2 (atitara), got {}", octave))?;
+    let by = default_octave(&document.metadata) - octave;
+
+    let mut result = document.clone();
+    for phrase in result.sections.iter_mut().flat_map(|s| s.phrases.iter_mut()) {
+        for token in &mut phrase.swaras {
+            *token = shift_octave(token, by);
+        }
+    }
+    result.metadata.default_octave = (octave != 0).then(|| name.to_string());
+    Ok(result)
+}
+
+/// One swara unit with its sahitya, as laid out in time
+#[derive(Debug, Clone)]
+struct Unit {
+    swara: String,
+    sahitya: String,
+    /// The syllable starts a new word
+    word_break: bool,
+    /// Length in aksharas
+    size: Ratio,
+    /// Index of the phrase the unit came from
+    phrase: usize,
+}
+
+/// A token being assembled during a reflow
+#[derive(Default)]
+struct PendingToken {
+    units: Vec<Unit>,
+}
+
+struct Context<'a> {
+    metadata: &'a Metadata,
+    registry: &'a SyllabifierRegistry,
+    language: Option<&'a str>,
+    scheme: SahityaScheme,
+}
+
+impl<'a> Context<'a> {
+    fn new(document: &'a VnaDocument, registry: &'a SyllabifierRegistry) -> Self {
+        Self {
+            metadata: &document.metadata,
+            registry,
+            language: document.metadata.language.as_deref(),
+            scheme: detect_document_scheme(document),
+        }
+    }
+
+    fn section_gati(&self, section: &Section) -> u8 {
+        section
+            .gati
+            .or(self.metadata.gati)
+            .filter(|g| *g > 0)
+            .unwrap_or(DEFAULT_GATI)
+    }
+
+    fn section_tala<'s>(&'s self, section: &'s Section) -> &'s str {
+        section.tala.as_deref().unwrap_or(&self.metadata.tala)
+    }
+
+    /// Flatten a section into units, pairing each swara unit with its sahitya unit
+    fn section_units(&self, section: &Section) -> Result<Vec<Unit>> {
+        let section_gati = self.section_gati(section);
+        let mut units = Vec::new();
+        let mut pending_break = false;
+
+        for (phrase_index, phrase) in section.phrases.iter().enumerate() {
+            if phrase.tala.is_some() {
+                return Err(anyhow!(
+                    "Line {} has its own @tala, which can't be re-flowed",
+                    phrase.line_number
+                ));
+            }
+            if let Some(sangati) = phrase.sangati {
+                return Err(anyhow!(
+                    "Line {} is sangati {}, which can't be re-flowed",
+                    phrase.line_number,
+                    sangati
+                ));
+            }
+            if let Some(extra) = phrase.sahitya_lines.first() {
+                return Err(anyhow!(
+                    "Line {} has a {} line, which can't be re-flowed",
+                    phrase.line_number,
+                    extra.tag()
+                ));
+            }
+            let line_gati = phrase.gati.filter(|g| *g > 0).unwrap_or(section_gati);
+            // A line end is a word end, which must survive lines being merged
+            let mut line_has_syllable = false;
+
+            // A swara-only line is sung to nothing, a rest under every unit
+            let rests: Vec<String>;
+            let sahitya_tokens = if phrase.sahitya.is_empty() {
+                rests = phrase.swaras.iter().map(|t| "-".repeat(parse_swara_units(split_token_gati(t).0).len())).collect();
+                &rests
+            } else {
+                &phrase.sahitya
+            };
+
+            for (token, sahitya) in phrase.swaras.iter().zip(sahitya_tokens) {
+                let (text, token_gati) = split_token_gati(token);
+                let gati = match token_gati {
+                    Some(gati) => gati
+                        .parse::<u8>()
+                        .ok()
+                        .filter(|g| *g > 0)
+                        .ok_or_else(|| anyhow!("Invalid gati in token '{}' at line {}", token, phrase.line_number))?,
+                    None => line_gati,
+                };
+
+                // Units keep their gamaka markers through the re-flow
+                let swara_units = parse_written_units(text);
+                let (sahitya_units, word_breaks) = parse_sahitya_token_with_word_breaks(
+                    sahitya,
+                    self.registry,
+                    self.language,
+                    self.scheme,
+                    &mut pending_break,
+                );
+                if swara_units.len() != sahitya_units.len() {
+                    return Err(anyhow!(
+                        "Token unit mismatch at line {}: swara '{}' ({} units) vs sahitya '{}' ({} units)",
+                        phrase.line_number,
+                        token,
+                        swara_units.len(),
+                        sahitya,
+                        sahitya_units.len()
+                    ));
+                }
+
+                for ((swara, sahitya), word_break) in swara_units.into_iter().zip(sahitya_units).zip(word_breaks) {
+                    let starts_line = sahitya != "-" && !std::mem::replace(&mut line_has_syllable, true);
+                    let word_break = word_break || starts_line;
+                    units.push(Unit {
+                        swara,
+                        sahitya,
+                        word_break,
+                        size: Ratio::new(1, gati as u64),
+                        phrase: phrase_index,
+                    });
+                }
+            }
+        }
+
+        Ok(units)
+    }
+
+    /// Lay units out as phrases: one token per akshara (split where unit sizes
+    /// change), one line per avartanam and beat markers at anga boundaries
+    /// Comments before an original phrase move to the line where it now starts
+    /// `start` is the position of the first unit within the section, in aksharas
+    fn reflow(&self, original: &[Phrase], units: Vec<Unit>, start: Ratio, gati: u8, tala: &str) -> Result<Vec<Phrase>> {
+        let cycle = tala_aksharas(tala).ok_or_else(|| anyhow!("Can't re-flow without a tala pattern"))?;
+        let angas = anga_starts(tala);
+        let mut lines: Vec<(Phrase, Vec<usize>)> = Vec::new();
+        let mut line: Vec<PendingToken> = Vec::new();
+        let mut beat_positions = Vec::new();
+        let mut token = PendingToken::default();
+        let mut position = start;
+
+        for unit in units {
+            let at_akshara = position.is_integer();
+            if token.units.first().is_some_and(|first| at_akshara || first.size != unit.size) {
+                line.push(std::mem::take(&mut token));
+            }
+            if at_akshara && !line.is_empty() {
+                let beat = position.floor() % cycle;
+                if beat == 0 {
+                    lines.push(self.finish_line(std::mem::take(&mut line), std::mem::take(&mut beat_positions), gati)?);
+                } else if angas.contains(&beat) {
+                    beat_positions.push(line.len());
+                }
+            }
+
+            position += unit.size;
+            token.units.push(unit);
+        }
+
+        if !token.units.is_empty() {
+            line.push(token);
+        }
+        if !line.is_empty() {
+            lines.push(self.finish_line(line, beat_positions, gati)?);
+        }
+
+        // Original comments go with the first new line holding one of the phrase's units,
+        // and its line-level directives (@raga, @tempo, ...) with every new line drawn from it
+        let mut placed = vec![false; original.len()];
+        let mut phrases = Vec::new();
+        for (mut phrase, origins) in lines {
+            let first = &original[origins[0]];
+            if first.sahitya.is_empty() {
+                phrase.sahitya.clear();
+            }
+            phrase.raga = first.raga.clone();
+            phrase.tempo = first.tempo;
+            phrase.kalam = first.kalam;
+            phrase.directives = first.directives.clone();
+            let untyped = |p: &Phrase| -> Vec<(String, String)> {
+                p.directives.iter().filter(|(k, _)| !TYPED_DIRECTIVES.contains(&k.as_str())).cloned().collect()
+            };
+            let same_directives = |o: &Phrase| {
+                o.raga == first.raga && o.tempo == first.tempo && o.kalam == first.kalam && untyped(o) == untyped(first)
+            };
+            if let Some(other) = origins.iter().map(|&o| &original[o]).find(|o| !same_directives(o)) {
+                return Err(anyhow!(
+                    "Lines {} and {} have different directives and can't be merged",
+                    first.line_number,
+                    other.line_number
+                ));
+            }
+            for origin in origins {
+                if !std::mem::replace(&mut placed[origin], true) {
+                    phrase.preceding_comments.extend(original[origin].preceding_comments.iter().cloned());
+                }
+            }
+            phrases.push(phrase);
+        }
+
+        Ok(phrases)
+    }
+
+    /// Build a phrase from tokens, with the indices of the original phrases it draws on
+    fn finish_line(&self, tokens: Vec<PendingToken>, beat_positions: Vec<usize>, gati: u8) -> Result<(Phrase, Vec<usize>)> {
+        let mut swaras = Vec::new();
+        let mut sahitya = Vec::new();
+        let mut origins: Vec<usize> = Vec::new();
+        let mut line_has_syllable = false;
+
+        for token in tokens {
+            let size = token.units[0].size;
+            if size.numer() != 1 || size.denom() > u8::MAX as u64 {
+                return Err(anyhow!("Units of {} aksharas can't be written as a gati", size));
+            }
+
+            let mut swara: String = token.units.iter().map(|u| u.swara.as_str()).collect();
+            if size.denom() != gati as u64 {
+                swara.push_str(&format!(":{}", size.denom()));
+            }
+            swaras.push(swara);
+
+            let units: Vec<String> = token.units.iter().map(|u| u.sahitya.clone()).collect();
+            let mut breaks: Vec<bool> = token.units.iter().map(|u| u.word_break).collect();
+            // A line starts a word, so its first syllable doesn't carry one on
+            if !line_has_syllable {
+                if let Some(first) = units.iter().position(|u| u != "-") {
+                    breaks[first] = true;
+                    line_has_syllable = true;
+                }
+            }
+            sahitya.push(encode_token(&units, &breaks, self.registry, self.language, self.scheme)?);
+
+            for unit in &token.units {
+                if origins.last() != Some(&unit.phrase) {
+                    origins.push(unit.phrase);
+                }
+            }
+        }
+
+        let phrase = Phrase {
+            swaras,
+            sahitya,
+            sahitya_lines: Vec::new(),
+            phrase_analysis: None,
+            line_number: 0,
+            preceding_comments: Vec::new(),
+            gati: None,
+            tala: None,
+            raga: None,
+            tempo: None,
+            kalam: None,
+            sangati: None,
+            directives: Default::default(),
+            beat_positions,
+        };
+        Ok((phrase, origins))
+    }
+}
+
+/// Re-express units in `gati`, one event (a note, rest or syllable with its
+/// sustains) at a time
+fn regroup(phrases: &[Phrase], units: Vec<Unit>, gati: u8) -> Result<Vec<Unit>> {
+    let mut events: Vec<Vec<Unit>> = Vec::new();
+    for unit in units {
+        let starts_event = is_note(&unit.swara) || unit.swara == "-" || unit.sahitya != "-";
+        match events.last_mut() {
+            Some(event) if !starts_event => event.push(unit),
+            _ => events.push(vec![unit]),
+        }
+    }
+
+    let mut result = Vec::new();
+    for event in events {
+        let length = event.iter().fold(Ratio::ZERO, |sum, u| sum + u.size);
+        let first = event.into_iter().next().expect("events are never empty");
+
+        let grouping = [1u64, 2, 4]
+            .into_iter()
+            .map(|speed| Ratio::new(1, gati as u64 * speed))
+            .find(|size| (length / *size).is_integer());
+        let Some(size) = grouping else {
+            return Err(anyhow!(
+                "'{}' at line {} lasts {} aksharas, which isn't a whole number of units in gati {}",
+                first.swara,
+                phrases[first.phrase].line_number,
+                length,
+                gati
+            ));
+        };
+
+        let sustain = if first.swara == "-" { "-" } else { "," };
+        let count = (length / size).floor();
+        for i in 0..count {
+            let unit = if i == 0 {
+                first.clone()
+            } else {
+                Unit {
+                    swara: sustain.to_string(),
+                    sahitya: "-".to_string(),
+                    word_break: false,
+                    ..first.clone()
+                }
+            };
+            result.push(Unit { size, ..unit });
+        }
+    }
+
+    Ok(result)
+}
+
+/// Sections to transform: the named one (with its pathantaras, unless one is
+/// named as `pallavi@semmangudi`), or all of them
+fn selected_sections<'d>(document: &'d mut VnaDocument, name: Option<&str>) -> Result<Vec<&'d mut Section>> {
+    let sections: Vec<&mut Section> = document
+        .sections
+        .iter_mut()
+        .filter(|s| name.is_none_or(|name| s.name == name || s.header() == name))
+        .collect();
+
+    match name {
+        Some(name) if sections.is_empty() => Err(anyhow!("No section named [{}]", name)),
+        _ => Ok(sections),
+    }
+}
+
+/// Aksharas (after the first) where a new anga starts: each tali after the first
+/// `+234+0+0` → [4, 6]
+fn anga_starts(tala: &str) -> Vec<u64> {
+    tala.chars()
+        .filter(|c| *c == '+' || *c == '0' || c.is_ascii_digit())
+        .enumerate()
+        .filter(|(i, c)| *i > 0 && *c == '+')
+        .map(|(i, _)| i as u64)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    const CONTENT: &str = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+language: "telugu"
+---
+
+[pallavi]
+# First line
+G,G,      R,,,  | SSRR   GGRR | SRGR     SRSD.  | SRGP     GRSR  ||
+`nin`-nu- kō--- | =ri--- ---- | `yun`--- =nā--- | =`nu`--- rā--- ||
+
+GPGG  RSRG    | RRSD. SRGR   | GPG:3 PDP:3 | DDPG DPGR   ||
+ni--- =--khi- | ----  =la--- | lō--  =ka-- | ---- ya-ka- ||
+"#;
+
+    #[test]
+    fn test_change_kalam() {
+        let doc = parse(CONTENT).unwrap();
+        let fast = change_kalam(&doc, 2, None, SyllabifierRegistry::shared()).unwrap();
+        let section = &fast.sections[0];
+
+        assert_eq!(section.gati, Some(8));
+        assert_eq!(section.phrases.len(), 1);
+        let phrase = &section.phrases[0];
+        assert_eq!(phrase.swaras[0], "G,G,R,,,");
+        assert_eq!(phrase.sahitya[0], "`nin`-nu-_kō---");
+        assert_eq!(phrase.sahitya[1], "=ri-------");
+        assert_eq!(phrase.swaras[1], "SSRRGGRR");
+        // Tisra tokens become a second-speed tisra group
+        assert_eq!(phrase.swaras[6], "GPGPDP:6");
+        assert_eq!(phrase.beat_positions, vec![4, 6]);
+        assert_eq!(phrase.preceding_comments[0].text, "First line");
+
+        // Same notes and syllables in half the time
+        let before = crate::timeline::build(&doc);
+        let after = crate::timeline::build(&fast);
+        assert_eq!(after.length, Ratio::from(8));
+        assert_eq!(before.events.len(), after.events.len());
+        let words = |doc: &VnaDocument| crate::lyrics::extract(doc)[0].lines.join(" ");
+        assert_eq!(words(&doc), words(&fast));
+
+        // The result is valid VNA
+        let formatted = crate::formatter::format(&fast).unwrap();
+        let reparsed = parse(&formatted).unwrap();
+        let issues = crate::validator::validate(&reparsed).unwrap();
+        assert!(issues.iter().all(|i| i.severity != Severity::Error), "{:?}", issues);
+    }
+
+    #[test]
+    fn test_change_kalam_errors() {
+        let doc = parse(CONTENT).unwrap();
+        assert!(change_kalam(&doc, 3, None, SyllabifierRegistry::shared()).is_err());
+        assert!(change_kalam(&doc, 2, Some("charanam"), SyllabifierRegistry::shared()).is_err());
+    }
+
+    #[test]
+    fn test_change_kalam_keeps_gamakas() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+{R}G~,,, P/,D\, | S,,, R,,, | G,,, P,,, | D,,, S',,, ||
+ni---    kō--- | ---- ---- | ---- ---- | ---- ---- ||
+"#;
+        let doc = parse(content).unwrap();
+        let fast = change_kalam(&doc, 2, None, SyllabifierRegistry::shared()).unwrap();
+        assert_eq!(fast.sections[0].phrases[0].swaras[0], "{R}G~,,,P/,D\\,");
+    }
+
+    const GATI_CONTENT: &str = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+S,,,  R,,,  G,,,  P,,,  | D,,,  P,,,  | G,R,   S,,,  ||
+sa--- ri--- ga--- pa--- | da--- pa--- | ga-ri- sa--- ||
+
+@gati: 3
+SRG  SRG SRG SRG | PDP GPG | D,, P,, ||
+sa-- --- --- --- | --- --- | --- --- ||
+"#;
+
+    #[test]
+    fn test_change_gati() {
+        let doc = parse(GATI_CONTENT).unwrap();
+        let tisra = change_gati(&doc, 3, None, Some(8), SyllabifierRegistry::shared()).unwrap();
+        let section = &tisra.sections[0];
+
+        // The section's own gati is stated so that the first line's reads back as a line gati
+        assert_eq!(section.gati, Some(4));
+        let phrase = &section.phrases[0];
+        assert_eq!(phrase.gati, Some(3));
+        assert_eq!(phrase.swaras[0], "S,,");
+        assert_eq!(phrase.sahitya[0], "sa--");
+        // Half an akshara is a grouping of second-speed tisra
+        assert_eq!(phrase.swaras[6], "G,,R,,:6");
+        assert_eq!(phrase.beat_positions, vec![4, 6]);
+        assert_eq!(section.phrases[1].swaras, doc.sections[0].phrases[1].swaras);
+
+        let sextuple = change_gati(&doc, 6, None, None, SyllabifierRegistry::shared()).unwrap();
+        let section = &sextuple.sections[0];
+        assert_eq!(section.gati, Some(6));
+        assert_eq!(section.phrases[1].swaras[0], "S,R,G,");
+
+        // Every onset and duration is unchanged
+        for converted in [&tisra, &sextuple] {
+            let formatted = crate::formatter::format(converted).unwrap();
+            let reparsed = parse(&formatted).unwrap();
+            let before = crate::timeline::build(&doc);
+            let after = crate::timeline::build(&reparsed);
+            assert_eq!(before.length, after.length);
+            let onsets = |timeline: &crate::timeline::Timeline| -> Vec<(Ratio, Ratio, Option<String>)> {
+                timeline.events.iter().map(|e| (e.onset, e.duration, e.syllable.clone())).collect()
+            };
+            assert_eq!(onsets(&before), onsets(&after));
+        }
+    }
+
+    #[test]
+    fn test_change_gati_refuses_inexact_durations() {
+        let doc = parse(GATI_CONTENT).unwrap();
+        let error = change_gati(&doc, 4, None, Some(12), SyllabifierRegistry::shared()).unwrap_err();
+        assert!(error.to_string().contains("'S' at line 12 lasts 1/3 aksharas"), "{}", error);
+        assert!(change_gati(&doc, 4, None, Some(10), SyllabifierRegistry::shared()).is_err());
+    }
+
+    #[test]
+    fn test_rebase_octave() {
+        let doc = parse(CONTENT).unwrap();
+        let lower = rebase_octave(&doc, -1).unwrap();
+        assert_eq!(lower.metadata.default_octave.as_deref(), Some("mandra"));
+        assert_eq!(lower.sections[0].phrases[0].swaras[0], "G',G',");
+        assert_eq!(lower.sections[0].phrases[1].swaras[4], "G'P'G':3");
+        assert_eq!(lower.sections[0].phrases[1].swaras[2], "R'R'S'D");
+
+        // The same pitches, and back again without loss
+        let notes = |doc: &VnaDocument| -> Vec<Option<crate::timeline::Note>> {
+            crate::timeline::build(doc).events.into_iter().map(|e| e.note).collect()
+        };
+        assert_eq!(notes(&lower), notes(&doc));
+        assert_eq!(rebase_octave(&lower, 0).unwrap(), doc);
+
+        assert!(rebase_octave(&doc, 3).is_err());
+    }
+}
@@ -0,0 +1,240 @@
+use crate::timeline::Note;
+use crate::types::Metadata;
+
+/// Pitch of the tonic when a file gives no key, or one that can't be read
+pub const DEFAULT_KEY: &str = "C";
+
+/// Octave of a pitch name written without one; the kattai run over the same
+/// octave, 1 kattai being C3
+const DEFAULT_OCTAVE: i32 = 3;
+
+/// Kattai in half steps (4½ kattai is 9) with their pitch class from C
+const KATTAI: [(u32, i32); 12] =
+    [(2, 0), (3, 1), (4, 2), (5, 3), (6, 4), (8, 5), (9, 6), (10, 7), (11, 8), (12, 9), (13, 10), (14, 11)];
+
+/// 5-limit just ratios of the swarasthanas, by semitone above S
+const JUST: [(u32, u32); 12] =
+    [(1, 1), (16, 15), (9, 8), (6, 5), (5, 4), (4, 3), (45, 32), (3, 2), (8, 5), (5, 3), (9, 5), (15, 8)];
+
+/// The 22 shrutis of the octave
+pub const SHRUTIS: [(&str, u32, u32); 22] = [
+    ("shadja", 1, 1),
+    ("ekashruti rishabha", 256, 243),
+    ("dvishruti rishabha", 16, 15),
+    ("trishruti rishabha", 10, 9),
+    ("chatushruti rishabha", 9, 8),
+    ("shuddha gandhara", 32, 27),
+    ("sadharana gandhara", 6, 5),
+    ("antara gandhara", 5, 4),
+    ("chyuta madhyama gandhara", 81, 64),
+    ("shuddha madhyama", 4, 3),
+    ("tivra shuddha madhyama", 27, 20),
+    ("prati madhyama", 45, 32),
+    ("chyuta panchama madhyama", 729, 512),
+    ("panchama", 3, 2),
+    ("ekashruti dhaivata", 128, 81),
+    ("dvishruti dhaivata", 8, 5),
+    ("trishruti dhaivata", 5, 3),
+    ("chatushruti dhaivata", 27, 16),
+    ("shuddha nishada", 16, 9),
+    ("kaisiki nishada", 9, 5),
+    ("kakali nishada", 15, 8),
+    ("chyuta shadja nishada", 243, 128),
+];
+
+/// The shruti each swarasthana takes, by semitone above S: R1 the
+/// ekashruti rishabha, R2 and G1 the chatushruti, R3 and G2 sadharana and so on
+const SHRUTI_OF: [usize; 12] = [0, 1, 4, 6, 7, 9, 11, 13, 14, 17, 19, 20];
+
+/// How swarasthanas are tuned against the tonic (`tuning:` in the metadata)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Temperament {
+    #[default]
+    Equal,
+    Just,
+    Shruti,
+}
+
+impl Temperament {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "equal" | "et" | "12-tet" | "equal temperament" => Some(Temperament::Equal),
+            "just" | "ji" | "just intonation" => Some(Temperament::Just),
+            "shruti" | "22-shruti" | "22 shruti" => Some(Temperament::Shruti),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Temperament::Equal => "equal temperament",
+            Temperament::Just => "just intonation",
+            Temperament::Shruti => "22 shrutis",
+        }
+    }
+
+    /// Ratio to S of a swarasthana, by semitone above S (0 to 11)
+    fn ratio(&self, semitone: usize) -> f64 {
+        match self {
+            Temperament::Equal => 2f64.powf(semitone as f64 / 12.0),
+            Temperament::Just => JUST[semitone].0 as f64 / JUST[semitone].1 as f64,
+            Temperament::Shruti => {
+                let (_, num, den) = SHRUTIS[SHRUTI_OF[semitone]];
+                num as f64 / den as f64
+            }
+        }
+    }
+}
+
+/// A document's tonic and temperament, turning notes into frequencies
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tuning {
+    /// Frequency of the middle octave's S
+    pub tonic_hz: f64,
+    pub temperament: Temperament,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            tonic_hz: key_hz(DEFAULT_KEY).unwrap_or_default(),
+            temperament: Temperament::Equal,
+        }
+    }
+}
+
+impl Tuning {
+    /// The metadata's `key` and `tuning`, falling back to C in equal
+    /// temperament for what is missing or can't be read
+    pub fn new(metadata: &Metadata) -> Self {
+        let default = Self::default();
+        Self {
+            tonic_hz: metadata.key.as_deref().and_then(key_hz).unwrap_or(default.tonic_hz),
+            temperament: metadata.tuning.as_deref().and_then(Temperament::parse).unwrap_or(default.temperament),
+        }
+    }
+
+    /// Ratio of a note to the middle octave's S, taking R2 G3 M1 D2 N3 where
+    /// the variant isn't known
+    pub fn ratio(&self, note: &Note) -> f64 {
+        let semitones = note.semitones();
+        self.temperament.ratio(semitones.rem_euclid(12) as usize) * 2f64.powi(semitones.div_euclid(12))
+    }
+
+    pub fn frequency(&self, note: &Note) -> f64 {
+        self.tonic_hz * self.ratio(note)
+    }
+
+    /// Cents of a note above the middle octave's S
+    pub fn cents(&self, note: &Note) -> f64 {
+        match self.temperament {
+            Temperament::Equal => note.semitones() as f64 * 100.0,
+            _ => 1200.0 * self.ratio(note).log2(),
+        }
+    }
+
+    /// Frequency of a pitch given in cents above the middle octave's S
+    pub fn cents_hz(&self, cents: f64) -> f64 {
+        self.tonic_hz * 2f64.powf(cents / 1200.0)
+    }
+
+    /// Name of the shruti a note takes, in the 22-shruti temperament
+    pub fn shruti(&self, note: &Note) -> Option<&'static str> {
+        (self.temperament == Temperament::Shruti).then(|| SHRUTIS[SHRUTI_OF[note.semitones().rem_euclid(12) as usize]].0)
+    }
+}
+
+/// Frequency of the tonic from a key: a pitch name (`C`, `F#`, `Bb2`), a
+/// kattai (`5 kattai`, `4½ kattai`, `4.5 kattai`) or a frequency (`138.6 Hz`)
+pub fn key_hz(key: &str) -> Option<f64> {
+    let key = key.trim().to_lowercase();
+    if let Some(hz) = key.strip_suffix("hz") {
+        return hz.trim().parse::<f64>().ok().filter(|hz| *hz > 0.0 && hz.is_finite());
+    }
+    if let Some(kattai) = key.strip_suffix("kattai") {
+        let kattai = kattai.trim();
+        let halves = match kattai.strip_suffix('½').or_else(|| kattai.strip_suffix(".5")) {
+            Some(whole) => whole.trim().parse::<u32>().ok()? * 2 + 1,
+            None => kattai.parse::<u32>().ok()? * 2,
+        };
+        let (_, pitch_class) = KATTAI.iter().find(|(h, _)| *h == halves)?;
+        return Some(pitch_hz(*pitch_class, DEFAULT_OCTAVE));
+    }
+
+    let mut chars = key.chars().peekable();
+    let mut pitch_class = match chars.next()? {
+        'c' => 0,
+        'd' => 2,
+        'e' => 4,
+        'f' => 5,
+        'g' => 7,
+        'a' => 9,
+        'b' => 11,
+        _ => return None,
+    };
+    match chars.peek() {
+        Some('#' | '♯') => pitch_class += 1,
+        Some('b' | '♭') => pitch_class -= 1,
+        _ => {}
+    }
+    if matches!(chars.peek(), Some('#' | '♯' | 'b' | '♭')) {
+        chars.next();
+    }
+    let rest: String = chars.collect();
+    let octave = if rest.is_empty() { DEFAULT_OCTAVE } else { rest.parse().ok()? };
+    Some(pitch_hz(pitch_class, octave))
+}
+
+/// Equal-tempered frequency of a pitch class (0 for C, may run past 0 to
+/// 11) in a scientific octave, from A4 at 440 Hz
+fn pitch_hz(pitch_class: i32, octave: i32) -> f64 {
+    let midi = 12 * (octave + 1) + pitch_class;
+    440.0 * 2f64.powf((midi - 69) as f64 / 12.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 0.01
+    }
+
+    #[test]
+    fn test_keys() {
+        assert!(close(key_hz("C").unwrap(), 130.81));
+        assert!(close(key_hz("A4").unwrap(), 440.0));
+        assert!(close(key_hz("F#").unwrap(), 185.0));
+        assert!(close(key_hz("Bb2").unwrap(), 116.54));
+        assert!(close(key_hz("C#3").unwrap(), key_hz("1½ kattai").unwrap()));
+        assert_eq!(key_hz("5 kattai"), key_hz("G"));
+        assert_eq!(key_hz("4.5 Kattai"), key_hz("F#3"));
+        assert_eq!(key_hz("138.6 Hz"), Some(138.6));
+
+        assert_eq!(key_hz("H"), None);
+        assert_eq!(key_hz("3½ kattai"), None);
+        assert_eq!(key_hz("-5 Hz"), None);
+    }
+
+    #[test]
+    fn test_temperaments() {
+        let note = |unit: &str| Note::parse(unit).unwrap();
+        let mut tuning = Tuning { tonic_hz: 200.0, temperament: Temperament::Equal };
+        assert!(close(tuning.frequency(&note("S'")), 400.0));
+        assert!(close(tuning.cents(&note("G3")), 400.0));
+        assert!(close(tuning.cents(&note("P.")), -500.0));
+        assert_eq!(tuning.shruti(&note("P")), None);
+
+        tuning.temperament = Temperament::Just;
+        assert!(close(tuning.frequency(&note("P")), 300.0));
+        assert!(close(tuning.frequency(&note("G")), 250.0));
+        assert!(close(tuning.frequency(&note("D2.")), 1000.0 / 6.0));
+
+        tuning.temperament = Temperament::Shruti;
+        assert!(close(tuning.frequency(&note("R1")), 200.0 * 256.0 / 243.0));
+        assert!(close(tuning.frequency(&note("D2")), 337.5));
+        assert_eq!(tuning.shruti(&note("N3")), Some("kakali nishada"));
+        // R3 and G2 are one swarasthana
+        assert_eq!(tuning.frequency(&note("R3")), tuning.frequency(&note("G2")));
+    }
+}
@@ -1,4 +1,7 @@
-use serde::{Deserialize, Serialize};
+use crate::ratio::Ratio;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
 
 /// Core VNA document structure
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -20,7 +23,9 @@ pub struct Metadata {
     pub composer: Option<String>,
     pub language: Option<String>,
     pub key: Option<String>,
+    pub tuning: Option<String>, // equal (default), just or shruti
     pub gati: Option<u8>,
+    pub eduppu: Option<Eduppu>,
     pub default_octave: Option<String>,
     pub arohanam: Option<String>,
     pub avarohanam: Option<String>,
@@ -30,11 +35,18 @@ pub struct Metadata {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Section {
     pub name: String,
+    pub variant: Option<String>, // Pathantara (school's version) of the section: [pallavi@semmangudi]
     pub phrases: Vec<Phrase>,
     pub line_number: usize,
     pub comments: Vec<Comment>,
     pub gati: Option<u8>, // Section-level gati override
     pub tala: Option<String>, // Section-level tala pattern override
+    pub eduppu: Option<Eduppu>, // Section-level start offset from samam
+    pub raga: Option<String>, // Section-level raga override (ragamalika)
+    pub tempo: Option<Tempo>, // Section-level tempo override
+    pub kalam: Option<u8>, // Section-level speed: 1, 2 or 3
+    pub swara_only: bool, // @swara-only: phrases are swara lines without sahitya
+    pub directives: Vec<(String, String)>, // Every @key: value directive in order; typed ones mark their place, their values being in their fields
 }
 
 /// A two-line notation group with optional phrase analysis
@@ -42,14 +54,272 @@ pub struct Section {
 pub struct Phrase {
     pub swaras: Vec<String>,
     pub sahitya: Vec<String>,
+    pub sahitya_lines: Vec<SahityaLine>, // Further verses, scripts and meanings, in file order
     pub phrase_analysis: Option<String>,
     pub line_number: usize,
     pub preceding_comments: Vec<Comment>,
     pub gati: Option<u8>, // Line-level gati override
     pub tala: Option<String>, // Line-level tala pattern override
+    pub raga: Option<String>, // Line-level raga override
+    pub tempo: Option<Tempo>, // Line-level tempo override
+    pub kalam: Option<u8>, // Line-level speed: 1, 2 or 3
+    pub sangati: Option<u32>, // Numbered variation of the line before it (@sangati: 2)
+    pub directives: Vec<(String, String)>, // Every @key: value directive in order; typed ones mark their place, their values being in their fields
     pub beat_positions: Vec<usize>, // Positions of | markers (not including final ||)
 }
 
+impl Section {
+    /// The name as written in the header: `pallavi`, `pallavi@semmangudi`
+    pub fn header(&self) -> String {
+        match &self.variant {
+            Some(variant) => format!("{}@{}", self.name, variant),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// A further words line under a phrase's sahitya: another verse (`sahitya[2]:`),
+/// the words in another script (`sahitya[te]:`) or a translation (`meaning:`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SahityaLine {
+    pub kind: SahityaLineKind,
+    pub label: Option<String>, // Text in brackets: a verse number, script or language
+    pub syllables: Vec<String>, // Tokens aligned with the swaras; empty for free text
+    pub text: Option<String>, // Free text, for a meaning written without beat markers
+    pub line_number: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SahityaLineKind {
+    Sahitya,
+    Meaning,
+}
+
+impl SahityaLineKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SahityaLineKind::Sahitya => "sahitya",
+            SahityaLineKind::Meaning => "meaning",
+        }
+    }
+}
+
+impl SahityaLine {
+    /// How the line is introduced, without the colon: `sahitya[2]`, `meaning`
+    pub fn tag(&self) -> String {
+        match &self.label {
+            Some(label) => format!("{}[{}]", self.kind.name(), label),
+            None => self.kind.name().to_string(),
+        }
+    }
+
+    /// Whether the line is written against the swaras' beat markers
+    pub fn is_aligned(&self) -> bool {
+        self.text.is_none()
+    }
+
+    /// Sung words, which must fill the swaras' units like the main sahitya
+    pub fn is_sung(&self) -> bool {
+        self.kind == SahityaLineKind::Sahitya
+    }
+
+    /// The verse number of a `sahitya[2]` line
+    pub fn verse(&self) -> Option<u32> {
+        self.label.as_deref().filter(|_| self.is_sung())?.parse().ok()
+    }
+}
+
+/// An ornament written on a swara: a kampita (`G~`), a jaru into the next
+/// note (`G/`, `G\`) or an anuswaram grace note before it (`{R}G`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Gamaka {
+    Kampita,
+    Jaru(Slide),
+    Anuswaram(String), // The grace note as written: R, S'
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Slide {
+    Up,
+    Down,
+}
+
+impl Gamaka {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Gamaka::Kampita => "kampita",
+            Gamaka::Jaru(_) => "jaru",
+            Gamaka::Anuswaram(_) => "anuswaram",
+        }
+    }
+
+    /// The marker as written in a swara token
+    pub fn marker(&self) -> String {
+        match self {
+            Gamaka::Kampita => "~".to_string(),
+            Gamaka::Jaru(Slide::Up) => "/".to_string(),
+            Gamaka::Jaru(Slide::Down) => "\\".to_string(),
+            Gamaka::Anuswaram(note) => format!("{{{}}}", note),
+        }
+    }
+}
+
+/// Where a section starts relative to samam, in aksharas
+/// `1/2` starts half an akshara after samam (anagata), `-1/2` half an akshara
+/// before it (atita); decimals such as `0.75` are read exactly
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Eduppu {
+    pub aksharas: Ratio,
+    pub before_samam: bool,
+}
+
+impl Eduppu {
+    pub const SAMAM: Eduppu = Eduppu { aksharas: Ratio::ZERO, before_samam: false };
+
+    /// Aksharas from the preceding samam to the start, within an avartanam of `cycle` aksharas
+    pub fn offset_in(&self, cycle: u64) -> Ratio {
+        let cycle = Ratio::from(cycle);
+        let aksharas = self.aksharas % cycle;
+        if self.before_samam && !aksharas.is_zero() {
+            cycle - aksharas
+        } else {
+            aksharas
+        }
+    }
+}
+
+impl FromStr for Eduppu {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let (before_samam, value) = match text.strip_prefix('-') {
+            Some(value) => (true, value.trim()),
+            None => (false, text.strip_prefix('+').unwrap_or(text).trim()),
+        };
+        let aksharas = match value.split_once('.') {
+            Some((whole, fraction)) if fraction.len() <= 6 && fraction.chars().all(|c| c.is_ascii_digit()) => {
+                let scale = 10u64.pow(fraction.len() as u32);
+                let whole = if whole.is_empty() { 0 } else { whole.parse::<u64>().map_err(|_| invalid_eduppu(text))? };
+                let fraction = if fraction.is_empty() { 0 } else { fraction.parse::<u64>().map_err(|_| invalid_eduppu(text))? };
+                Ratio::new(whole * scale + fraction, scale)
+            }
+            Some(_) => return Err(invalid_eduppu(text)),
+            None => value.parse::<Ratio>().map_err(|_| invalid_eduppu(text))?,
+        };
+        Ok(Eduppu {
+            aksharas,
+            before_samam: before_samam && !aksharas.is_zero(),
+        })
+    }
+}
+
+fn invalid_eduppu(text: &str) -> String {
+    format!("Invalid eduppu '{}': expected aksharas such as 1/2, 0.75 or -1/4", text)
+}
+
+impl fmt::Display for Eduppu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.before_samam {
+            write!(f, "-{}", self.aksharas)
+        } else {
+            write!(f, "{}", self.aksharas)
+        }
+    }
+}
+
+/// Written as text (`"1/2"`), since fractions aren't YAML or JSON numbers
+impl Serialize for Eduppu {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Eduppu {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Integer(i64),
+            Float(f64),
+            Text(String),
+        }
+
+        let text = match Raw::deserialize(deserializer)? {
+            Raw::Integer(value) => value.to_string(),
+            Raw::Float(value) => value.to_string(),
+            Raw::Text(value) => value,
+        };
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// A tempo in aksharas per minute, reached at once or gradually
+/// `80 over 2` moves from the tempo before it to 80 BPM across two avartanams
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tempo {
+    pub bpm: u32,
+    /// Avartanams taken to reach `bpm`
+    pub over: Option<u32>,
+}
+
+impl From<u32> for Tempo {
+    fn from(bpm: u32) -> Self {
+        Tempo { bpm, over: None }
+    }
+}
+
+impl FromStr for Tempo {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid tempo '{}': expected BPM such as 72, or 72 over 2 avartanams", text.trim());
+        let mut words = text.split_whitespace();
+        let bpm = words.next().and_then(|w| w.parse::<u32>().ok()).filter(|b| *b > 0).ok_or_else(invalid)?;
+        let over = match (words.next(), words.next(), words.next()) {
+            (None, _, _) => None,
+            (Some("over"), Some(count), None) => Some(count.parse::<u32>().ok().filter(|c| *c > 0).ok_or_else(invalid)?),
+            _ => return Err(invalid()),
+        };
+        Ok(Tempo { bpm, over })
+    }
+}
+
+impl fmt::Display for Tempo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.over {
+            Some(over) => write!(f, "{} over {}", self.bpm, over),
+            None => write!(f, "{}", self.bpm),
+        }
+    }
+}
+
+/// Written as text (`"80 over 2"`)
+impl Serialize for Tempo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Tempo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Integer(u32),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Integer(bpm) => Ok(Tempo::from(bpm)),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
 /// Comments and annotations
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Comment {
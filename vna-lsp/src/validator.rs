@@ -1,22 +1,55 @@
 use crate::types::*;
-use crate::sahitya_parser::parse_sahitya_token_with_lang;
+use crate::repeat;
+use crate::sahitya_parser::parse_sahitya_token_with_registry;
+use crate::swara_parser::{parse_swara_notes, parse_swara_units, split_token_gati};
+use crate::sahitya_scheme::{detect_document_scheme, detect_token_scheme, is_normalized, SahityaScheme};
+use crate::syllabifier::SyllabifierRegistry;
+use crate::ratio::Ratio;
+use crate::formatter::has_dash_only_sahitya;
+use crate::raga::RagaScales;
+use crate::variants::section_groups;
+use crate::script::Script;
+use crate::timeline::{default_octave, octave_name, parse_octave, tala_aksharas, Note, DEFAULT_GATI};
+use crate::tuning::{self, Temperament};
 use anyhow::Result;
 
 pub fn validate(document: &VnaDocument) -> Result<Vec<ValidationIssue>> {
-    let mut validator = VnaValidator::new();
+    validate_with(document, SyllabifierRegistry::shared())
+}
+
+/// Validate using a specific syllabifier registry (e.g. one with a project exception dictionary)
+pub fn validate_with(document: &VnaDocument, registry: &SyllabifierRegistry) -> Result<Vec<ValidationIssue>> {
+    let mut validator = VnaValidator::new(registry);
     validator.validate(document)
 }
 
-struct VnaValidator {
+struct VnaValidator<'a> {
     issues: Vec<ValidationIssue>,
     language: Option<String>,
+    scheme: SahityaScheme,
+    registry: &'a SyllabifierRegistry,
+    /// File-level tala and gati, for checking section eduppus
+    tala: String,
+    gati: u8,
+    /// File-level raga, and scales for checking swaras per section and line
+    raga: String,
+    scales: RagaScales,
+    /// Octave of unmarked swaras, from `default_octave`
+    octave: i8,
 }
 
-impl VnaValidator {
-    fn new() -> Self {
+impl<'a> VnaValidator<'a> {
+    fn new(registry: &'a SyllabifierRegistry) -> Self {
         Self {
             issues: Vec::new(),
             language: None,
+            scheme: SahityaScheme::Iso,
+            registry,
+            tala: String::new(),
+            gati: DEFAULT_GATI,
+            raga: String::new(),
+            scales: RagaScales::default(),
+            octave: 0,
         }
     }
 
@@ -24,38 +57,89 @@ impl VnaValidator {
         // Validate metadata and capture language
         self.validate_metadata(&document.metadata);
         self.language = document.metadata.language.clone();
+        self.scheme = detect_document_scheme(document);
+        self.tala = document.metadata.tala.clone();
+        self.gati = document.metadata.gati.filter(|g| *g > 0).unwrap_or(DEFAULT_GATI);
+        self.raga = document.metadata.raga.clone();
+        self.scales = RagaScales::new(&document.metadata);
+        self.octave = default_octave(&document.metadata);
+        if let Some(eduppu) = document.metadata.eduppu {
+            let tala = self.tala.clone();
+            self.validate_eduppu(eduppu, &tala, self.gati, 1);
+        }
 
         // Validate sections
         for section in &document.sections {
             self.validate_section(section);
         }
 
+        // Each pathantara needs the section it is a version of, once per school
+        for group in section_groups(document) {
+            let mut schools: Vec<&str> = Vec::new();
+            for section in group.pathantaras.iter().map(|&p| &document.sections[p]) {
+                let school = section.variant.as_deref().unwrap_or_default();
+                if group.base.is_none() {
+                    self.add_warning(
+                        section.line_number,
+                        format!("Section [{}] is a version of [{}], which the file doesn't have", section.header(), section.name),
+                        Some("pathantara_without_base".to_string())
+                    );
+                }
+                if schools.contains(&school) {
+                    self.add_warning(
+                        section.line_number,
+                        format!("Section [{}] repeats the {} version of [{}]", section.header(), school, section.name),
+                        Some("duplicate_pathantara".to_string())
+                    );
+                }
+                schools.push(school);
+            }
+        }
+
         Ok(self.issues.clone())
     }
 
     fn validate_metadata(&mut self, metadata: &Metadata) {
         // Check tempo range
         if let Some(tempo) = metadata.tempo {
-            if tempo < 20 || tempo > 300 {
-                self.add_warning(
-                    1, // Approximate line number for metadata
-                    format!("Unusual tempo: {} BPM (typical range: 20-300)", tempo),
-                    Some("unusual_tempo".to_string())
-                );
-            }
+            self.validate_tempo(tempo, 1); // Approximate line number for metadata
         }
 
         // Check gati value
         if let Some(gati) = metadata.gati {
-            if !matches!(gati, 3 | 4 | 5 | 7 | 9) {
+            if !is_typical_gati(gati) {
                 self.add_warning(
                     1,
-                    format!("Unusual gati value: {} (typical values: 3, 4, 5, 7, 9)", gati),
+                    format!("Unusual gati value: {} (typical values: 3, 4, 5, 7, 9, doubled or quadrupled in faster kalams)", gati),
                     Some("unusual_gati".to_string())
                 );
             }
         }
 
+        // Check the key and tuning can be read
+        if let Some(key) = metadata.key.as_deref().filter(|k| tuning::key_hz(k).is_none()) {
+            self.add_warning(
+                1,
+                format!("Unknown key '{}' (use a pitch like C or F#3, a kattai like 5 kattai, or a frequency like 138.6 Hz); tuning to {}", key, tuning::DEFAULT_KEY),
+                Some("invalid_key".to_string())
+            );
+        }
+        if let Some(name) = metadata.tuning.as_deref().filter(|t| Temperament::parse(t).is_none()) {
+            self.add_warning(
+                1,
+                format!("Unknown tuning '{}' (equal, just or shruti); using equal temperament", name),
+                Some("invalid_tuning".to_string())
+            );
+        }
+
+        if let Some(octave) = metadata.default_octave.as_deref().filter(|o| parse_octave(o).is_none()) {
+            self.add_warning(
+                1,
+                format!("Unknown default octave '{}' (anumandra, mandra, madhya, tara or atitara); using madhya", octave),
+                Some("invalid_default_octave".to_string())
+            );
+        }
+
         // Check for empty required fields (already handled by parser, but double-check)
         if metadata.title.trim().is_empty() {
             self.add_error(1, "Title cannot be empty".to_string(), Some("empty_title".to_string()));
@@ -83,10 +167,10 @@ impl VnaValidator {
 
         // Check section-level gati if present
         if let Some(gati) = section.gati {
-            if !matches!(gati, 3 | 4 | 5 | 7 | 9) {
+            if !is_typical_gati(gati) {
                 self.add_warning(
                     section.line_number,
-                    format!("Unusual gati value: {} (typical values: 3, 4, 5, 7, 9)", gati),
+                    format!("Unusual gati value: {} (typical values: 3, 4, 5, 7, 9, doubled or quadrupled in faster kalams)", gati),
                     Some("unusual_gati".to_string())
                 );
             }
@@ -97,19 +181,60 @@ impl VnaValidator {
             self.validate_tala_pattern(tala, section.line_number);
         }
 
+        // Check section-level tempo if present
+        if let Some(tempo) = section.tempo {
+            self.validate_tempo(tempo.bpm, section.line_number);
+        }
+
+        // Check section-level eduppu against the section's tala and gati
+        if let Some(eduppu) = section.eduppu {
+            let tala = section.tala.clone().unwrap_or_else(|| self.tala.clone());
+            let gati = section.gati.filter(|g| *g > 0).unwrap_or(self.gati);
+            self.validate_eduppu(eduppu, &tala, gati, section.line_number);
+        }
+
+        // Sahitya lines there only to pair with the swaras
+        if has_dash_only_sahitya(section) {
+            self.add_info(
+                section.line_number,
+                format!(
+                    "Every sahitya line in '{}' is only dashes; mark the section @swara-only and drop them (vna format --swara-only)",
+                    section.name
+                ),
+                Some("dash_only_sahitya".to_string())
+            );
+        }
+
+        // A ragamalika's first @raga reads as the section's, not the first line's
+        if let Some(raga) = &section.raga {
+            let own = section.phrases.iter().position(|p| p.raga.is_some());
+            if own.is_some_and(|i| i > 0) {
+                self.add_warning(
+                    section.line_number,
+                    format!(
+                        "@raga: \"{}\" before the first line sets the raga of all of '{}', not only its first line; \
+                         for the first line alone, give the section's @raga first and the line's after it",
+                        raga, section.name
+                    ),
+                    Some("section_raga_scope".to_string())
+                );
+            }
+        }
+
         // Check phrases
+        let raga = section.raga.clone().unwrap_or_else(|| self.raga.clone());
         for phrase in &section.phrases {
-            self.validate_phrase(phrase);
+            self.validate_phrase(phrase, &raga, section.swara_only);
         }
     }
 
-    fn validate_phrase(&mut self, phrase: &Phrase) {
+    fn validate_phrase(&mut self, phrase: &Phrase, section_raga: &str, swara_only: bool) {
         // Check line-level gati if present
         if let Some(gati) = phrase.gati {
-            if !matches!(gati, 3 | 4 | 5 | 7 | 9) {
+            if !is_typical_gati(gati) {
                 self.add_warning(
                     phrase.line_number,
-                    format!("Unusual gati value: {} (typical values: 3, 4, 5, 7, 9)", gati),
+                    format!("Unusual gati value: {} (typical values: 3, 4, 5, 7, 9, doubled or quadrupled in faster kalams)", gati),
                     Some("unusual_gati".to_string())
                 );
             }
@@ -120,6 +245,21 @@ impl VnaValidator {
             self.validate_tala_pattern(tala, phrase.line_number);
         }
 
+        // Check line-level tempo if present
+        if let Some(tempo) = phrase.tempo {
+            self.validate_tempo(tempo.bpm, phrase.line_number);
+        }
+
+        // Unit checks run on the written-out form of any repeat groups
+        let phrase = match repeat::expand_phrase(phrase) {
+            Ok(expanded) => expanded,
+            Err(e) => {
+                self.add_error(phrase.line_number, e.to_string(), Some("invalid_repeat".to_string()));
+                return;
+            }
+        };
+        let phrase = &phrase;
+
         // Check that required lines have elements
         if phrase.swaras.is_empty() {
             self.add_error(
@@ -129,7 +269,7 @@ impl VnaValidator {
             );
         }
         
-        if phrase.sahitya.is_empty() {
+        if phrase.sahitya.is_empty() && !swara_only {
             self.add_error(
                 phrase.line_number + 1,
                 "Sahitya line cannot be empty".to_string(),
@@ -137,72 +277,95 @@ impl VnaValidator {
             );
         }
         
-        // Check token count consistency
-        let swara_count = phrase.swaras.len();
-        let sahitya_count = phrase.sahitya.len();
-
-        if swara_count != sahitya_count {
-            self.add_error(
-                phrase.line_number + 1,
-                format!(
-                    "Token count mismatch: swara line has {} tokens, sahitya line has {}",
-                    swara_count, sahitya_count
-                ),
-                Some("token_count_mismatch".to_string())
-            );
-            return; // Skip further checks if counts don't match
-        }
-
-        // Check token length matching (strict rule)
-        for (i, (swara, sahitya)) in phrase.swaras.iter().zip(phrase.sahitya.iter()).enumerate() {
-            // Parse token-level gati notation if present (e.g., SRG:3)
-            let (swara_text, swara_gati) = if let Some(colon_pos) = swara.find(':') {
-                let text = &swara[..colon_pos];
-                let gati_str = &swara[colon_pos + 1..];
+        // Check token-level gati notation (e.g., SRG:3)
+        for swara in &phrase.swaras {
+            let (_, gati_str) = split_token_gati(swara);
+            if let Some(gati_str) = gati_str {
                 if let Ok(gati) = gati_str.parse::<u8>() {
-                    if !matches!(gati, 3 | 4 | 5 | 7 | 9) {
+                    if !is_typical_gati(gati) {
                         self.add_warning(
                             phrase.line_number,
-                            format!("Unusual gati value in token '{}': {} (typical values: 3, 4, 5, 7, 9)", swara, gati),
+                            format!("Unusual gati value in token '{}': {} (typical values: 3, 4, 5, 7, 9, doubled or quadrupled in faster kalams)", swara, gati),
                             Some("unusual_token_gati".to_string())
                         );
                     }
-                    (text, Some(gati))
                 } else {
                     self.add_error(
                         phrase.line_number,
                         format!("Invalid gati notation in token '{}': expected number after colon", swara),
                         Some("invalid_token_gati".to_string())
                     );
-                    (text, None)
                 }
-            } else {
-                (swara.as_str(), None)
-            };
-
-            // Parse swara and sahitya into units
-            let swara_units = self.parse_swara_units(swara_text);
-            let sahitya_units = parse_sahitya_token_with_lang(sahitya, self.language.as_deref());
-            
-            if swara_units.len() != sahitya_units.len() {
-                self.add_error(
-                    phrase.line_number + 1,
-                    format!(
-                        "Token unit mismatch at position {}: swara '{}' ({} units) vs sahitya '{}' ({} units)",
-                        i + 1, swara_text, swara_units.len(), sahitya, sahitya_units.len()
-                    ),
-                    Some("token_unit_mismatch".to_string())
+            }
+        }
+
+        // Gamaka markers must sit on notes
+        for swara in &phrase.swaras {
+            if let Err(e) = parse_swara_notes(split_token_gati(swara).0) {
+                self.add_error(phrase.line_number, e.to_string(), Some("invalid_gamaka".to_string()));
+            }
+        }
+
+        // Octave markers count from the default octave, and notes stay within
+        // two octaves of the middle one
+        for swara in &phrase.swaras {
+            let far = parse_swara_units(split_token_gati(swara).0)
+                .into_iter()
+                .find(|unit| Note::parse(unit).is_some_and(|note| (note.octave + self.octave).abs() > 2));
+            if let Some(unit) = far {
+                let octave = octave_name(self.octave).unwrap_or_default();
+                self.add_warning(
+                    phrase.line_number,
+                    format!("'{}' in token '{}' is more than two octaves from madhya S (unmarked swaras are {})", unit, swara, octave),
+                    Some("octave_out_of_range".to_string())
                 );
             }
         }
 
+        // Further verses and scripts are sung too, so each must fill the swaras' units
+        for extra in phrase.sahitya_lines.iter().filter(|l| l.is_sung()) {
+            let scheme = self.line_scheme(extra);
+            if self.check_units(&phrase.swaras, &extra.syllables, &extra.tag(), extra.line_number, scheme) {
+                self.check_scheme(&extra.syllables, extra.line_number, scheme, "line");
+            }
+        }
+
+        if !swara_only && !self.check_units(&phrase.swaras, &phrase.sahitya, "sahitya", phrase.line_number + 1, self.scheme) {
+            return; // Skip further checks if counts don't match
+        }
+
+        // Check swaras against the raga in effect, when its scale is known
+        let raga = phrase.raga.as_deref().unwrap_or(section_raga);
+        if let Some(scale) = self.scales.get(raga) {
+            for (i, swara) in phrase.swaras.iter().enumerate() {
+                let (swara_text, _) = split_token_gati(swara);
+                let outside = parse_swara_units(swara_text)
+                    .into_iter()
+                    .find(|unit| Note::parse(unit).is_some_and(|note| !scale.contains(&note)));
+                if let Some(unit) = outside {
+                    self.add_warning(
+                        phrase.line_number,
+                        format!(
+                            "Swara '{}' at position {} is not in {} ({})",
+                            unit, i + 1, raga, scale
+                        ),
+                        Some("swara_outside_raga".to_string())
+                    );
+                }
+            }
+        }
+
+        // Check sahitya transliteration consistency
+        self.check_scheme(&phrase.sahitya, phrase.line_number + 1, self.scheme, "document");
+
         // Validate phrase analysis if present
         if let Some(analysis) = &phrase.phrase_analysis {
+            let analysis_line = phrase.line_number + if swara_only { 1 } else { 2 };
             // Basic validation: check for invalid characters
             for (i, ch) in analysis.chars().enumerate() {
                 if !matches!(ch, '_' | '*' | '(' | ')' | ' ') {
                     self.add_warning(
-                        phrase.line_number + 2,
+                        analysis_line,
                         format!(
                             "Invalid character '{}' in phrase analysis at position {}",
                             ch, i + 1
@@ -228,6 +391,93 @@ impl VnaValidator {
         }
     }
 
+    /// Check a words line against the swara line token by token (strict rule)
+    /// Returns false when the token counts differ, as nothing else lines up then
+    fn check_units(&mut self, swaras: &[String], words: &[String], name: &str, line: usize, scheme: SahityaScheme) -> bool {
+        if swaras.len() != words.len() {
+            self.add_error(
+                line,
+                format!(
+                    "Token count mismatch: swara line has {} tokens, {} line has {}",
+                    swaras.len(), name, words.len()
+                ),
+                Some("token_count_mismatch".to_string())
+            );
+            return false;
+        }
+
+        for (i, (swara, sahitya)) in swaras.iter().zip(words).enumerate() {
+            let (swara_text, _) = split_token_gati(swara);
+            let swara_units = parse_swara_units(swara_text);
+            let sahitya_units = parse_sahitya_token_with_registry(
+                sahitya,
+                self.registry,
+                self.language.as_deref(),
+                scheme,
+            );
+
+            if swara_units.len() != sahitya_units.len() {
+                self.add_error(
+                    line,
+                    format!(
+                        "Token unit mismatch at position {}: swara '{}' ({} units) vs {} '{}' ({} units)",
+                        i + 1, swara_text, swara_units.len(), name, sahitya, sahitya_units.len()
+                    ),
+                    Some("token_unit_mismatch".to_string())
+                );
+            }
+        }
+        true
+    }
+
+    /// Check that a words line keeps to the transliteration of `whole` (the
+    /// document or the line itself), in composed form
+    fn check_scheme(&mut self, words: &[String], line: usize, expected: SahityaScheme, whole: &str) {
+        for (i, sahitya) in words.iter().enumerate() {
+            if let Some(scheme) = detect_token_scheme(sahitya) {
+                if scheme != expected {
+                    self.add_warning(
+                        line,
+                        format!(
+                            "Sahitya '{}' at position {} looks like {} but the {} is mostly {}",
+                            sahitya,
+                            i + 1,
+                            scheme.name(),
+                            whole,
+                            expected.name()
+                        ),
+                        Some("mixed_sahitya_scheme".to_string())
+                    );
+                }
+            }
+
+            if !is_normalized(sahitya) {
+                self.add_warning(
+                    line,
+                    format!(
+                        "Sahitya '{}' at position {} uses decomposed diacritics (not NFC)",
+                        sahitya, i + 1
+                    ),
+                    Some("sahitya_not_nfc".to_string())
+                );
+            }
+        }
+    }
+
+    /// The scheme a further words line is written in: a verse follows the
+    /// document, a line labelled with a script or language uses that script,
+    /// anything else is read as it looks
+    fn line_scheme(&self, line: &SahityaLine) -> SahityaScheme {
+        if line.verse().is_some() {
+            return self.scheme;
+        }
+        let label = line.label.as_deref().unwrap_or_default();
+        SahityaScheme::from_name(label)
+            .or_else(|| Script::for_language(label).map(SahityaScheme::Native))
+            .or_else(|| line.syllables.iter().find_map(|s| detect_token_scheme(s)))
+            .unwrap_or(self.scheme)
+    }
+
     fn add_error(&mut self, line: usize, message: String, code: Option<String>) {
         self.issues.push(ValidationIssue {
             severity: Severity::Error,
@@ -261,6 +511,38 @@ impl VnaValidator {
         });
     }
     
+    /// A tempo outside the usual range is likely a typo
+    fn validate_tempo(&mut self, tempo: u32, line: usize) {
+        if !(20..=300).contains(&tempo) {
+            self.add_warning(
+                line,
+                format!("Unusual tempo: {} BPM (typical range: 20-300)", tempo),
+                Some("unusual_tempo".to_string())
+            );
+        }
+    }
+
+    /// An eduppu should fall within one avartanam, on a unit of the gati
+    fn validate_eduppu(&mut self, eduppu: Eduppu, tala: &str, gati: u8, line: usize) {
+        if let Some(cycle) = tala_aksharas(tala) {
+            if eduppu.aksharas >= Ratio::from(cycle) {
+                self.add_warning(
+                    line,
+                    format!("Eduppu {} is a whole avartanam ({} aksharas) or more from samam", eduppu, cycle),
+                    Some("eduppu_out_of_range".to_string())
+                );
+            }
+        }
+
+        if !(eduppu.aksharas * Ratio::from(gati as u64)).is_integer() {
+            self.add_warning(
+                line,
+                format!("Eduppu {} doesn't fall on a unit of gati {}", eduppu, gati),
+                Some("eduppu_off_grid".to_string())
+            );
+        }
+    }
+
     fn validate_tala_pattern(&mut self, pattern: &str, line: usize) {
         // Validate tala pattern format
         for (i, ch) in pattern.chars().enumerate() {
@@ -281,7 +563,7 @@ impl VnaValidator {
         }
         
         // Check for common tala patterns
-        let known_patterns = vec![
+        let known_patterns = [
             ("+234+0+0", "Adi"),
             ("0++234", "Rupaka"),
             ("+230+00", "Misra Chapu"),
@@ -305,48 +587,14 @@ impl VnaValidator {
             );
         }
     }
-    
-    /// Parse swara token into individual units
-    /// Each note counts as one unit, including octave markers
-    fn parse_swara_units(&self, token: &str) -> Vec<String> {
-        let mut units = Vec::new();
-        let mut chars = token.chars().peekable();
-        
-        while let Some(ch) = chars.next() {
-            if ch == ',' {
-                // Comma is a sustain marker, counts as one unit
-                units.push(",".to_string());
-            } else if ch == '-' {
-                // Dash is a rest marker, counts as one unit
-                units.push("-".to_string());
-            } else if ['S', 'R', 'G', 'M', 'P', 'D', 'N'].contains(&ch) {
-                // Swara note
-                let mut note = String::from(ch);
-                
-                // Check for variant (1, 2, 3)
-                if let Some(&next_ch) = chars.peek() {
-                    if ['1', '2', '3'].contains(&next_ch) {
-                        note.push(chars.next().unwrap());
-                    }
-                }
-                
-                // Check for octave markers (., ')
-                while let Some(&next_ch) = chars.peek() {
-                    if next_ch == '.' || next_ch == '\'' {
-                        note.push(chars.next().unwrap());
-                    } else {
-                        break;
-                    }
-                }
-                
-                units.push(note);
-            }
-        }
-        
-        units
-    }
 }
 
+/// The five gatis, in first, second or third speed (1×, 2×, 4×)
+fn is_typical_gati(gati: u8) -> bool {
+    [1, 2, 4]
+        .iter()
+        .any(|kalam| gati.is_multiple_of(*kalam) && matches!(gati / kalam, 3 | 4 | 5 | 7 | 9))
+}
 
 #[cfg(test)]
 mod tests {
@@ -444,4 +692,290 @@ nin ||
         let warnings: Vec<_> = issues.iter().filter(|i| i.severity == Severity::Warning).collect();
         assert!(!warnings.is_empty());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_key_and_tuning_checks() {
+        let content = |key: &str, tuning: &str| format!(
+            "---\ntitle: \"Test\"\nraga: \"mohanam\"\ntala: \"+234+0+0\"\nkey: \"{}\"\ntuning: \"{}\"\n---\n\n[pallavi]\nG ||\nnin ||\n",
+            key, tuning
+        );
+        let codes = |key: &str, tuning: &str| -> Vec<String> {
+            let doc = parse(&content(key, tuning)).unwrap();
+            validate(&doc).unwrap().into_iter().filter_map(|i| i.code).filter(|c| c.starts_with("invalid_")).collect()
+        };
+
+        assert!(codes("4½ kattai", "shruti").is_empty());
+        assert!(codes("138.6 Hz", "just").is_empty());
+        assert_eq!(codes("Sa", "pythagorean"), vec!["invalid_key", "invalid_tuning"]);
+    }
+
+    #[test]
+    fn test_default_octave_checks() {
+        let content = |octave: &str| format!(
+            "---\ntitle: \"Test\"\nraga: \"mohanam\"\ntala: \"+234+0+0\"\ndefault_octave: \"{}\"\n---\n\n[pallavi]\nS..R. S'P'' ||\nni- -- ||\n",
+            octave
+        );
+        let codes = |octave: &str| -> Vec<(usize, String)> {
+            let doc = parse(&content(octave)).unwrap();
+            validate(&doc).unwrap().into_iter().filter(|i| i.severity != Severity::Info).map(|i| (i.line, i.code.unwrap())).collect()
+        };
+
+        assert!(codes("madhya").is_empty());
+        // Two below mandra is too low, two above it fine
+        assert_eq!(codes("mandra"), vec![(9, "octave_out_of_range".to_string())]);
+        assert_eq!(codes("tara"), vec![(9, "octave_out_of_range".to_string())]);
+        assert_eq!(codes("bass"), vec![(1, "invalid_default_octave".to_string())]);
+    }
+
+    #[test]
+    fn test_raga_checks() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+SRGP DS'DP | GRSR GRGP ||
+---- ---- | ---- ---- ||
+
+@raga: "kalyani"
+SRGM PDNS' | S'NDP MGRS ||
+---- ---- | ---- ---- ||
+
+[charanam]
+@raga: "hindolam"
+SGMD NS'ND | MGSN. SGMD ||
+---- ---- | ---- ---- ||
+
+SRGM PDNS' | S'ND, MGR1S ||
+---- ---- | ---- ---- ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let issues = validate(&doc).unwrap();
+        assert!(issues.iter().all(|i| i.severity != Severity::Error), "{:?}", issues);
+        let outside: Vec<_> = issues
+            .iter()
+            .filter(|i| i.code.as_deref() == Some("swara_outside_raga"))
+            .collect();
+
+        // Only the last line leaves hindolam (its R, P and written R1); the
+        // kalyani line has its own raga
+        assert_eq!(outside.len(), 3, "{:?}", outside);
+        assert!(outside.iter().all(|i| i.line == 20));
+        assert_eq!(outside[0].message, "Swara 'R' at position 1 is not in hindolam (S G2 M1 D1 N2)");
+        assert!(outside[2].message.contains("'R1'"));
+        assert!(issues.iter().all(|i| i.code.as_deref() != Some("section_raga_scope")));
+
+        // A later line's own raga makes the first one look meant for its line
+        let ragamalika = content.replace("SRGM PDNS' | S'ND,", "@raga: \"kalyani\"\nSRGM PDNS' | S'ND,");
+        let issues = validate(&parse(&ragamalika).unwrap()).unwrap();
+        let scope: Vec<_> = issues.iter().filter(|i| i.code.as_deref() == Some("section_raga_scope")).collect();
+        assert_eq!(scope.len(), 1, "{:?}", issues);
+        assert_eq!(scope[0].line, 15);
+
+        // Stating the section's raga and then the first line's is unambiguous
+        let explicit = ragamalika.replace("@raga: \"hindolam\"\n", "@raga: \"mohanam\"\n@raga: \"hindolam\"\n");
+        let doc = parse(&explicit).unwrap();
+        assert_eq!(doc.sections[1].phrases[0].raga.as_deref(), Some("hindolam"));
+        let issues = validate(&doc).unwrap();
+        assert!(issues.iter().all(|i| i.code.as_deref() != Some("section_raga_scope")), "{:?}", issues);
+    }
+
+    #[test]
+    fn test_eduppu_checks() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+eduppu: 9
+---
+
+[pallavi]
+@gati: 4
+@eduppu: 1/3
+G ||
+nin ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let codes: Vec<_> = validate(&doc).unwrap().into_iter().filter_map(|i| i.code).collect();
+        assert!(codes.contains(&"eduppu_out_of_range".to_string()));
+        assert!(codes.contains(&"eduppu_off_grid".to_string()));
+
+        let doc = parse(&content.replace("eduppu: 9", "eduppu: 1/2").replace("1/3", "3/4")).unwrap();
+        assert!(validate(&doc).unwrap().iter().all(|i| !i.code.as_deref().unwrap_or("").starts_with("eduppu")));
+    }
+
+    #[test]
+    fn test_mixed_sahitya_scheme() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+G, R, | S, ||
+nā- kō- | DECOMPOSED- ||
+
+[anupallavi]
+G, ||
+kO- ||
+"#;
+        let content = content.replace("DECOMPOSED", "ko\u{304}");
+
+        let doc = parse(&content).unwrap();
+        let issues = validate(&doc).unwrap();
+
+        let codes: Vec<_> = issues.iter().filter_map(|i| i.code.as_deref()).collect();
+        assert!(codes.contains(&"mixed_sahitya_scheme"), "Issues: {:?}", issues);
+        assert!(codes.contains(&"sahitya_not_nfc"), "Issues: {:?}", issues);
+
+        // HK sahitya still syllabifies correctly despite the mismatch
+        let errors: Vec<_> = issues.iter().filter(|i| i.severity == Severity::Error).collect();
+        assert!(errors.is_empty(), "Got errors: {:?}", errors);
+    }
+
+    #[test]
+    fn test_sahitya_lines_unit_checks() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[charanam]
+G,RG | RSR, ||
+ma--- | hi--- ||
+sahitya[2]: sā--- | mi-- ||
+sahitya[te]: మ--- | హి--- ||
+meaning: great | one ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let issues = validate(&doc).unwrap();
+        let errors: Vec<_> = issues.iter().filter(|i| i.severity == Severity::Error).collect();
+
+        // Only the verse is short of units; the Telugu line is read as Telugu and meanings aren't sung
+        assert_eq!(errors.len(), 1, "Issues: {:?}", issues);
+        assert_eq!(errors[0].line, 10);
+        assert!(errors[0].message.contains("sahitya[2] 'mi--' (3 units)"), "{}", errors[0].message);
+        assert!(issues.iter().all(|i| i.code.as_deref() != Some("mixed_sahitya_scheme")), "Issues: {:?}", issues);
+    }
+
+    #[test]
+    fn test_swara_only_sections() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[muktayisvaram]
+@swara-only
+G,RG RSR, | SRSG RGSR ||
+
+[cittasvaram]
+G,RG RSR, | SRSG RGSR ||
+---- ---- | ---- ---- ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let issues = validate(&doc).unwrap();
+        assert!(issues.iter().all(|i| i.severity != Severity::Error), "Issues: {:?}", issues);
+
+        // The dashes-only section is pointed at @swara-only
+        let hints: Vec<_> = issues.iter().filter(|i| i.code.as_deref() == Some("dash_only_sahitya")).collect();
+        assert_eq!(hints.len(), 1, "Issues: {:?}", issues);
+        assert_eq!(hints[0].line, 11);
+    }
+
+    #[test]
+    fn test_pathantara_checks() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+G,G, R,,, ||
+nin- kō-- ||
+
+[pallavi@semmangudi]
+G,G, RSR, ||
+nin- kō-- ||
+
+[pallavi@semmangudi]
+G,G, RSR, ||
+nin- kō-- ||
+
+[charanam@semmangudi]
+S,,, R,,, ||
+sa-- ---- ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let issues = validate(&doc).unwrap();
+        let warnings: Vec<_> = issues
+            .iter()
+            .filter(|i| i.code.as_deref().is_some_and(|c| c.contains("pathantara")))
+            .map(|i| (i.line, i.code.as_deref().unwrap()))
+            .collect();
+        assert_eq!(warnings, vec![(15, "duplicate_pathantara"), (19, "pathantara_without_base")]);
+    }
+
+    #[test]
+    fn test_gamaka_checks() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+{R}G~,,, P/,,, ||
+ni---    kō--- ||
+
+,~PP G,,, ||
+ni-- ---- ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let issues = validate(&doc).unwrap();
+        // Markers aren't units, so only the misplaced kampita is an error
+        let errors: Vec<_> = issues.iter().filter(|i| i.severity == Severity::Error).collect();
+        assert_eq!(errors.len(), 1, "Issues: {:?}", issues);
+        assert_eq!(errors[0].code.as_deref(), Some("invalid_gamaka"));
+        assert_eq!(errors[0].line, 11);
+    }
+
+    #[test]
+    fn test_exception_dictionary() {
+        let content = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+language: "telugu"
+---
+
+[pallavi]
+G, R, ||
+nin- nu- ||
+"#;
+
+        let doc = parse(content).unwrap();
+        let error_count = |issues: Vec<ValidationIssue>| {
+            issues.iter().filter(|i| i.severity == Severity::Error).count()
+        };
+
+        // Automatic splitting gives "nin" → ["ni", "n"], one unit too many
+        assert_eq!(error_count(validate(&doc).unwrap()), 1);
+
+        let exceptions = crate::syllabifier::ExceptionDictionary::parse("nin: \"nin\"\n").unwrap();
+        let registry = SyllabifierRegistry::default().with_exceptions(exceptions);
+        assert_eq!(error_count(validate_with(&doc, &registry).unwrap()), 0);
+    }
+}
@@ -0,0 +1,200 @@
+use crate::types::*;
+use anyhow::{anyhow, Result};
+
+/// A section together with its pathantaras, other schools' versions of it
+/// (`[pallavi@semmangudi]`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionGroup {
+    pub name: String,
+    /// Index of the section as written without a variant, if the file has it
+    pub base: Option<usize>,
+    /// Indices of the named versions, in file order
+    pub pathantaras: Vec<usize>,
+}
+
+impl SectionGroup {
+    /// Where the group first appears in the file
+    fn first(&self) -> usize {
+        self.base.into_iter().chain(self.pathantaras.iter().copied()).min().unwrap_or(0)
+    }
+}
+
+/// A line together with its sangatis (`@sangati: 2`, `@sangati: 3`, ...)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SangatiGroup {
+    /// Indices of the phrases: the line, then each sangati in order
+    pub phrases: Vec<usize>,
+}
+
+/// Sections grouped with their pathantaras
+///
+/// A pathantara belongs to the closest section of its name before it, or
+/// after it when there is none before. Pathantaras of a section the file
+/// doesn't have form groups without a base, after the others.
+pub fn section_groups(document: &VnaDocument) -> Vec<SectionGroup> {
+    let sections = &document.sections;
+    let mut groups: Vec<SectionGroup> = Vec::new();
+    let mut pending: Vec<usize> = Vec::new();
+
+    for (index, section) in sections.iter().enumerate() {
+        if section.variant.is_none() {
+            let (pathantaras, rest) = pending.into_iter().partition(|&p| sections[p].name == section.name);
+            pending = rest;
+            groups.push(SectionGroup { name: section.name.clone(), base: Some(index), pathantaras });
+            continue;
+        }
+        match groups.iter_mut().rev().find(|g| g.name == section.name) {
+            Some(group) => group.pathantaras.push(index),
+            None => pending.push(index),
+        }
+    }
+
+    for index in pending {
+        let name = &sections[index].name;
+        match groups.iter_mut().find(|g| g.base.is_none() && &g.name == name) {
+            Some(group) => group.pathantaras.push(index),
+            None => groups.push(SectionGroup { name: name.clone(), base: None, pathantaras: vec![index] }),
+        }
+    }
+    groups
+}
+
+/// Lines of a section that have sangatis, each with its sangatis
+pub fn sangati_groups(section: &Section) -> Vec<SangatiGroup> {
+    let mut groups: Vec<SangatiGroup> = Vec::new();
+    for (index, phrase) in section.phrases.iter().enumerate() {
+        match (phrase.sangati, groups.last_mut()) {
+            (Some(n), Some(group)) if n > 1 => group.phrases.push(index),
+            _ => groups.push(SangatiGroup { phrases: vec![index] }),
+        }
+    }
+    groups.retain(|g| g.phrases.len() > 1);
+    groups
+}
+
+/// Every school named in a pathantara, in order of first appearance
+pub fn variant_names(document: &VnaDocument) -> Vec<&str> {
+    let mut names: Vec<&str> = Vec::new();
+    for variant in document.sections.iter().filter_map(|s| s.variant.as_deref()) {
+        if !names.contains(&variant) {
+            names.push(variant);
+        }
+    }
+    names
+}
+
+/// The document as one school sings it: each section replaced by its
+/// pathantara for `variant` where there is one, other pathantaras dropped
+///
+/// With no variant, the sections as written without one. Sangatis are kept,
+/// since they are all sung in turn.
+pub fn select(document: &VnaDocument, variant: Option<&str>) -> Result<VnaDocument> {
+    if let Some(variant) = variant {
+        let names = variant_names(document);
+        if !names.contains(&variant) {
+            return Err(anyhow!(
+                "No section has the variant '{}' (found: {})",
+                variant,
+                if names.is_empty() { "none".to_string() } else { names.join(", ") }
+            ));
+        }
+    }
+
+    let mut groups = section_groups(document);
+    groups.sort_by_key(|g| g.first());
+
+    let mut selected = document.clone();
+    selected.sections = groups
+        .iter()
+        .filter_map(|group| {
+            let pathantara = variant.and_then(|variant| {
+                group
+                    .pathantaras
+                    .iter()
+                    .copied()
+                    .find(|&p| document.sections[p].variant.as_deref() == Some(variant))
+            });
+            pathantara.or(group.base)
+        })
+        .map(|index| Section { variant: None, ..document.sections[index].clone() })
+        .collect();
+    Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    const CONTENT: &str = r#"---
+title: "Test"
+raga: "mohanam"
+tala: "+234+0+0"
+---
+
+[pallavi]
+G,G, R,,, ||
+nin- kō-- ||
+
+@sangati: 2
+G,GR R,,, ||
+nin- kō-- ||
+
+@sangati: 3
+GPGR R,,, ||
+nin- kō-- ||
+
+SRGR GRSR ||
+ri-- ---- ||
+
+[anupallavi]
+P,,, D,,, ||
+nā-- mī-- ||
+
+[pallavi@semmangudi]
+G,G, RSR, ||
+nin- kō-- ||
+
+[charanam@ariyakudi]
+S,,, R,,, ||
+sa-- ---- ||
+"#;
+
+    #[test]
+    fn test_variant_groups() {
+        let doc = parse(CONTENT).unwrap();
+
+        let groups = section_groups(&doc);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0], SectionGroup { name: "pallavi".to_string(), base: Some(0), pathantaras: vec![2] });
+        assert_eq!(groups[2], SectionGroup { name: "charanam".to_string(), base: None, pathantaras: vec![3] });
+        assert_eq!(variant_names(&doc), vec!["semmangudi", "ariyakudi"]);
+
+        let sangatis = sangati_groups(&doc.sections[0]);
+        assert_eq!(sangatis, vec![SangatiGroup { phrases: vec![0, 1, 2] }]);
+        assert!(sangati_groups(&doc.sections[1]).is_empty());
+    }
+
+    #[test]
+    fn test_select_variant() {
+        let doc = parse(CONTENT).unwrap();
+
+        let base = select(&doc, None).unwrap();
+        let headers: Vec<String> = base.sections.iter().map(|s| s.header()).collect();
+        assert_eq!(headers, vec!["pallavi", "anupallavi"]);
+        assert_eq!(base.sections[0].phrases.len(), 4);
+
+        // The school's pallavi takes the pallavi's place
+        let semmangudi = select(&doc, Some("semmangudi")).unwrap();
+        let headers: Vec<String> = semmangudi.sections.iter().map(|s| s.header()).collect();
+        assert_eq!(headers, vec!["pallavi", "anupallavi"]);
+        assert_eq!(semmangudi.sections[0].phrases[0].swaras, vec!["G,G,", "RSR,"]);
+
+        let ariyakudi = select(&doc, Some("ariyakudi")).unwrap();
+        assert_eq!(ariyakudi.sections.len(), 3);
+        assert_eq!(ariyakudi.sections[2].name, "charanam");
+
+        let error = select(&doc, Some("lalgudi")).unwrap_err();
+        assert_eq!(error.to_string(), "No section has the variant 'lalgudi' (found: semmangudi, ariyakudi)");
+    }
+}
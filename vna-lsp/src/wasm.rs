@@ -1,6 +1,7 @@
 use wasm_bindgen::prelude::*;
 use serde_wasm_bindgen::to_value;
 use crate::{parse, validate, format};
+use crate::{gamaka_sidecar, pitch_contour};
 use crate::wasm_types::{WasmVnaDocument, WasmValidationIssue};
 
 
@@ -88,7 +89,9 @@ impl VnaParser {
             ("language", false),
             ("type", false),
             ("key", false),
+            ("tuning", false),
             ("gati", false),
+            ("eduppu", false),
             ("default_octave", false),
             ("arohanam", false),
             ("avarohanam", false),
@@ -129,4 +132,32 @@ impl VnaParser {
         
         to_value(&swaras).map_err(|e| JsError::new(&e.to_string()))
     }
-}
\ No newline at end of file
+
+    /// Pitch contour of a phrase in seconds and cents from Sa, with any
+    /// drawn curves (the `.gamakas.json` content) in place of its notes
+    #[wasm_bindgen]
+    pub fn phrase_contour(
+        &self,
+        content: &str,
+        section: usize,
+        phrase: usize,
+        curves_json: Option<String>,
+    ) -> Result<JsValue, JsError> {
+        let document = parse(content).map_err(|e| JsError::new(&e.to_string()))?;
+        let curves = match curves_json {
+            Some(json) => Some(gamaka_sidecar::parse(&json).map_err(|e| JsError::new(&e.to_string()))?),
+            None => None,
+        };
+        let contour = pitch_contour::phrase_contour(&document, section, phrase, curves.as_ref())
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        to_value(&contour).map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Sample a curve segment the way the editor's `CurveSegment` does
+    #[wasm_bindgen]
+    pub fn sample_segment(&self, segment: JsValue, resolution: u32) -> Result<JsValue, JsError> {
+        let segment: pitch_contour::Segment =
+            serde_wasm_bindgen::from_value(segment).map_err(|e| JsError::new(&e.to_string()))?;
+        to_value(&segment.sample(resolution)).map_err(|e| JsError::new(&e.to_string()))
+    }
+}
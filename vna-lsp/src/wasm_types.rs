@@ -16,11 +16,22 @@ pub struct WasmMetadata {
     pub language: Option<String>,
     pub tempo: Option<u32>,
     pub gati: Option<u8>,
+    pub key: Option<String>,
+    pub tuning: Option<String>,
+    /// Frequency of the middle octave's S, from the key
+    pub tonic_hz: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WasmSection {
     pub name: String,
+    /// Pathantara of the section (`[pallavi@semmangudi]`)
+    pub variant: Option<String>,
+    pub raga: Option<String>,
+    /// Phrases have no sahitya (`@swara-only`)
+    pub swara_only: bool,
+    /// Every `@key: value` directive of the section, known or not, as `[key, value]` in file order
+    pub annotations: Vec<(String, String)>,
     pub phrases: Vec<WasmPhrase>,
 }
 
@@ -29,6 +40,41 @@ pub struct WasmPhrase {
     pub swaras: Vec<String>,
     pub sahitya: Vec<String>,
     pub phrase_analysis: Option<String>,
+    pub raga: Option<String>,
+    /// Numbered variation of the line before it
+    pub sangati: Option<u32>,
+    /// Every line-level `@key: value` directive, known or not, as `[key, value]` in file order
+    pub annotations: Vec<(String, String)>,
+    pub sahitya_lines: Vec<WasmSahityaLine>,
+    /// Gamakas written on the swaras, in order
+    pub gamakas: Vec<WasmGamaka>,
+}
+
+/// A gamaka on one note of a swara token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WasmGamaka {
+    /// Index of the swara token
+    pub token: usize,
+    /// Index of the note among the token's units
+    pub unit: usize,
+    pub swara: String,
+    /// `kampita`, `jaru` or `anuswaram`
+    pub kind: String,
+    /// `up` or `down` for a jaru
+    pub direction: Option<String>,
+    /// The grace note of an anuswaram
+    pub grace: Option<String>,
+}
+
+/// A further verse, script or meaning line under the sahitya
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WasmSahityaLine {
+    /// `sahitya` or `meaning`
+    pub kind: String,
+    pub label: Option<String>,
+    /// Tokens aligned with the swaras, or the free text as one token
+    pub tokens: Vec<String>,
+    pub aligned: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,7 +85,10 @@ pub struct WasmValidationIssue {
 }
 
 // Conversion implementations
-use crate::types::{VnaDocument, Section, Phrase, ValidationIssue, Severity};
+use crate::formatter::{line_directive_values, ordered_directives, section_directive_values};
+use crate::swara_parser::{parse_swara_notes, split_token_gati};
+use crate::tuning::Tuning;
+use crate::types::{VnaDocument, Section, Phrase, SahityaLine, Gamaka, Slide, ValidationIssue, Severity};
 
 impl From<&VnaDocument> for WasmVnaDocument {
     fn from(doc: &VnaDocument) -> Self {
@@ -52,6 +101,9 @@ impl From<&VnaDocument> for WasmVnaDocument {
                 language: doc.metadata.language.clone(),
                 tempo: doc.metadata.tempo,
                 gati: doc.metadata.gati,
+                key: doc.metadata.key.clone(),
+                tuning: doc.metadata.tuning.clone(),
+                tonic_hz: Tuning::new(&doc.metadata).tonic_hz,
             },
             sections: doc.sections.iter().map(|s| s.into()).collect(),
         }
@@ -62,6 +114,10 @@ impl From<&Section> for WasmSection {
     fn from(section: &Section) -> Self {
         WasmSection {
             name: section.name.clone(),
+            variant: section.variant.clone(),
+            raga: section.raga.clone(),
+            swara_only: section.swara_only,
+            annotations: annotations(section_directive_values(section), &section.directives),
             phrases: section.phrases.iter().map(|p| p.into()).collect(),
         }
     }
@@ -73,10 +129,63 @@ impl From<&Phrase> for WasmPhrase {
             swaras: phrase.swaras.clone(),
             sahitya: phrase.sahitya.clone(),
             phrase_analysis: phrase.phrase_analysis.clone(),
+            raga: phrase.raga.clone(),
+            sangati: phrase.sangati,
+            annotations: annotations(line_directive_values(phrase), &phrase.directives),
+            sahitya_lines: phrase.sahitya_lines.iter().map(|l| l.into()).collect(),
+            gamakas: gamakas(&phrase.swaras),
         }
     }
 }
 
+impl From<&SahityaLine> for WasmSahityaLine {
+    fn from(line: &SahityaLine) -> Self {
+        WasmSahityaLine {
+            kind: line.kind.name().to_string(),
+            label: line.label.clone(),
+            tokens: match &line.text {
+                Some(text) => vec![text.clone()],
+                None => line.syllables.clone(),
+            },
+            aligned: line.is_aligned(),
+        }
+    }
+}
+
+/// Every gamaka of a swara line; malformed tokens are left to validation
+fn gamakas(swaras: &[String]) -> Vec<WasmGamaka> {
+    let mut gamakas = Vec::new();
+    for (token, text) in swaras.iter().enumerate() {
+        let notes = parse_swara_notes(split_token_gati(text).0).unwrap_or_default();
+        for (unit, note) in notes.iter().enumerate() {
+            for gamaka in &note.gamakas {
+                gamakas.push(WasmGamaka {
+                    token,
+                    unit,
+                    swara: note.swara.clone(),
+                    kind: gamaka.name().to_string(),
+                    direction: match gamaka {
+                        Gamaka::Jaru(Slide::Up) => Some("up".to_string()),
+                        Gamaka::Jaru(Slide::Down) => Some("down".to_string()),
+                        _ => None,
+                    },
+                    grace: match gamaka {
+                        Gamaka::Anuswaram(note) => Some(note.clone()),
+                        _ => None,
+                    },
+                });
+            }
+        }
+    }
+    gamakas
+}
+
+/// Directives in the order the formatter writes them, typed values unquoted
+fn annotations(known: Vec<(&'static str, String)>, written: &[(String, String)]) -> Vec<(String, String)> {
+    let known = known.into_iter().map(|(key, value)| (key, value.trim_matches('"').to_string())).collect();
+    ordered_directives(known, written)
+}
+
 impl From<&ValidationIssue> for WasmValidationIssue {
     fn from(issue: &ValidationIssue) -> Self {
         WasmValidationIssue {